@@ -0,0 +1,51 @@
+use renderdog_automation as renderdog;
+use serde::{Deserialize, Serialize};
+
+const COUNT_DRAWS_SCRIPT: &str = r#"
+from renderdog_qrenderdoc import (
+    flatten_actions,
+    is_drawcall_like,
+    run_job,
+    with_capture_controller,
+)
+
+
+def handle_request(req):
+    def run(controller):
+        actions = flatten_actions(controller.GetRootActions())
+        return {"draw_count": sum(1 for a in actions if is_drawcall_like(a.flags))}
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job("count_draws.request", "count_draws.response", handle_request)
+    raise SystemExit(0)
+"#;
+
+#[derive(Serialize)]
+struct CountDrawsRequest {
+    capture_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountDrawsResponse {
+    draw_count: u64,
+}
+
+fn main() -> anyhow::Result<()> {
+    let capture_path = std::env::args()
+        .nth(1)
+        .ok_or_else(|| anyhow::anyhow!("usage: custom_qrenderdoc_job <capture.rdc>"))?;
+
+    let install = renderdog::RenderDocInstallation::detect()?;
+    let cwd = std::env::current_dir()?;
+    let capture_path = cwd.join(capture_path).display().to_string();
+
+    let job = renderdog::CustomQRenderDocJob::from_script("count_draws", COUNT_DRAWS_SCRIPT);
+    let res: CountDrawsResponse =
+        install.run_custom_qrenderdoc_job(&cwd, &job, &CountDrawsRequest { capture_path })?;
+
+    println!("draws: {}", res.draw_count);
+    Ok(())
+}
@@ -26,6 +26,7 @@ fn main() -> anyhow::Result<()> {
                 drawcall_scope: renderdog::DrawcallScope::default(),
                 filter: renderdog::EventFilter::default(),
                 bindings: renderdog::BindingsExportOptions::default(),
+                annotations: renderdog::AnnotationsExportOptions::default(),
                 post_actions: renderdog::CapturePostActions::default(),
             },
         },
@@ -29,6 +29,9 @@ fn main() -> anyhow::Result<()> {
                 working_dir: None,
                 artifacts_dir: None,
                 capture_template_name: Some("capture_{app}_{timestamp}_{frame}".to_string()),
+                capture_options: renderdog::CaptureLaunchOptions::default(),
+                env: Default::default(),
+                wait_for_exit: false,
             },
             trigger: renderdog::TriggerCaptureOptions::default(),
             output: renderdog::ExportOutput::default(),
@@ -36,8 +39,10 @@ fn main() -> anyhow::Result<()> {
                 drawcall_scope: renderdog::DrawcallScope::default(),
                 filter: renderdog::EventFilter::default(),
                 bindings: renderdog::BindingsExportOptions::default(),
+                annotations: renderdog::AnnotationsExportOptions::default(),
                 post_actions: renderdog::CapturePostActions::default(),
             },
+            process: renderdog::TargetProcessOptions::default(),
         },
     )?;
     println!("{}", serde_json::to_string_pretty(&res)?);
@@ -2,7 +2,13 @@ import json
 import os
 
 from renderdog_action_query import ActionFilter, is_drawcall_like, walk_actions
-from renderdog_qrenderdoc import response_path, run_job, with_capture_controller
+from renderdog_qrenderdoc import (
+    ARTIFACT_SCHEMA_VERSION,
+    read_capture_provenance,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
 
 
 REQUEST_PATH = "export_actions.request"
@@ -20,20 +26,15 @@ def handle_request(req):
         roots = controller.GetRootActions()
 
         counters = {"total_actions": 0, "drawcall_actions": 0}
-        action_filter = ActionFilter(
-            only_drawcalls=bool(req.get("only_drawcalls", False)),
-            marker_prefix=str(req.get("marker_prefix") or ""),
-            event_min=req.get("event_id_min", None),
-            event_max=req.get("event_id_max", None),
-            name_contains=req.get("name_contains") or "",
-            marker_contains=req.get("marker_contains") or "",
-            case_sensitive=bool(req.get("case_sensitive", False)),
+        action_filter = ActionFilter.from_request(
+            req, only_drawcalls=bool(req.get("only_drawcalls", False))
         )
 
         with open(actions_path, "w", encoding="utf-8") as fp:
 
             def handle_action(action) -> None:
                 rec = {
+                    "schema_version": ARTIFACT_SCHEMA_VERSION,
                     "event_id": action.event_id,
                     "parent_event_id": action.parent_event_id,
                     "depth": action.depth,
@@ -54,12 +55,16 @@ def handle_request(req):
         api = str(controller.GetAPIProperties().pipelineType)
 
         summary = {
+            "schema_version": ARTIFACT_SCHEMA_VERSION,
             "capture_path": response_path(req["capture_path"]),
             "api": api,
             "total_actions": int(counters["total_actions"]),
             "drawcall_actions": int(counters["drawcall_actions"]),
             "actions_jsonl_path": response_path(actions_path),
         }
+        provenance = read_capture_provenance(req["capture_path"])
+        if provenance is not None:
+            summary["provenance"] = provenance
 
         with open(summary_path, "w", encoding="utf-8") as fp:
             json.dump(summary, fp, ensure_ascii=False, indent=2)
@@ -69,6 +74,7 @@ def handle_request(req):
             "actions_summary_json_path": response_path(summary_path),
             "total_actions": int(counters["total_actions"]),
             "drawcall_actions": int(counters["drawcall_actions"]),
+            "provenance": provenance,
         }
 
     return with_capture_controller(req["capture_path"], run)
@@ -0,0 +1,204 @@
+import json
+import os
+
+from renderdog_action_query import ActionFilter, walk_actions
+from renderdog_qrenderdoc import (
+    ARTIFACT_SCHEMA_VERSION,
+    enum_name,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "export_annotations.request"
+RESPONSE_PATH = "export_annotations.response"
+
+SD_CONTAINER_TYPES = ("Chunk", "Struct")
+
+
+def sd_basetype(obj) -> str:
+    try:
+        return enum_name(obj.type.basetype)
+    except Exception:
+        return "Unknown"
+
+
+def sd_children(obj):
+    try:
+        return [obj.GetChild(i) for i in range(int(obj.NumChildren()))]
+    except Exception:
+        return []
+
+
+def sd_scalar(obj, basetype: str):
+    try:
+        if basetype == "Null":
+            return None
+        if basetype == "Boolean":
+            return bool(obj.AsBool())
+        if basetype in ("UnsignedInteger", "SignedInteger", "Enum"):
+            return int(obj.AsInt())
+        if basetype == "Float":
+            return float(obj.AsFloat())
+        if basetype == "Resource":
+            return str(obj.AsResourceId())
+        return str(obj.AsString())
+    except Exception:
+        return None
+
+
+def sd_value(obj):
+    basetype = sd_basetype(obj)
+    if basetype in SD_CONTAINER_TYPES:
+        return {str(child.name): sd_value(child) for child in sd_children(obj)}
+    if basetype == "Array":
+        return [sd_value(child) for child in sd_children(obj)]
+    return sd_scalar(obj, basetype)
+
+
+def flatten_annotations(obj, prefix: str, out) -> None:
+    # Annotation keys are stored as a `.`-separated hierarchy of structs; leaves
+    # (including vector arrays) become one entry each, keyed by the full path.
+    basetype = sd_basetype(obj)
+    if basetype in SD_CONTAINER_TYPES:
+        for child in sd_children(obj):
+            name = str(child.name)
+            flatten_annotations(child, f"{prefix}.{name}" if prefix else name, out)
+        return
+
+    out.append({"key": prefix, "type": basetype, "value": sd_value(obj)})
+
+
+def annotation_entries(annotations):
+    if annotations is None:
+        return []
+
+    entries = []
+    if isinstance(annotations, dict):
+        for key, value in annotations.items():
+            entries.append({"key": str(key), "type": type(value).__name__, "value": value})
+        return entries
+
+    flatten_annotations(annotations, "", entries)
+    return [entry for entry in entries if entry["key"]]
+
+
+class AnnotationSource:
+    """Locates annotations on replay objects.
+
+    Annotations only exist in captures made by RenderDoc 1.37 or newer, and the
+    replay API exposes them as structured data on descriptions. Older runtimes
+    simply report the API as unavailable instead of failing the job.
+    """
+
+    def __init__(self):
+        self.api_available = False
+
+    def lookup(self, obj):
+        if obj is None or not hasattr(obj, "annotations"):
+            return []
+        self.api_available = True
+        try:
+            return annotation_entries(obj.annotations)
+        except Exception:
+            return []
+
+    def command_annotations(self, action):
+        entries = self.lookup(action)
+        if entries:
+            return entries
+        for event in getattr(action, "events", None) or []:
+            entries = self.lookup(event)
+            if entries:
+                return entries
+        return []
+
+
+def handle_request(req):
+    os.makedirs(req["output_dir"], exist_ok=True)
+
+    annotations_path = os.path.join(
+        req["output_dir"], f"{req['basename']}.annotations.jsonl"
+    )
+    summary_path = os.path.join(
+        req["output_dir"], f"{req['basename']}.annotations_summary.json"
+    )
+
+    def run(controller):
+        structured_file = controller.GetStructuredFile()
+        roots = controller.GetRootActions()
+        source = AnnotationSource()
+
+        counters = {"object_annotations": 0, "command_annotations": 0}
+        action_filter = ActionFilter.from_request(req, only_drawcalls=False)
+
+        with open(annotations_path, "w", encoding="utf-8") as fp:
+            try:
+                resources = controller.GetResources()
+            except Exception:
+                resources = []
+
+            for res in resources:
+                entries = source.lookup(res)
+                if not entries:
+                    continue
+                rec = {
+                    "schema_version": ARTIFACT_SCHEMA_VERSION,
+                    "kind": "object",
+                    "resource_id": str(res.resourceId),
+                    "resource_name": str(res.name or ""),
+                    "resource_type": enum_name(res.type),
+                    "annotations": entries,
+                }
+                fp.write(json.dumps(rec, ensure_ascii=False) + "\n")
+                counters["object_annotations"] += 1
+
+            def handle_action(action) -> None:
+                entries = source.command_annotations(action.action)
+                if not entries:
+                    return
+                rec = {
+                    "schema_version": ARTIFACT_SCHEMA_VERSION,
+                    "kind": "command",
+                    "event_id": action.event_id,
+                    "depth": action.depth,
+                    "name": action.name,
+                    "marker_path": action.marker_path,
+                    "annotations": entries,
+                }
+                fp.write(json.dumps(rec, ensure_ascii=False) + "\n")
+                counters["command_annotations"] += 1
+
+            walk_actions(structured_file, roots, action_filter, handle_action)
+
+        api = str(controller.GetAPIProperties().pipelineType)
+
+        summary = {
+            "schema_version": ARTIFACT_SCHEMA_VERSION,
+            "capture_path": response_path(req["capture_path"]),
+            "api": api,
+            "annotations_api_available": bool(source.api_available),
+            "object_annotations": int(counters["object_annotations"]),
+            "command_annotations": int(counters["command_annotations"]),
+            "annotations_jsonl_path": response_path(annotations_path),
+        }
+
+        with open(summary_path, "w", encoding="utf-8") as fp:
+            json.dump(summary, fp, ensure_ascii=False, indent=2)
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "annotations_jsonl_path": response_path(annotations_path),
+            "annotations_summary_json_path": response_path(summary_path),
+            "annotations_api_available": bool(source.api_available),
+            "object_annotations": int(counters["object_annotations"]),
+            "command_annotations": int(counters["command_annotations"]),
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
@@ -4,7 +4,12 @@ import os
 import renderdoc as rd
 
 from renderdog_action_query import ActionFilter, walk_actions
-from renderdog_qrenderdoc import response_path, run_job, with_capture_controller
+from renderdog_qrenderdoc import (
+    ARTIFACT_SCHEMA_VERSION,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
 
 
 REQUEST_PATH = "export_bindings_index.request"
@@ -172,15 +177,7 @@ def handle_request(req):
         roots = controller.GetRootActions()
 
         counters = {"total_drawcalls": 0}
-        action_filter = ActionFilter(
-            only_drawcalls=True,
-            marker_prefix=str(req.get("marker_prefix") or ""),
-            event_min=req.get("event_id_min", None),
-            event_max=req.get("event_id_max", None),
-            name_contains=req.get("name_contains") or "",
-            marker_contains=req.get("marker_contains") or "",
-            case_sensitive=bool(req.get("case_sensitive", False)),
-        )
+        action_filter = ActionFilter.from_request(req, only_drawcalls=True)
 
         with open(bindings_path, "w", encoding="utf-8") as fp:
             include_cbuffers = bool(req.get("include_cbuffers", False))
@@ -239,6 +236,7 @@ def handle_request(req):
                             resource_names.append(cb.get("resource_name"))
 
                 rec = {
+                    "schema_version": ARTIFACT_SCHEMA_VERSION,
                     "event_id": action.event_id,
                     "depth": action.depth,
                     "name": action.name,
@@ -259,6 +257,7 @@ def handle_request(req):
         api = str(controller.GetAPIProperties().pipelineType)
 
         summary = {
+            "schema_version": ARTIFACT_SCHEMA_VERSION,
             "capture_path": response_path(req["capture_path"]),
             "api": api,
             "total_drawcalls": int(counters["total_drawcalls"]),
@@ -13,14 +13,8 @@ def handle_request(req):
 
         out_list = []
         counters = {"truncated": False, "total_matches": 0}
-        action_filter = ActionFilter(
-            bool(req.get("only_drawcalls", False)),
-            req.get("marker_prefix", None),
-            req.get("event_id_min", None),
-            req.get("event_id_max", None),
-            req.get("name_contains", None),
-            req.get("marker_contains", None),
-            bool(req.get("case_sensitive", False)),
+        action_filter = ActionFilter.from_request(
+            req, only_drawcalls=bool(req.get("only_drawcalls", False))
         )
         max_results = req.get("max_results", None)
 
@@ -0,0 +1,76 @@
+import renderdoc as rd
+
+from renderdog_action_query import index_actions_by_event
+from renderdog_qrenderdoc import (
+    ProgressReporter,
+    enum_name,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "frame_graph.request"
+RESPONSE_PATH = "frame_graph.response"
+
+
+def swapchain_ids(controller):
+    swap = getattr(rd.TextureCategory, "SwapBuffer", None)
+    if swap is None:
+        return set()
+    return {
+        int(tex.resourceId)
+        for tex in controller.GetTextures()
+        if int(tex.creationFlags) & int(swap)
+    }
+
+
+def handle_request(req):
+    def run(controller):
+        actions = index_actions_by_event(controller)
+        swapchain = swapchain_ids(controller)
+        tracked_types = (rd.ResourceType.Texture, rd.ResourceType.Buffer)
+        candidates = [res for res in controller.GetResources() if res.type in tracked_types]
+
+        resources = []
+        usages = []
+        progress = ProgressReporter("resources", len(candidates))
+        for res in candidates:
+            progress.advance()
+            resource_id = int(res.resourceId)
+            resource_usages = controller.GetUsage(res.resourceId)
+            if not resource_usages:
+                continue
+            resources.append(
+                {
+                    "resource_id": resource_id,
+                    "name": str(res.name or ""),
+                    "resource_type": enum_name(res.type),
+                    "swapchain": resource_id in swapchain,
+                }
+            )
+            for usage in resource_usages:
+                event_id = int(usage.eventId)
+                usages.append(
+                    {
+                        "resource_id": resource_id,
+                        "event_id": event_id,
+                        "marker_path": actions.get(event_id, {}).get("marker_path", []),
+                        "usage": enum_name(usage.usage),
+                    }
+                )
+        progress.finish()
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "api": str(controller.GetAPIProperties().pipelineType),
+            "resources": resources,
+            "usages": usages,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
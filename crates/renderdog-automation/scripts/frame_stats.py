@@ -0,0 +1,206 @@
+import json
+import os
+
+import renderdoc as rd
+
+from renderdog_action_query import ActionFilter, walk_actions
+from renderdog_qrenderdoc import (
+    ARTIFACT_SCHEMA_VERSION,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "frame_stats.request"
+RESPONSE_PATH = "frame_stats.response"
+
+COUNTER_KEYS = (
+    "draws",
+    "dispatches",
+    "clears",
+    "copies",
+    "indices",
+    "vertices",
+    "instances",
+    "dispatch_groups",
+    "indirect_draws",
+    "indirect_dispatches",
+    "pipeline_changes",
+    "shader_changes",
+    "render_target_changes",
+)
+
+SHADER_STAGES = (
+    rd.ShaderStage.Vertex,
+    rd.ShaderStage.Hull,
+    rd.ShaderStage.Domain,
+    rd.ShaderStage.Geometry,
+    rd.ShaderStage.Pixel,
+    rd.ShaderStage.Compute,
+)
+
+
+def new_counters():
+    return {key: 0 for key in COUNTER_KEYS}
+
+
+def has_flag(flags: int, name: str) -> bool:
+    flag = getattr(rd.ActionFlags, name, None)
+    return flag is not None and bool(flags & flag)
+
+
+def pipeline_object(pipe, is_dispatch: bool):
+    getter = "GetComputePipelineObject" if is_dispatch else "GetGraphicsPipelineObject"
+    try:
+        return str(getattr(pipe, getter)())
+    except Exception:
+        return None
+
+
+def shader_objects(pipe):
+    shaders = []
+    for stage in SHADER_STAGES:
+        try:
+            shaders.append(str(pipe.GetShader(stage)))
+        except Exception:
+            shaders.append(None)
+    return tuple(shaders)
+
+
+def render_target_objects(pipe):
+    try:
+        om = pipe.GetOutputMerger()
+        if om is None:
+            return ()
+        targets = [str(rt.resourceId) for rt in om.renderTargets]
+        targets.append(str(om.depthTarget.resourceId))
+        return tuple(targets)
+    except Exception:
+        return ()
+
+
+class StateTracker:
+    """Counts state changes between consecutive draws/dispatches in event order.
+
+    The first draw or dispatch counts as a change, since its state had to be bound.
+    """
+
+    def __init__(self, controller):
+        self.controller = controller
+        self.previous = {}
+
+    def changes(self, event_id: int, is_dispatch: bool):
+        self.controller.SetFrameEvent(event_id, False)
+        pipe = self.controller.GetPipelineState()
+
+        # Graphics and compute pipelines are bound independently, so track them separately.
+        pipeline_key = "compute_pipeline" if is_dispatch else "graphics_pipeline"
+        current = {pipeline_key: pipeline_object(pipe, is_dispatch), "shader": shader_objects(pipe)}
+        if not is_dispatch:
+            current["render_target"] = render_target_objects(pipe)
+
+        changed = {}
+        for key, value in current.items():
+            changed[key] = key not in self.previous or self.previous[key] != value
+            self.previous[key] = value
+        changed["pipeline"] = changed.pop(pipeline_key)
+        return changed
+
+
+def handle_request(req):
+    os.makedirs(req["output_dir"], exist_ok=True)
+
+    stats_path = os.path.join(req["output_dir"], f"{req['basename']}.frame_stats.json")
+
+    def run(controller):
+        structured_file = controller.GetStructuredFile()
+        roots = controller.GetRootActions()
+        tracker = (
+            StateTracker(controller) if bool(req.get("include_state_changes", True)) else None
+        )
+
+        total = new_counters()
+        passes = {}
+        action_filter = ActionFilter.from_request(req, only_drawcalls=False)
+
+        def handle_action(action) -> None:
+            flags = action.flags
+            is_draw = has_flag(flags, "Drawcall") or has_flag(flags, "MeshDispatch")
+            is_dispatch = has_flag(flags, "Dispatch") or has_flag(flags, "DispatchRay")
+            is_clear = has_flag(flags, "Clear")
+            is_copy = has_flag(flags, "Copy")
+            if not (is_draw or is_dispatch or is_clear or is_copy):
+                return
+
+            entry = passes.get(action.marker_path_joined)
+            if entry is None:
+                entry = {
+                    "marker_path": list(action.marker_path),
+                    "first_event_id": action.event_id,
+                    "last_event_id": action.event_id,
+                    **new_counters(),
+                }
+                passes[action.marker_path_joined] = entry
+            entry["last_event_id"] = action.event_id
+
+            counts = new_counters()
+            raw = action.action
+            if is_draw:
+                counts["draws"] = 1
+                num_indices = int(getattr(raw, "numIndices", 0) or 0)
+                if has_flag(flags, "Indexed"):
+                    counts["indices"] = num_indices
+                else:
+                    counts["vertices"] = num_indices
+                counts["instances"] = max(int(getattr(raw, "numInstances", 1) or 0), 1)
+                if has_flag(flags, "Indirect"):
+                    counts["indirect_draws"] = 1
+            if is_dispatch:
+                counts["dispatches"] = 1
+                groups = 1
+                for dim in getattr(raw, "dispatchDimension", None) or (0, 0, 0):
+                    groups *= int(dim)
+                counts["dispatch_groups"] = groups
+                if has_flag(flags, "Indirect"):
+                    counts["indirect_dispatches"] = 1
+            if is_clear:
+                counts["clears"] = 1
+            if is_copy:
+                counts["copies"] = 1
+
+            if tracker is not None and (is_draw or is_dispatch):
+                changed = tracker.changes(action.event_id, is_dispatch)
+                counts["pipeline_changes"] = int(changed.get("pipeline", False))
+                counts["shader_changes"] = int(changed.get("shader", False))
+                counts["render_target_changes"] = int(changed.get("render_target", False))
+
+            for counter, value in counts.items():
+                entry[counter] += value
+                total[counter] += value
+
+        walk_actions(structured_file, roots, action_filter, handle_action)
+
+        stats = {
+            "schema_version": ARTIFACT_SCHEMA_VERSION,
+            "capture_path": response_path(req["capture_path"]),
+            "api": str(controller.GetAPIProperties().pipelineType),
+            "state_changes_tracked": tracker is not None,
+            "total": total,
+            "passes": list(passes.values()),
+        }
+
+        with open(stats_path, "w", encoding="utf-8") as fp:
+            json.dump(stats, fp, ensure_ascii=False, indent=2)
+
+        return {
+            "frame_stats_json_path": response_path(stats_path),
+            "stats": stats,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
@@ -1,6 +1,8 @@
+import re
+
 import renderdoc as rd
 
-from renderdog_qrenderdoc import is_drawcall_like
+from renderdog_qrenderdoc import ProgressReporter, is_drawcall_like
 
 
 def normalize(value: str, case_sensitive: bool) -> str:
@@ -17,6 +19,112 @@ def marker_path_join(marker_path) -> str:
     return "/".join([str(x) for x in marker_path])
 
 
+def parent_marker(action):
+    # A push marker's own name ends its marker path; its parent is the entry before.
+    own = 1 if action.flags & rd.ActionFlags.PushMarker else 0
+    if len(action.marker_path) <= own:
+        return None
+    return action.marker_path[-1 - own]
+
+
+def in_range(value: int, lo, hi) -> bool:
+    return (lo is None or value >= int(lo)) and (hi is None or value <= int(hi))
+
+
+def flag_bits(names) -> int:
+    bits = 0
+    for name in names or []:
+        flag = getattr(rd.ActionFlags, str(name), None)
+        if flag is None:
+            raise ValueError(f"unknown event flag: {name}")
+        bits |= int(flag)
+    return bits
+
+
+def compile_event_query(query):
+    """Compiles the serde `EventQuery` AST into a predicate over `ActionRecord`s.
+
+    Mirrors the Rust evaluator: sub-queries are never short-circuited so `nth` counts every event.
+    """
+    op = query.get("op")
+
+    if op in ("and", "or"):
+        children = [compile_event_query(q) for q in query.get("queries") or []]
+        combine = all if op == "and" else any
+
+        def match_bool(action):
+            return combine([child(action) for child in children])
+
+        return match_bool
+
+    if op == "not":
+        inner = compile_event_query(query["query"])
+        return lambda action: not inner(action)
+
+    if op in ("name_contains", "marker_contains"):
+        case_sensitive = bool(query.get("case_sensitive", False))
+        value = normalize(query["value"], case_sensitive)
+        field = "name" if op == "name_contains" else "marker_path_joined"
+        return lambda action: value in normalize(getattr(action, field), case_sensitive)
+
+    if op in ("name_regex", "marker_regex"):
+        # Already validated with the Rust regex crate (EventQuery::matcher), whose syntax is
+        # authoritative; only the subset shared with `re` behaves identically here.
+        pattern = re.compile(str(query["pattern"]))
+        field = "name" if op == "name_regex" else "marker_path_joined"
+        return lambda action: pattern.search(getattr(action, field)) is not None
+
+    if op == "marker_prefix":
+        prefix = str(query["prefix"])
+        return lambda action: (
+            action.marker_path_joined == prefix
+            or action.marker_path_joined.startswith(prefix + "/")
+        )
+
+    if op == "parent_marker":
+        case_sensitive = bool(query.get("case_sensitive", False))
+        name = normalize(query["name"], case_sensitive)
+
+        def match_parent(action):
+            parent = parent_marker(action)
+            return parent is not None and normalize(parent, case_sensitive) == name
+
+        return match_parent
+
+    if op == "event_id_range":
+        lo, hi = query.get("min"), query.get("max")
+        return lambda action: in_range(action.event_id, lo, hi)
+
+    if op == "depth":
+        lo, hi = query.get("min"), query.get("max")
+        return lambda action: in_range(action.depth, lo, hi)
+
+    if op == "flags":
+        required = flag_bits(query.get("required"))
+        forbidden = flag_bits(query.get("forbidden"))
+        return lambda action: (
+            action.flags & required == required and action.flags & forbidden == 0
+        )
+
+    if op == "drawcall":
+        return lambda action: is_drawcall_like(action.flags)
+
+    if op == "nth":
+        index = int(query["index"])
+        inner = compile_event_query(query["query"])
+        state = {"seen": 0}
+
+        def match_nth(action):
+            if not inner(action):
+                return False
+            state["seen"] += 1
+            return state["seen"] - 1 == index
+
+        return match_nth
+
+    raise ValueError(f"unsupported event query op: {op}")
+
+
 class ActionFilter:
     def __init__(
         self,
@@ -27,6 +135,7 @@ class ActionFilter:
         name_contains: str = "",
         marker_contains: str = "",
         case_sensitive: bool = False,
+        query=None,
     ):
         self.only_drawcalls = bool(only_drawcalls)
         self.marker_prefix = str(marker_prefix or "")
@@ -35,6 +144,21 @@ class ActionFilter:
         self.case_sensitive = bool(case_sensitive)
         self.name_contains = normalize(name_contains, self.case_sensitive)
         self.marker_contains = normalize(marker_contains, self.case_sensitive)
+        self.query = compile_event_query(query) if query else None
+
+    @classmethod
+    def from_request(cls, req, only_drawcalls: bool):
+        # Reads the fields of the Rust `EventFilter`, which jobs flatten into their request.
+        return cls(
+            only_drawcalls=only_drawcalls,
+            marker_prefix=str(req.get("marker_prefix") or ""),
+            event_min=req.get("event_id_min", None),
+            event_max=req.get("event_id_max", None),
+            name_contains=req.get("name_contains") or "",
+            marker_contains=req.get("marker_contains") or "",
+            case_sensitive=bool(req.get("case_sensitive", False)),
+            query=req.get("query", None),
+        )
 
     def matches(self, action) -> bool:
         if self.marker_prefix:
@@ -62,6 +186,9 @@ class ActionFilter:
             ):
                 return False
 
+        if self.query is not None and not self.query(action):
+            return False
+
         return True
 
 
@@ -99,6 +226,10 @@ class ActionRecord:
         return cls(action, parent_event_id, depth, name, flags, marker_path)
 
 
+def count_actions(actions) -> int:
+    return sum(1 + count_actions(action.children) for action in actions)
+
+
 def walk_actions(
     structured_file,
     actions,
@@ -107,11 +238,28 @@ def walk_actions(
     marker_stack=None,
     parent_event_id=None,
     depth: int = 0,
+    progress=None,
 ):
+    if progress is None:
+        progress = ProgressReporter("actions", count_actions(actions))
+        walk_actions(
+            structured_file,
+            actions,
+            action_filter,
+            on_match,
+            marker_stack,
+            parent_event_id,
+            depth,
+            progress,
+        )
+        progress.finish()
+        return
+
     if marker_stack is None:
         marker_stack = []
 
     for action in actions:
+        progress.advance()
         record = ActionRecord.from_action(
             structured_file,
             action,
@@ -132,4 +280,25 @@ def walk_actions(
             next_marker_stack,
             record.event_id,
             depth + 1,
+            progress,
         )
+
+
+def index_actions_by_event(controller):
+    # Usage is reported per API event; map every event to the action that contains it so barriers
+    # and state changes recorded before a draw still get the draw's name and marker path.
+    by_event = {}
+
+    def handle_action(action) -> None:
+        entry = {"action_event_id": action.event_id, "name": action.name, "marker_path": action.marker_path}
+        for api_event in getattr(action.action, "events", None) or []:
+            by_event.setdefault(int(api_event.eventId), entry)
+        by_event[action.event_id] = entry
+
+    walk_actions(
+        controller.GetStructuredFile(),
+        controller.GetRootActions(),
+        ActionFilter(),
+        handle_action,
+    )
+    return by_event
@@ -1,20 +1,51 @@
 import json
 import os
+import time
 import traceback
 
 import renderdoc as rd
 
+# Written as `schema_version` into every exported record and summary. Keep in sync with
+# `ARTIFACT_SCHEMA_VERSION` in artifact_schema.rs.
+ARTIFACT_SCHEMA_VERSION = 1
+
+# Must match `renderdog::CAPTURE_PROVENANCE_KEY`, the key the in-app helper nests its JSON under.
+CAPTURE_PROVENANCE_KEY = "renderdog_provenance"
+
 
 def load_job_request(request_path):
     with open(request_path, "r", encoding="utf-8") as f:
         return json.load(f)
 
 
+class JobError(Exception):
+    """A failure the Rust side can classify; `kind` and `context` become the error payload."""
+
+    def __init__(self, kind: str, message: str, **context):
+        super().__init__(message)
+        self.kind = kind
+        self.message = message
+        self.context = context
+
+
 def write_job_response(response_path, ok: bool, result=None, error=None) -> None:
     with open(response_path, "w", encoding="utf-8") as f:
         json.dump({"ok": ok, "result": result, "error": error}, f, ensure_ascii=False)
 
 
+def job_error_payload(exc: BaseException) -> dict:
+    payload = {"traceback": traceback.format_exc()}
+    if isinstance(exc, JobError):
+        payload.update(exc.context)
+        payload["kind"] = exc.kind
+        payload["message"] = exc.message
+    else:
+        payload["kind"] = "python_exception"
+        payload["exception_type"] = type(exc).__name__
+        payload["message"] = str(exc)
+    return payload
+
+
 def response_path(path) -> str:
     value = os.fspath(path)
     if os.sep == "\\":
@@ -22,12 +53,54 @@ def response_path(path) -> str:
     return value
 
 
+_progress_path = None
+
+
+def report_progress(stage: str, current: int, total=None, message=None) -> None:
+    # Progress goes to `<job>.progress` in the run dir as JSON lines; the Rust side tails it.
+    if _progress_path is None:
+        return
+    entry = {"stage": stage, "current": int(current)}
+    if total is not None:
+        entry["total"] = int(total)
+    if message:
+        entry["message"] = str(message)
+    try:
+        with open(_progress_path, "a", encoding="utf-8") as f:
+            f.write(json.dumps(entry, ensure_ascii=False) + "\n")
+    except OSError:
+        pass
+
+
+class ProgressReporter:
+    """Counts steps of one stage, reporting at most every `interval_s` seconds."""
+
+    def __init__(self, stage: str, total=None, interval_s: float = 0.25):
+        self.stage = stage
+        self.total = total
+        self.current = 0
+        self.interval_s = interval_s
+        self.last_report = 0.0
+
+    def advance(self, step: int = 1, message=None) -> None:
+        self.current += step
+        now = time.monotonic()
+        if now - self.last_report >= self.interval_s:
+            self.last_report = now
+            report_progress(self.stage, self.current, self.total, message)
+
+    def finish(self, message=None) -> None:
+        report_progress(self.stage, self.current, self.total, message)
+
+
 def run_job(request_path, response_path, handler) -> None:
+    global _progress_path
+    _progress_path = os.path.splitext(request_path)[0] + ".progress"
     try:
         request = load_job_request(request_path)
         result = handler(request)
-    except Exception:
-        write_job_response(response_path, False, error=traceback.format_exc())
+    except Exception as exc:
+        write_job_response(response_path, False, error=job_error_payload(exc))
     else:
         write_job_response(response_path, True, result=result)
 
@@ -44,17 +117,41 @@ def with_capture_controller(capture_path, callback):
     def run():
         cap = rd.OpenCaptureFile()
         try:
+            report_progress("open_capture", 0, 1, str(capture_path))
             result = cap.OpenFile(capture_path, "", None)
             if result != rd.ResultCode.Succeeded:
-                raise RuntimeError("Couldn't open file: " + str(result))
+                raise JobError(
+                    "capture_open_failed",
+                    "Couldn't open file: " + str(result),
+                    capture_path=str(capture_path),
+                    result_code=enum_name(result),
+                )
 
             if not cap.LocalReplaySupport():
-                raise RuntimeError("Capture cannot be replayed")
+                raise JobError(
+                    "replay_unsupported",
+                    "Capture cannot be replayed",
+                    capture_path=str(capture_path),
+                    driver=str(cap.DriverName()),
+                )
 
             result, controller = cap.OpenCapture(rd.ReplayOptions(), None)
+            if result == rd.ResultCode.APIUnsupported:
+                raise JobError(
+                    "api_unsupported",
+                    "Couldn't initialise replay: " + str(result),
+                    capture_path=str(capture_path),
+                    driver=str(cap.DriverName()),
+                )
             if result != rd.ResultCode.Succeeded:
-                raise RuntimeError("Couldn't initialise replay: " + str(result))
-
+                raise JobError(
+                    "capture_open_failed",
+                    "Couldn't initialise replay: " + str(result),
+                    capture_path=str(capture_path),
+                    result_code=enum_name(result),
+                )
+
+            report_progress("open_capture", 1, 1, str(capture_path))
             try:
                 return callback(controller)
             finally:
@@ -71,6 +168,46 @@ def with_capture_controller(capture_path, callback):
     return with_replay(run)
 
 
+def enum_name(value) -> str:
+    # Enums are IntEnums in the bindings; older builds only give the repr, e.g. `ResourceType.Texture`.
+    name = getattr(value, "name", None)
+    if name:
+        return str(name)
+    return str(value).rsplit(".", 1)[-1]
+
+
+def read_capture_comments(capture_path) -> str:
+    # Comments set via the in-app SetCaptureFileComments live in the notes section as JSON.
+    cap = rd.OpenCaptureFile()
+    try:
+        if cap.OpenFile(capture_path, "", None) != rd.ResultCode.Succeeded:
+            return ""
+        section = cap.FindSectionByType(rd.SectionType.Notes)
+        if section < 0:
+            return ""
+        notes = json.loads(bytes(cap.GetSectionContents(section)).decode("utf-8"))
+        return str(notes.get("comments") or "")
+    except Exception:
+        return ""
+    finally:
+        try:
+            cap.Shutdown()
+        except Exception:
+            pass
+
+
+def read_capture_provenance(capture_path):
+    # Returns the object written by renderdog::CaptureProvenance, or None for free-form comments.
+    try:
+        comments = json.loads(read_capture_comments(capture_path).strip() or "null")
+    except ValueError:
+        return None
+    if not isinstance(comments, dict):
+        return None
+    provenance = comments.get(CAPTURE_PROVENANCE_KEY)
+    return provenance if isinstance(provenance, dict) else None
+
+
 def is_drawcall_like(flags: int) -> bool:
     return bool(
         (flags & rd.ActionFlags.Drawcall)
@@ -84,16 +221,35 @@ def set_frame_event_if_present(controller, event_id, apply_changes: bool = True)
     if event_id is None:
         return None
 
-    event_id = int(event_id)
+    event_id = check_event_id(controller, event_id)
     controller.SetFrameEvent(event_id, apply_changes)
     return event_id
 
 
+def check_event_id(controller, event_id) -> int:
+    event_id = int(event_id)
+    actions = flatten_actions(controller.GetRootActions())
+    max_event_id = max((int(action.eventId) for action in actions), default=0)
+    if event_id < 0 or event_id > max_event_id:
+        raise JobError(
+            "event_out_of_range",
+            f"event_id {event_id} out of range",
+            event_id=event_id,
+            max_event_id=max_event_id,
+        )
+    return event_id
+
+
 def get_texture_by_index(controller, texture_index):
     textures = controller.GetTextures()
     texture_index = int(texture_index)
     if texture_index < 0 or texture_index >= len(textures):
-        raise RuntimeError("texture_index out of range")
+        raise JobError(
+            "texture_index_out_of_range",
+            "texture_index out of range",
+            texture_index=texture_index,
+            texture_count=len(textures),
+        )
 
     return texture_index, textures[texture_index]
 
@@ -129,7 +285,7 @@ def resolve_event_selection(controller, event_selection: str, event_id=None) ->
     if event_selection == "event_id":
         if event_id is None:
             raise RuntimeError("event_id is required when event_selection is event_id")
-        return int(event_id)
+        return check_event_id(controller, event_id)
 
     if event_selection == "last_drawcall":
         return pick_last_drawcall_event_id(controller)
@@ -0,0 +1,95 @@
+import renderdoc as rd
+
+from renderdog_action_query import ActionFilter, walk_actions
+from renderdog_qrenderdoc import (
+    JobError,
+    report_progress,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "replay_gpu_timings.request"
+RESPONSE_PATH = "replay_gpu_timings.response"
+
+
+def counter_seconds(value, desc, capture_path) -> float:
+    # EventGPUDuration is documented as a double in seconds. Seconds is the only time unit in
+    # CounterUnit, so any other unit can't be converted without guessing.
+    unit = getattr(desc, "unit", rd.CounterUnit.Seconds)
+    if unit != rd.CounterUnit.Seconds:
+        raise JobError(
+            "counter_unit_unsupported",
+            f"EventGPUDuration is reported in {unit}, not seconds",
+            capture_path=response_path(capture_path),
+            counter="EventGPUDuration",
+            unit=str(unit),
+        )
+    is_float = desc.resultType == rd.CompType.Float
+    if desc.resultByteWidth == 8:
+        raw = value.d if is_float else value.u64
+    else:
+        raw = value.f if is_float else value.u32
+    return float(raw)
+
+
+def fetch_gpu_durations(controller, capture_path):
+    counter = rd.GPUCounter.EventGPUDuration
+    if counter not in controller.EnumerateCounters():
+        raise JobError(
+            "counter_unavailable",
+            "EventGPUDuration is not available on this replay",
+            capture_path=response_path(capture_path),
+            counter="EventGPUDuration",
+            driver=str(controller.GetAPIProperties().pipelineType),
+        )
+    desc = controller.DescribeCounter(counter)
+    report_progress("gpu_counters", 0, 1, "fetching EventGPUDuration")
+    results = controller.FetchCounters([counter])
+    report_progress("gpu_counters", 1, 1)
+    return {
+        int(result.eventId): counter_seconds(result.value, desc, capture_path) for result in results
+    }
+
+
+def handle_request(req):
+    def run(controller):
+        durations = fetch_gpu_durations(controller, req["capture_path"])
+
+        structured_file = controller.GetStructuredFile()
+        roots = controller.GetRootActions()
+        action_filter = ActionFilter.from_request(
+            req, only_drawcalls=bool(req.get("only_drawcalls", False))
+        )
+
+        events = []
+
+        def handle_action(action) -> None:
+            # Parents report the sum of their children; only leaves are timed to avoid double counting.
+            if action.num_children != 0 or action.event_id not in durations:
+                return
+            events.append(
+                {
+                    "event_id": action.event_id,
+                    "name": action.name,
+                    "marker_path": action.marker_path,
+                    "flags": action.flags,
+                    "duration_us": durations[action.event_id] * 1e6,
+                }
+            )
+
+        walk_actions(structured_file, roots, action_filter, handle_action)
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "api": str(controller.GetAPIProperties().pipelineType),
+            "events": events,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
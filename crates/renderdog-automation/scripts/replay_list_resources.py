@@ -0,0 +1,103 @@
+import renderdoc as rd
+
+from renderdog_qrenderdoc import (
+    enum_name,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "replay_list_resources.request"
+RESPONSE_PATH = "replay_list_resources.response"
+
+TEXTURE_CATEGORIES = (
+    "ShaderRead",
+    "ColorTarget",
+    "DepthTarget",
+    "ShaderReadWrite",
+    "SwapBuffer",
+)
+
+BUFFER_CATEGORIES = (
+    "Vertex",
+    "Index",
+    "Constants",
+    "ReadWrite",
+    "Indirect",
+)
+
+
+def category_names(value, enum_cls, names):
+    out = []
+    for name in names:
+        flag = getattr(enum_cls, name, None)
+        if flag is not None and int(value) & int(flag):
+            out.append(name)
+    return out
+
+
+def format_name(fmt) -> str:
+    try:
+        return str(fmt.Name())
+    except Exception:
+        return str(fmt)
+
+
+def texture_details(tex):
+    return {
+        "format": format_name(tex.format),
+        "usage_flags": category_names(tex.creationFlags, rd.TextureCategory, TEXTURE_CATEGORIES),
+        "byte_size": int(getattr(tex, "byteSize", 0) or 0),
+        "texture": {
+            "texture_type": enum_name(tex.type),
+            "width": int(tex.width),
+            "height": int(tex.height),
+            "depth": int(tex.depth),
+            "mips": int(tex.mips),
+            "array_size": int(getattr(tex, "arraysize", getattr(tex, "arraySize", 1))),
+            "ms_samp": int(getattr(tex, "msSamp", getattr(tex, "msSamples", 1))),
+        },
+    }
+
+
+def buffer_details(buf):
+    return {
+        "usage_flags": category_names(buf.creationFlags, rd.BufferCategory, BUFFER_CATEGORIES),
+        "byte_size": int(getattr(buf, "length", getattr(buf, "byteSize", 0)) or 0),
+    }
+
+
+def handle_request(req):
+    def run(controller):
+        details = {}
+        for tex in controller.GetTextures():
+            details[int(tex.resourceId)] = texture_details(tex)
+        for buf in controller.GetBuffers():
+            details[int(buf.resourceId)] = buffer_details(buf)
+
+        resources = []
+        for res in controller.GetResources():
+            resource_id = int(res.resourceId)
+            entry = {
+                "resource_id": resource_id,
+                "name": str(res.name or ""),
+                "autogenerated_name": bool(getattr(res, "autogeneratedName", False)),
+                "resource_type": enum_name(res.type),
+                "parent_ids": [int(rid) for rid in getattr(res, "parentResources", None) or []],
+                "derived_ids": [int(rid) for rid in getattr(res, "derivedResources", None) or []],
+            }
+            entry.update(details.get(resource_id, {}))
+            resources.append(entry)
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "resources": resources,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
@@ -0,0 +1,77 @@
+import renderdoc as rd
+
+from renderdog_action_query import index_actions_by_event
+from renderdog_qrenderdoc import (
+    JobError,
+    enum_name,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "replay_resource_usage.request"
+RESPONSE_PATH = "replay_resource_usage.response"
+
+
+def select_resources(controller, req):
+    resource_id = req.get("resource_id", None)
+    resource_name = req.get("resource_name", None)
+    selected = []
+    for res in controller.GetResources():
+        if resource_id is not None and int(res.resourceId) == int(resource_id):
+            selected.append(res)
+        elif resource_name is not None and str(res.name or "") == str(resource_name):
+            selected.append(res)
+    if not selected:
+        raise JobError(
+            "resource_not_found",
+            "No resource matches the request",
+            capture_path=response_path(req["capture_path"]),
+            resource=str(resource_id if resource_id is not None else resource_name),
+        )
+    return selected
+
+
+def handle_request(req):
+    def run(controller):
+        resources = select_resources(controller, req)
+        actions = index_actions_by_event(controller)
+
+        out = []
+        for res in resources:
+            usages = []
+            for usage in controller.GetUsage(res.resourceId):
+                event_id = int(usage.eventId)
+                action = actions.get(event_id, {})
+                view = usage.view
+                usages.append(
+                    {
+                        "event_id": event_id,
+                        "action_event_id": action.get("action_event_id"),
+                        "name": action.get("name", ""),
+                        "marker_path": action.get("marker_path", []),
+                        "usage": enum_name(usage.usage),
+                        "view_id": None if view == rd.ResourceId.Null() else int(view),
+                    }
+                )
+            out.append(
+                {
+                    "resource_id": int(res.resourceId),
+                    "resource_name": str(res.name or ""),
+                    "resource_type": enum_name(res.type),
+                    "usages": usages,
+                }
+            )
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "resources": out,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
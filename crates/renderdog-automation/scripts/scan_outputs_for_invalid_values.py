@@ -0,0 +1,199 @@
+import math
+import struct
+
+import renderdoc as rd
+
+from renderdog_action_query import ActionFilter, walk_actions
+from renderdog_qrenderdoc import (
+    ProgressReporter,
+    response_path,
+    run_job,
+    with_capture_controller,
+)
+
+
+REQUEST_PATH = "scan_outputs_for_invalid_values.request"
+RESPONSE_PATH = "scan_outputs_for_invalid_values.response"
+OUTPUT_KIND_COLOR = "color"
+OUTPUT_KIND_DEPTH = "depth"
+
+HISTOGRAM_BUCKETS = 256
+# Stand-in for an unset bound. The float32 histogram shader overflows `max - min` over the full
+# float range, so unset bounds stop at +-FLT_MAX/4 (about 8.5e37) and finite values beyond that are
+# counted as invalid too; the request docs say so.
+DEFAULT_RANGE = 3.4028234663852886e38 / 4
+
+
+def encode_float(value):
+    # JSON has no NaN/Inf; the Rust side reads these strings back as `FloatValue::NonFinite`.
+    value = float(value)
+    if math.isnan(value):
+        return "nan"
+    if math.isinf(value):
+        return "inf" if value > 0 else "-inf"
+    return value
+
+
+def float32_above(value: float) -> float:
+    """Smallest float32 greater than `value`, so a histogram range includes its maximum."""
+    bits = struct.unpack("<I", struct.pack("<f", value))[0]
+    if value > 0:
+        bits += 1
+    elif value < 0:
+        bits -= 1
+    else:
+        bits = 1
+    return struct.unpack("<f", struct.pack("<I", bits))[0]
+
+
+def writes_output_targets(flags: int) -> bool:
+    # Dispatches also count as drawcall-like, but on D3D11/D3D12 GetOutputTargets() at a dispatch
+    # still reports the graphics targets, which the dispatch never wrote.
+    return bool((flags & rd.ActionFlags.Drawcall) or (flags & rd.ActionFlags.MeshDispatch))
+
+
+def count_in_range(controller, rid, sub, channel: int, lo: float, hi: float) -> int:
+    # Values the histogram can't place (NaN, Inf) are either dropped or, on drivers that convert
+    # NaN to bucket index 0, counted in the first bucket. Stretching the range down by one bucket
+    # keeps bucket 0 free of in-range values, so the remaining buckets count exactly the pixels
+    # inside [lo, hi]. Which of the two a driver does isn't specified and needs a GPU replay to
+    # observe; a driver that bins NaN anywhere else would undercount NaN pixels.
+    hi = float32_above(hi)
+    bucket_width = (hi - lo) / (HISTOGRAM_BUCKETS - 1)
+    channels = [index == channel for index in range(4)]
+    buckets = controller.GetHistogram(
+        rid, sub, rd.CompType.Typeless, lo - bucket_width, hi, channels
+    )
+    return int(sum(buckets[1:]))
+
+
+def extract_resource_id(obj):
+    if obj is None:
+        return None
+    if hasattr(obj, "resourceId"):
+        return obj.resourceId
+    if hasattr(obj, "resource"):
+        return obj.resource
+    return None
+
+
+def bound_subresource(bound):
+    mip = int(getattr(bound, "firstMip", 0) or 0)
+    array_slice = int(getattr(bound, "firstSlice", 0) or 0)
+    return rd.Subresource(mip, array_slice, 0)
+
+
+class OutputScanner:
+    def __init__(self, controller, lo: float, hi: float):
+        self.controller = controller
+        self.lo = lo
+        self.hi = hi
+        self.textures = {int(tex.resourceId): tex for tex in controller.GetTextures()}
+        self.resources = {}
+
+    def scan(self, kind: str, index, bound):
+        rid = extract_resource_id(bound)
+        if rid is None or rid == rd.ResourceId.Null():
+            return None
+        tex = self.textures.get(int(rid))
+        if tex is None:
+            return None
+
+        sub = bound_subresource(bound)
+        width = max(1, int(tex.width) >> sub.mip)
+        height = max(1, int(tex.height) >> sub.mip)
+        pixels = width * height
+        num_channels = 1 if kind == OUTPUT_KIND_DEPTH else max(1, int(tex.format.compCount))
+
+        if int(rid) not in self.resources:
+            self.resources[int(rid)] = {
+                "resource_id": int(rid),
+                "name": str(self.controller.GetResourceDescription(rid).name or ""),
+                "format": str(tex.format.Name()),
+            }
+
+        min_value, max_value = self.controller.GetMinMax(rid, sub, rd.CompType.Typeless)
+        channels = []
+        for channel in range(num_channels):
+            in_range = count_in_range(self.controller, rid, sub, channel, self.lo, self.hi)
+            channels.append(
+                {
+                    "min": encode_float(min_value.floatValue[channel]),
+                    "max": encode_float(max_value.floatValue[channel]),
+                    "invalid_pixels": max(0, pixels - in_range),
+                }
+            )
+
+        return {
+            "kind": kind,
+            "index": index,
+            "resource_id": int(rid),
+            "pixels": pixels,
+            "channels": channels,
+        }
+
+
+def handle_request(req):
+    def run(controller):
+        valid_min = req.get("valid_min", None)
+        valid_max = req.get("valid_max", None)
+        scanner = OutputScanner(
+            controller,
+            float(valid_min) if valid_min is not None else -DEFAULT_RANGE,
+            float(valid_max) if valid_max is not None else DEFAULT_RANGE,
+        )
+        include_depth = bool(req.get("include_depth", True))
+
+        drawcalls = []
+        action_filter = ActionFilter.from_request(req, only_drawcalls=True)
+
+        def collect(action) -> None:
+            if writes_output_targets(action.flags):
+                drawcalls.append(action)
+
+        walk_actions(
+            controller.GetStructuredFile(),
+            controller.GetRootActions(),
+            action_filter,
+            collect,
+        )
+
+        events = []
+        progress = ProgressReporter("outputs", len(drawcalls))
+        for action in drawcalls:
+            progress.advance()
+            controller.SetFrameEvent(action.event_id, True)
+            pipe = controller.GetPipelineState()
+
+            targets = []
+            for index, bound in enumerate(pipe.GetOutputTargets()):
+                scanned = scanner.scan(OUTPUT_KIND_COLOR, index, bound)
+                if scanned is not None:
+                    targets.append(scanned)
+            if include_depth:
+                scanned = scanner.scan(OUTPUT_KIND_DEPTH, None, pipe.GetDepthTarget())
+                if scanned is not None:
+                    targets.append(scanned)
+
+            events.append(
+                {
+                    "event_id": action.event_id,
+                    "name": action.name,
+                    "marker_path": action.marker_path,
+                    "targets": targets,
+                }
+            )
+        progress.finish()
+
+        return {
+            "capture_path": response_path(req["capture_path"]),
+            "resources": list(scanner.resources.values()),
+            "events": events,
+        }
+
+    return with_capture_controller(req["capture_path"], run)
+
+
+if __name__ == "__main__":
+    run_job(REQUEST_PATH, RESPONSE_PATH, handle_request)
+    raise SystemExit(0)
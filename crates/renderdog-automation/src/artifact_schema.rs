@@ -0,0 +1,205 @@
+//! Versioned JSON Schemas for exported artifacts, generated from the typed records in
+//! [`offline`](crate::offline).
+
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ActionRecord, ActionsSummary, AnnotationRecord, AnnotationsSummary, BindingsRecord,
+    BindingsSummary, FrameGraph, FrameStats,
+};
+
+/// Version written as `schema_version` into every exported record and summary.
+///
+/// Bump it whenever a field is removed, renamed, or changes meaning; adding optional fields does
+/// not require a bump. `ARTIFACT_SCHEMA_VERSION` in `renderdog_qrenderdoc.py` must match.
+pub const ARTIFACT_SCHEMA_VERSION: u32 = 1;
+
+/// Exported artifacts whose layout this crate defines and versions.
+///
+/// `<basename>.gpu_trace.json` from `replay_gpu_timings` is deliberately not one of them: it is a
+/// Chrome trace-format file whose layout belongs to `chrome://tracing` and Perfetto, so it carries
+/// no `schema_version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Actions,
+    ActionsSummary,
+    Bindings,
+    BindingsSummary,
+    Annotations,
+    AnnotationsSummary,
+    FrameStats,
+    FrameGraph,
+}
+
+impl ArtifactKind {
+    pub const ALL: [Self; 8] = [
+        Self::Actions,
+        Self::ActionsSummary,
+        Self::Bindings,
+        Self::BindingsSummary,
+        Self::Annotations,
+        Self::AnnotationsSummary,
+        Self::FrameStats,
+        Self::FrameGraph,
+    ];
+
+    /// File name suffix after `<basename>`.
+    pub fn file_suffix(self) -> &'static str {
+        match self {
+            Self::Actions => ".actions.jsonl",
+            Self::ActionsSummary => ".summary.json",
+            Self::Bindings => ".bindings.jsonl",
+            Self::BindingsSummary => ".bindings_summary.json",
+            Self::Annotations => ".annotations.jsonl",
+            Self::AnnotationsSummary => ".annotations_summary.json",
+            Self::FrameStats => ".frame_stats.json",
+            Self::FrameGraph => ".frame_graph.json",
+        }
+    }
+
+    /// `<basename><suffix>`, the file name the exporter writes.
+    pub fn file_name(self, basename: &str) -> String {
+        format!("{basename}{}", self.file_suffix())
+    }
+
+    /// The kind whose exporter writes exactly `file_name` for `basename`.
+    pub fn from_file_name(file_name: &str, basename: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| file_name.strip_prefix(basename) == Some(kind.file_suffix()))
+    }
+
+    pub fn is_jsonl(self) -> bool {
+        matches!(self, Self::Actions | Self::Bindings | Self::Annotations)
+    }
+
+    /// Name of the checked-in schema file under `crates/renderdog-automation/schemas/`.
+    pub fn schema_file_name(self) -> &'static str {
+        match self {
+            Self::Actions => "actions.schema.json",
+            Self::ActionsSummary => "actions_summary.schema.json",
+            Self::Bindings => "bindings.schema.json",
+            Self::BindingsSummary => "bindings_summary.schema.json",
+            Self::Annotations => "annotations.schema.json",
+            Self::AnnotationsSummary => "annotations_summary.schema.json",
+            Self::FrameStats => "frame_stats.schema.json",
+            Self::FrameGraph => "frame_graph.schema.json",
+        }
+    }
+
+    /// JSON Schema for one record (JSONL) or the whole document (summaries).
+    ///
+    /// `schema_version` is required and pinned to [`ARTIFACT_SCHEMA_VERSION`], even though the Rust
+    /// readers default it to `0` for older artifacts.
+    pub fn schema(self) -> Value {
+        let schema = match self {
+            Self::Actions => schemars::schema_for!(ActionRecord),
+            Self::ActionsSummary => schemars::schema_for!(ActionsSummary),
+            Self::Bindings => schemars::schema_for!(BindingsRecord),
+            Self::BindingsSummary => schemars::schema_for!(BindingsSummary),
+            Self::Annotations => schemars::schema_for!(AnnotationRecord),
+            Self::AnnotationsSummary => schemars::schema_for!(AnnotationsSummary),
+            Self::FrameStats => schemars::schema_for!(FrameStats),
+            Self::FrameGraph => schemars::schema_for!(FrameGraph),
+        };
+        let mut schema = schema.to_value();
+        require_schema_version(&mut schema);
+        if let Some(variants) = schema.get_mut("oneOf").and_then(Value::as_array_mut) {
+            variants.iter_mut().for_each(require_schema_version);
+        }
+        schema
+    }
+}
+
+fn require_schema_version(schema: &mut Value) {
+    let Some(property) = schema
+        .pointer_mut("/properties/schema_version")
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    property.remove("default");
+    property.insert("const".into(), ARTIFACT_SCHEMA_VERSION.into());
+    if let Some(required) = schema.get_mut("required").and_then(Value::as_array_mut) {
+        required.insert(0, "schema_version".into());
+    }
+}
+
+/// Writes every artifact schema into `dir`, returning the written paths.
+pub fn write_artifact_schemas(dir: impl AsRef<Path>) -> std::io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    ArtifactKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let path = dir.join(kind.schema_file_name());
+            std::fs::write(&path, schema_file_contents(kind))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+fn schema_file_contents(kind: ArtifactKind) -> String {
+    let mut text = serde_json::to_string_pretty(&kind.schema()).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{ARTIFACT_SCHEMA_VERSION, ArtifactKind, schema_file_contents};
+
+    fn checked_in_schemas_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas")
+    }
+
+    #[test]
+    fn checked_in_schemas_match_rust_types() {
+        // Regenerate with `RENDERDOG_UPDATE_SCHEMAS=1 cargo test -p renderdog-automation`.
+        let dir = checked_in_schemas_dir();
+        if std::env::var_os("RENDERDOG_UPDATE_SCHEMAS").is_some() {
+            super::write_artifact_schemas(&dir).expect("write schemas");
+        }
+        for kind in ArtifactKind::ALL {
+            let path = dir.join(kind.schema_file_name());
+            let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(
+                checked_in,
+                schema_file_contents(kind),
+                "{} is stale; rerun with RENDERDOG_UPDATE_SCHEMAS=1",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn from_file_name_matches_whole_exporter_file_names() {
+        assert_eq!(
+            ArtifactKind::from_file_name("frame.v2.summary.json", "frame.v2"),
+            Some(ArtifactKind::ActionsSummary)
+        );
+        assert_eq!(
+            ArtifactKind::from_file_name("frame.bindings_summary.json", "frame"),
+            Some(ArtifactKind::BindingsSummary)
+        );
+        assert_eq!(
+            ArtifactKind::from_file_name("frame.gpu.summary.json", "frame"),
+            None
+        );
+    }
+
+    #[test]
+    fn python_exporter_writes_the_same_schema_version() {
+        let script = include_str!("../scripts/renderdog_qrenderdoc.py");
+        assert!(script.contains(&format!(
+            "ARTIFACT_SCHEMA_VERSION = {ARTIFACT_SCHEMA_VERSION}\n"
+        )));
+    }
+}
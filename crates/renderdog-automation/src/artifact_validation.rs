@@ -0,0 +1,282 @@
+//! Validation of exported artifact directories against the [`artifact_schema`](crate::artifact_schema)
+//! JSON Schemas.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{ARTIFACT_SCHEMA_VERSION, ArtifactKind, path_to_api_string, resolve_path_from_cwd};
+
+/// Issues reported per file before the rest are counted but dropped.
+const MAX_ISSUES_PER_FILE: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateArtifactsRequest {
+    /// Directory holding `<basename>.actions.jsonl`, summaries, and other exported artifacts.
+    pub artifacts_dir: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArtifactIssue {
+    /// 1-based line for JSONL artifacts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// JSON pointer to the offending value; empty for the whole record.
+    pub instance_path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArtifactFileReport {
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub records: u64,
+    /// Distinct `schema_version` values found; empty when the artifact predates versioning.
+    pub schema_versions: Vec<u32>,
+    pub issues: Vec<ArtifactIssue>,
+    pub total_issues: u64,
+}
+
+impl ArtifactFileReport {
+    pub fn is_valid(&self) -> bool {
+        self.total_issues == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateArtifactsResponse {
+    pub artifacts_dir: String,
+    pub expected_schema_version: u32,
+    pub valid: bool,
+    pub files: Vec<ArtifactFileReport>,
+}
+
+#[derive(Debug, Error)]
+pub enum ValidateArtifactsError {
+    #[error("failed to list {0}: {1}")]
+    ReadDir(PathBuf, std::io::Error),
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("invalid {kind:?} schema: {message}")]
+    Schema { kind: ArtifactKind, message: String },
+}
+
+/// Checks every recognized artifact in a directory against the schemas for
+/// [`ARTIFACT_SCHEMA_VERSION`].
+///
+/// A file is recognized when its name is exactly [`ArtifactKind::file_name`] for some basename.
+/// `<basename>.summary.json` is too common a name to claim on its own, so it only counts next to
+/// another artifact with the same basename (normally `<basename>.actions.jsonl`); everything else
+/// is ignored. Artifacts from an older or newer exporter fail on `schema_version`, so downstream
+/// tools can tell a breaking change from a malformed file.
+pub fn validate_artifacts(
+    cwd: &Path,
+    req: &ValidateArtifactsRequest,
+) -> Result<ValidateArtifactsResponse, ValidateArtifactsError> {
+    let dir = resolve_path_from_cwd(cwd, &req.artifacts_dir);
+    let entries =
+        std::fs::read_dir(&dir).map_err(|err| ValidateArtifactsError::ReadDir(dir.clone(), err))?;
+
+    let mut file_names = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| ValidateArtifactsError::ReadDir(dir.clone(), err))?
+            .path();
+        if let Some(name) = path.file_name().and_then(|name| name.to_str())
+            && path.is_file()
+        {
+            file_names.push(name.to_string());
+        }
+    }
+    file_names.sort();
+
+    let basenames: BTreeSet<&str> = file_names
+        .iter()
+        .flat_map(|name| {
+            ArtifactKind::ALL
+                .into_iter()
+                .filter_map(|kind| artifact_basename(kind, name))
+        })
+        .collect();
+    let files = file_names
+        .iter()
+        .filter_map(|name| {
+            basenames
+                .iter()
+                .find_map(|basename| ArtifactKind::from_file_name(name, basename))
+                .map(|kind| validate_artifact_file(&dir.join(name), kind))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ValidateArtifactsResponse {
+        artifacts_dir: path_to_api_string(&dir),
+        expected_schema_version: ARTIFACT_SCHEMA_VERSION,
+        valid: files.iter().all(ArtifactFileReport::is_valid),
+        files,
+    })
+}
+
+/// Strips `kind`'s suffix, unless the suffix is too generic to identify an exporter on its own.
+fn artifact_basename(kind: ArtifactKind, file_name: &str) -> Option<&str> {
+    if kind == ArtifactKind::ActionsSummary {
+        return None;
+    }
+    file_name
+        .strip_suffix(kind.file_suffix())
+        .filter(|basename| !basename.is_empty())
+}
+
+/// Validates one artifact file against the schema for `kind`.
+pub fn validate_artifact_file(
+    path: &Path,
+    kind: ArtifactKind,
+) -> Result<ArtifactFileReport, ValidateArtifactsError> {
+    let validator = jsonschema::validator_for(&kind.schema()).map_err(|err| {
+        ValidateArtifactsError::Schema {
+            kind,
+            message: err.to_string(),
+        }
+    })?;
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| ValidateArtifactsError::Read(path.to_path_buf(), err))?;
+
+    let mut report = ArtifactFileReport {
+        path: path_to_api_string(path),
+        kind,
+        records: 0,
+        schema_versions: Vec::new(),
+        issues: Vec::new(),
+        total_issues: 0,
+    };
+    let mut check = |line: Option<usize>, document: &str| {
+        report.records += 1;
+        let instance: Value = match serde_json::from_str(document) {
+            Ok(instance) => instance,
+            Err(err) => {
+                report.push_issue(line, String::new(), format!("invalid JSON: {err}"));
+                return;
+            }
+        };
+        if let Some(version) = instance
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            && !report.schema_versions.contains(&version)
+        {
+            report.schema_versions.push(version);
+        }
+        for err in validator.iter_errors(&instance) {
+            report.push_issue(line, err.instance_path.to_string(), err.to_string());
+        }
+    };
+
+    if kind.is_jsonl() {
+        for (index, line) in text.lines().enumerate() {
+            if !line.trim().is_empty() {
+                check(Some(index + 1), line);
+            }
+        }
+    } else {
+        check(None, &text);
+    }
+    Ok(report)
+}
+
+impl ArtifactFileReport {
+    fn push_issue(&mut self, line: Option<usize>, instance_path: String, message: String) {
+        self.total_issues += 1;
+        if self.issues.len() < MAX_ISSUES_PER_FILE {
+            self.issues.push(ArtifactIssue {
+                line,
+                instance_path,
+                message,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ValidateArtifactsRequest, validate_artifacts};
+    use crate::test_support::{action, fixture_dir};
+    use crate::{ARTIFACT_SCHEMA_VERSION, ArtifactKind};
+
+    #[test]
+    fn validate_artifacts_reports_version_mismatches_and_bad_records() {
+        let dir = fixture_dir("artifact-schema");
+        let action = |schema_version: u32, event_id: serde_json::Value| {
+            let mut record = action(1, "Draw", 0x0002, &["Frame"]);
+            record["schema_version"] = json!(schema_version);
+            record["event_id"] = event_id;
+            record.to_string()
+        };
+        std::fs::write(
+            dir.join("frame.actions.jsonl"),
+            [action(1, json!(1)), action(1, json!("two"))].join("\n"),
+        )
+        .expect("write actions");
+        std::fs::write(
+            dir.join("frame.summary.json"),
+            json!({
+                "schema_version": 1,
+                "capture_path": "/captures/frame.rdc",
+                "api": "Vulkan",
+                "total_actions": 2,
+                "drawcall_actions": 2,
+                "actions_jsonl_path": "frame.actions.jsonl",
+            })
+            .to_string(),
+        )
+        .expect("write summary");
+        std::fs::write(
+            dir.join("next.actions.jsonl"),
+            action(2, json!(1)) + "\n" + &action(1, json!(2)),
+        )
+        .expect("write next-version actions");
+        std::fs::write(dir.join("notes.txt"), "ignored").expect("write unrelated file");
+        std::fs::write(dir.join("notes.summary.json"), "{}").expect("write unrelated summary");
+
+        let res = validate_artifacts(
+            &dir,
+            &ValidateArtifactsRequest {
+                artifacts_dir: ".".into(),
+            },
+        )
+        .expect("validate artifacts");
+        assert!(!res.valid);
+        assert_eq!(res.expected_schema_version, ARTIFACT_SCHEMA_VERSION);
+
+        let kinds: Vec<_> = res.files.iter().map(|file| file.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ArtifactKind::Actions,
+                ArtifactKind::ActionsSummary,
+                ArtifactKind::Actions
+            ]
+        );
+
+        let [frame_actions, frame_summary, next_actions] = &res.files[..] else {
+            panic!("unexpected files: {:?}", res.files);
+        };
+        assert!(frame_summary.is_valid());
+        assert_eq!(frame_actions.records, 2);
+        assert_eq!(frame_actions.total_issues, 1);
+        assert_eq!(frame_actions.issues[0].line, Some(2));
+        assert_eq!(frame_actions.issues[0].instance_path, "/event_id");
+
+        assert_eq!(next_actions.schema_versions, [2, 1]);
+        assert_eq!(next_actions.total_issues, 1);
+        assert_eq!(next_actions.issues[0].instance_path, "/schema_version");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,10 @@
-use std::{ffi::OsString, path::Path};
+use std::{collections::BTreeMap, ffi::OsString, path::Path, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::process::OutputCollector;
 use crate::renderdoccmd::{
     CaptureLaunchCommand as CommandCaptureLaunchCommand,
     CaptureLaunchError as CommandCaptureLaunchError,
@@ -11,10 +12,107 @@ use crate::renderdoccmd::{
 };
 use crate::{
     CaptureInput, CaptureRef, OpenCaptureUiError, OutputFile, OutputRef, RenderDocInstallation,
-    TargetControlRef, ToolInvocationError, default_artifacts_dir, path_to_api_string,
-    resolve_path_from_cwd,
+    TargetControlRef, TargetProcessNode, ToolInvocationError, default_artifacts_dir,
+    path_to_api_string, resolve_path_from_cwd,
 };
 
+fn default_true() -> bool {
+    true
+}
+
+/// RenderDoc capture options applied at launch, mirroring `renderdog::CaptureOption`.
+///
+/// Each field maps to one of `renderdoccmd capture`'s `--opt-*` flags; defaults match RenderDoc's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CaptureLaunchOptions {
+    #[serde(default = "default_true")]
+    pub allow_vsync: bool,
+    #[serde(default = "default_true")]
+    pub allow_fullscreen: bool,
+    #[serde(default)]
+    pub api_validation: bool,
+    /// Hide API validation messages from the application's own debug output (RenderDoc still
+    /// records them). Set to `false` to let them through; only meaningful with `api_validation`.
+    #[serde(default = "default_true")]
+    pub debug_output_mute: bool,
+    #[serde(default)]
+    pub capture_callstacks: bool,
+    #[serde(default)]
+    pub capture_callstacks_only_actions: bool,
+    /// Seconds to wait after launch for a debugger to attach.
+    #[serde(default)]
+    pub delay_for_debugger_s: u32,
+    #[serde(default)]
+    pub verify_buffer_access: bool,
+    #[serde(default)]
+    pub hook_into_children: bool,
+    #[serde(default)]
+    pub ref_all_resources: bool,
+    #[serde(default)]
+    pub capture_all_cmd_lists: bool,
+    /// Soft memory limit in megabytes; `0` disables it.
+    #[serde(default)]
+    pub soft_memory_limit_mb: u32,
+}
+
+impl Default for CaptureLaunchOptions {
+    fn default() -> Self {
+        Self {
+            allow_vsync: true,
+            allow_fullscreen: true,
+            api_validation: false,
+            debug_output_mute: true,
+            capture_callstacks: false,
+            capture_callstacks_only_actions: false,
+            delay_for_debugger_s: 0,
+            verify_buffer_access: false,
+            hook_into_children: false,
+            ref_all_resources: false,
+            capture_all_cmd_lists: false,
+            soft_memory_limit_mb: 0,
+        }
+    }
+}
+
+impl CaptureLaunchOptions {
+    pub(crate) fn renderdoccmd_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        let mut flag = |enabled: bool, name: &str| {
+            if enabled {
+                args.push(OsString::from(name));
+            }
+        };
+
+        flag(!self.allow_vsync, "--opt-disallow-vsync");
+        flag(!self.allow_fullscreen, "--opt-disallow-fullscreen");
+        flag(self.api_validation, "--opt-api-validation");
+        flag(
+            self.api_validation && !self.debug_output_mute,
+            "--opt-api-validation-unmute",
+        );
+        flag(self.capture_callstacks, "--opt-capture-callstacks");
+        flag(
+            self.capture_callstacks_only_actions,
+            "--opt-capture-callstacks-only-actions",
+        );
+        flag(self.verify_buffer_access, "--opt-verify-buffer-access");
+        flag(self.hook_into_children, "--opt-hook-children");
+        flag(self.ref_all_resources, "--opt-ref-all-resources");
+        flag(self.capture_all_cmd_lists, "--opt-capture-all-cmd-lists");
+
+        if self.delay_for_debugger_s > 0 {
+            args.push(OsString::from("--opt-delay-for-debugger"));
+            args.push(OsString::from(self.delay_for_debugger_s.to_string()));
+        }
+        if self.soft_memory_limit_mb > 0 {
+            args.push(OsString::from("--opt-soft-memory-limit"));
+            args.push(OsString::from(self.soft_memory_limit_mb.to_string()));
+        }
+
+        args
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CaptureTargetRequest {
     pub executable: String,
@@ -26,15 +124,32 @@ pub struct CaptureTargetRequest {
     pub artifacts_dir: Option<String>,
     #[serde(default)]
     pub capture_template_name: Option<String>,
+    #[serde(flatten)]
+    pub capture_options: CaptureLaunchOptions,
+    /// Extra environment variables for the target, on top of this process's environment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Block until the target exits (`renderdoccmd capture --wait-for-exit`).
+    ///
+    /// Only useful when captures are taken without target control, e.g. by the application's own
+    /// in-app API; the one-shot capture workflows reject it.
+    #[serde(default)]
+    pub wait_for_exit: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CaptureLaunchReport {
     #[serde(flatten)]
     pub target: TargetControlRef,
+    /// PID of the launched application, as reported over target control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
     pub capture_file_template: Option<String>,
     pub stdout: String,
     pub stderr: String,
+    /// Launcher and child processes seen with `follow_children`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub process_tree: Vec<TargetProcessNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -74,13 +189,23 @@ impl From<CommandCaptureLaunchError> for CaptureTargetError {
     }
 }
 
-impl From<CommandCaptureLaunchOutcome> for CaptureLaunchReport {
+pub(crate) struct LaunchedCaptureTarget {
+    pub report: CaptureLaunchReport,
+    pub output: OutputCollector,
+}
+
+impl From<CommandCaptureLaunchOutcome> for LaunchedCaptureTarget {
     fn from(value: CommandCaptureLaunchOutcome) -> Self {
         Self {
-            target: TargetControlRef::new(value.target_ident),
-            capture_file_template: value.capture_file_template,
-            stdout: value.stdout,
-            stderr: value.stderr,
+            report: CaptureLaunchReport {
+                target: TargetControlRef::new(value.target_ident),
+                pid: None,
+                capture_file_template: value.capture_file_template,
+                stdout: value.stdout,
+                stderr: value.stderr,
+                process_tree: Vec::new(),
+            },
+            output: value.target_output,
         }
     }
 }
@@ -116,16 +241,51 @@ impl CaptureTargetRequest {
                     .as_deref()
                     .map(|path| resolve_path_from_cwd(cwd, path)),
                 capture_file_template,
+                options: self.capture_options.renderdoccmd_args(),
+                env: self
+                    .env
+                    .iter()
+                    .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+                    .collect(),
+                wait_for_exit: self.wait_for_exit,
             },
         })
     }
 }
 
+impl ResolvedCaptureTarget {
+    pub(crate) fn waits_for_exit(&self) -> bool {
+        self.command.wait_for_exit
+    }
+}
+
 impl RenderDocInstallation {
+    /// Launches `req.executable` under `renderdoccmd capture` and returns its target ident.
+    ///
+    /// With `wait_for_exit`, returns once the target has exited, including everything it wrote to
+    /// stdout/stderr.
+    pub fn launch_capture_target_in_cwd(
+        &self,
+        cwd: &Path,
+        req: &CaptureTargetRequest,
+    ) -> Result<CaptureLaunchReport, CaptureTargetError> {
+        let resolved = req.resolved_in_cwd(cwd)?;
+        let LaunchedCaptureTarget { mut report, output } = self.launch_capture_target(&resolved)?;
+
+        if resolved.waits_for_exit() {
+            output.wait_closed(Duration::from_secs(1));
+            let (stdout, stderr) = output.take();
+            report.stdout.push_str(&stdout);
+            report.stderr.push_str(&stderr);
+        }
+
+        Ok(report)
+    }
+
     pub(crate) fn launch_capture_target(
         &self,
         req: &ResolvedCaptureTarget,
-    ) -> Result<CaptureLaunchReport, CaptureTargetError> {
+    ) -> Result<LaunchedCaptureTarget, CaptureTargetError> {
         Ok(self.launch_capture(&req.command)?.into())
     }
 
@@ -166,38 +326,28 @@ impl RenderDocInstallation {
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        path::PathBuf,
-        time::{SystemTime, UNIX_EPOCH},
-    };
-
     use crate::renderdoccmd::CaptureLaunchError as CommandCaptureLaunchError;
+    use crate::test_support::fixture_dir;
     use serde_json::{Value, json};
 
     use super::*;
 
-    fn make_temp_dir() -> PathBuf {
-        let unique = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time before unix epoch")
-            .as_nanos();
-        let dir = std::env::temp_dir().join(format!(
-            "renderdog-automation-capture-test-{}-{unique}",
-            std::process::id()
-        ));
-        std::fs::create_dir_all(&dir).expect("failed to create temp dir");
-        dir
-    }
-
     #[test]
     fn capture_target_request_resolves_relative_paths() {
-        let cwd = make_temp_dir();
+        let cwd = fixture_dir("capture-test");
         let req = CaptureTargetRequest {
             executable: "bin/app".to_string(),
             args: vec!["--flag".to_string()],
             working_dir: Some("run".to_string()),
             artifacts_dir: Some("captures".to_string()),
             capture_template_name: Some("capture_{frame}".to_string()),
+            capture_options: CaptureLaunchOptions {
+                api_validation: true,
+                hook_into_children: true,
+                ..CaptureLaunchOptions::default()
+            },
+            env: BTreeMap::from([("RUST_LOG".to_string(), "debug".to_string())]),
+            wait_for_exit: true,
         };
 
         let resolved = req.resolved_in_cwd(&cwd).expect("resolve should succeed");
@@ -211,19 +361,34 @@ mod tests {
             Some(expected_template_path.clone())
         );
         assert!(cwd.join("captures").is_dir());
+        assert_eq!(
+            resolved.command.options,
+            vec![
+                OsString::from("--opt-api-validation"),
+                OsString::from("--opt-hook-children"),
+            ]
+        );
+        assert_eq!(
+            resolved.command.env,
+            vec![(OsString::from("RUST_LOG"), OsString::from("debug"))]
+        );
+        assert!(resolved.waits_for_exit());
 
         std::fs::remove_dir_all(&cwd).expect("cleanup should succeed");
     }
 
     #[test]
     fn capture_target_request_uses_default_artifacts_dir() {
-        let cwd = make_temp_dir();
+        let cwd = fixture_dir("capture-test");
         let req = CaptureTargetRequest {
             executable: "app".to_string(),
             args: Vec::new(),
             working_dir: None,
             artifacts_dir: None,
             capture_template_name: None,
+            capture_options: CaptureLaunchOptions::default(),
+            env: BTreeMap::new(),
+            wait_for_exit: false,
         };
 
         let resolved = req.resolved_in_cwd(&cwd).expect("resolve should succeed");
@@ -247,6 +412,45 @@ mod tests {
         assert_eq!(req.working_dir, None);
         assert_eq!(req.artifacts_dir, None);
         assert_eq!(req.capture_template_name, None);
+        assert_eq!(req.capture_options, CaptureLaunchOptions::default());
+        assert!(req.env.is_empty());
+        assert!(!req.wait_for_exit);
+    }
+
+    #[test]
+    fn capture_launch_options_map_to_renderdoccmd_flags() {
+        assert!(
+            CaptureLaunchOptions::default()
+                .renderdoccmd_args()
+                .is_empty()
+        );
+
+        let options = CaptureLaunchOptions {
+            allow_vsync: false,
+            api_validation: true,
+            debug_output_mute: false,
+            capture_callstacks: true,
+            ref_all_resources: true,
+            delay_for_debugger_s: 10,
+            soft_memory_limit_mb: 2048,
+            ..CaptureLaunchOptions::default()
+        };
+
+        assert_eq!(
+            options.renderdoccmd_args(),
+            [
+                "--opt-disallow-vsync",
+                "--opt-api-validation",
+                "--opt-api-validation-unmute",
+                "--opt-capture-callstacks",
+                "--opt-ref-all-resources",
+                "--opt-delay-for-debugger",
+                "10",
+                "--opt-soft-memory-limit",
+                "2048",
+            ]
+            .map(OsString::from)
+        );
     }
 
     #[test]
@@ -326,9 +530,11 @@ mod tests {
     fn capture_launch_report_serializes_flat_fields() {
         let response = CaptureLaunchReport {
             target: TargetControlRef::new(7),
+            pid: Some(4242),
             capture_file_template: Some("/tmp/capture.rdc".to_string()),
             stdout: "stdout".to_string(),
             stderr: "stderr".to_string(),
+            process_tree: Vec::new(),
         };
 
         let json = serde_json::to_value(response).expect("serialize response");
@@ -338,6 +544,7 @@ mod tests {
             object.get("target_ident"),
             Some(&Value::Number(7_u32.into()))
         );
+        assert_eq!(object.get("pid"), Some(&Value::Number(4242_u32.into())));
         assert_eq!(
             object.get("capture_file_template"),
             Some(&Value::String("/tmp/capture.rdc".to_string()))
@@ -1,16 +1,20 @@
 use std::{
     ffi::OsString,
     path::PathBuf,
-    process::{Command, Output},
+    process::{Command, Output, Stdio},
+    time::Duration,
 };
 
 use thiserror::Error;
 
+use crate::process::OutputCollector;
+
 #[derive(Debug, Clone)]
 pub(crate) struct CommandSpec {
     pub program: PathBuf,
     pub args: Vec<OsString>,
     pub cwd: Option<PathBuf>,
+    pub env: Vec<(OsString, OsString)>,
 }
 
 impl CommandSpec {
@@ -19,6 +23,7 @@ impl CommandSpec {
             program: program.into(),
             args: Vec::new(),
             cwd: None,
+            env: Vec::new(),
         }
     }
 
@@ -84,7 +89,7 @@ pub(crate) fn run_command_output_text(
     spec: &CommandSpec,
 ) -> Result<CommandOutputText, CommandError> {
     let mut cmd = Command::new(&spec.program);
-    cmd.args(&spec.args);
+    cmd.args(&spec.args).envs(spec.env.iter().cloned());
     if let Some(cwd) = &spec.cwd {
         cmd.current_dir(cwd);
     }
@@ -127,6 +132,65 @@ pub(crate) fn run_command_output_text(
     })
 }
 
+/// Runs a command that may hand its stdout/stderr to processes outliving it.
+///
+/// Returns once the command itself exits, with the output read so far; anything written to the
+/// pipes afterwards stays available through the returned collector.
+pub(crate) fn run_command_collecting_output(
+    spec: &CommandSpec,
+) -> Result<(CommandOutputText, OutputCollector), CommandError> {
+    let args = || {
+        spec.args
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+    };
+    let cwd = || spec.cwd.as_ref().map(|p| p.display().to_string());
+
+    let mut cmd = Command::new(&spec.program);
+    cmd.args(&spec.args)
+        .envs(spec.env.iter().cloned())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = &spec.cwd {
+        cmd.current_dir(cwd);
+    }
+
+    let spawn_error = |source| CommandError::Spawn {
+        program: spec.program.display().to_string(),
+        args: args(),
+        cwd: cwd(),
+        source,
+    };
+    let mut child = cmd.spawn().map_err(spawn_error)?;
+    let output = OutputCollector::spawn(&mut child);
+    let status = child.wait().map_err(spawn_error)?;
+
+    // Give the readers a moment to drain what the command wrote before it exited.
+    output.wait_closed(Duration::from_millis(100));
+    let (stdout, stderr) = output.take();
+
+    let Some(status) = status.code() else {
+        return Err(CommandError::NoStatusCode {
+            program: spec.program.display().to_string(),
+            args: args(),
+            cwd: cwd(),
+            stdout,
+            stderr,
+        });
+    };
+
+    Ok((
+        CommandOutputText {
+            status,
+            stdout,
+            stderr,
+        },
+        output,
+    ))
+}
+
 pub(crate) fn run_command_expect_success(
     spec: &CommandSpec,
 ) -> Result<CommandOutputText, CommandError> {
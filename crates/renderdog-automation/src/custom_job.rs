@@ -0,0 +1,232 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    RenderDocInstallation,
+    qrenderdoc_jobs::ACTION_QUERY_SUPPORT_FILES,
+    scripting::{BOOTSTRAP_SCRIPT_FILE_NAME, QRenderDocJobError, QRenderDocJobFiles},
+};
+
+/// A user-provided `qrenderdoc --python` job.
+///
+/// The script runs with the same runtime as the bundled jobs: it is copied into a fresh run dir
+/// next to the bundled `renderdog_qrenderdoc.py` and `renderdog_action_query.py` helpers, reads
+/// its request from `<name>.request` and writes its response to `<name>.response`, where `<name>`
+/// is [`CustomQRenderDocJob::name`]. The usual entry point is:
+///
+/// ```python
+/// from renderdog_qrenderdoc import run_job, with_capture_controller
+///
+/// def handle_request(req):
+///     return with_capture_controller(req["capture_path"], lambda controller: {...})
+///
+/// if __name__ == "__main__":
+///     run_job("my_job.request", "my_job.response", handle_request)
+///     raise SystemExit(0)
+/// ```
+///
+/// Errors raised as `JobError` by the helpers surface as typed
+/// [`QRenderDocScriptError`](crate::QRenderDocScriptError)s.
+#[derive(Debug, Clone)]
+pub struct CustomQRenderDocJob {
+    name: String,
+    script_content: String,
+    support_files: Vec<(String, String)>,
+}
+
+impl CustomQRenderDocJob {
+    /// Creates a job from inline script source. `name` names the script file and its
+    /// request/response files.
+    pub fn from_script(name: impl Into<String>, script_content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            script_content: script_content.into(),
+            support_files: Vec::new(),
+        }
+    }
+
+    /// Reads the script from `path`; the job is named after the file stem.
+    pub fn from_script_path(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("script path has no UTF-8 file stem: {}", path.display()),
+                )
+            })?;
+        Ok(Self::from_script(name, fs::read_to_string(path)?))
+    }
+
+    /// Adds a python module the script can import, e.g. shared helpers.
+    pub fn with_support_file(
+        mut self,
+        file_name: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.support_files.push((file_name.into(), content.into()));
+        self
+    }
+
+    /// Adds a support file read from `path`, keeping its file name.
+    pub fn with_support_file_path(self, path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "support file path has no UTF-8 file name: {}",
+                        path.display()
+                    ),
+                )
+            })?
+            .to_string();
+        let content = fs::read_to_string(path)?;
+        Ok(self.with_support_file(file_name, content))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn script_file_name(&self) -> String {
+        format!("{}.py", self.name)
+    }
+
+    fn validate(&self) -> Result<(), QRenderDocJobError> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(QRenderDocJobError::InvalidCustomJob(format!(
+                "job name must be non-empty and use only [A-Za-z0-9_-]: {:?}",
+                self.name
+            )));
+        }
+
+        let script_file_name = self.script_file_name();
+        if is_reserved_file_name(&script_file_name) {
+            return Err(QRenderDocJobError::InvalidCustomJob(format!(
+                "job name {:?} is reserved for a bundled helper",
+                self.name
+            )));
+        }
+        for (file_name, _) in &self.support_files {
+            if file_name.is_empty() || PathBuf::from(file_name).components().count() != 1 {
+                return Err(QRenderDocJobError::InvalidCustomJob(format!(
+                    "support file name must be a plain file name: {file_name:?}"
+                )));
+            }
+            if *file_name == script_file_name || is_reserved_file_name(file_name) {
+                return Err(QRenderDocJobError::InvalidCustomJob(format!(
+                    "support file {file_name:?} would overwrite the job script or a bundled helper"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn files<'a>(&'a self, script_file_name: &'a str) -> QRenderDocJobFiles<'a> {
+        let mut support_files: Vec<(&str, &str)> = ACTION_QUERY_SUPPORT_FILES
+            .iter()
+            .map(|file| (file.file_name, file.content))
+            .collect();
+        support_files.extend(
+            self.support_files
+                .iter()
+                .map(|(file_name, content)| (file_name.as_str(), content.as_str())),
+        );
+
+        QRenderDocJobFiles {
+            run_dir_prefix: &self.name,
+            script_file_name,
+            script_content: &self.script_content,
+            support_files,
+        }
+    }
+}
+
+/// Files the job runtime writes into every run dir itself.
+fn is_reserved_file_name(file_name: &str) -> bool {
+    file_name == BOOTSTRAP_SCRIPT_FILE_NAME
+        || ACTION_QUERY_SUPPORT_FILES
+            .iter()
+            .any(|bundled| bundled.file_name == file_name)
+}
+
+impl RenderDocInstallation {
+    /// Runs a custom job through `qrenderdoc --python`.
+    ///
+    /// `request` is serialized as-is. The script runs with its run dir under `cwd` as working
+    /// directory, so pass absolute paths for captures and outputs.
+    pub fn run_custom_qrenderdoc_job<TReq, TResp>(
+        &self,
+        cwd: &Path,
+        job: &CustomQRenderDocJob,
+        request: &TReq,
+    ) -> Result<TResp, QRenderDocJobError>
+    where
+        TReq: Serialize,
+        TResp: DeserializeOwned,
+    {
+        job.validate()?;
+        let script_file_name = job.script_file_name();
+        self.run_qrenderdoc_job_files(cwd, &job.files(&script_file_name), request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomQRenderDocJob;
+
+    #[test]
+    fn custom_job_ships_bundled_helpers_after_validation() {
+        let job = CustomQRenderDocJob::from_script("count_draws", "print('hi')")
+            .with_support_file("team_helpers.py", "X = 1");
+        job.validate().expect("valid job");
+
+        let script_file_name = job.script_file_name();
+        let files = job.files(&script_file_name);
+        assert_eq!(files.script_file_name, "count_draws.py");
+        assert_eq!(files.run_dir_prefix, "count_draws");
+        let names: Vec<&str> = files.support_files.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "renderdog_qrenderdoc.py",
+                "renderdog_action_query.py",
+                "team_helpers.py"
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_job_rejects_unsafe_names() {
+        for job in [
+            CustomQRenderDocJob::from_script("", ""),
+            CustomQRenderDocJob::from_script("../escape", ""),
+            CustomQRenderDocJob::from_script("ok", "").with_support_file("sub/dir.py", ""),
+            CustomQRenderDocJob::from_script("ok", "")
+                .with_support_file("renderdog_qrenderdoc.py", ""),
+            CustomQRenderDocJob::from_script("ok", "").with_support_file("ok.py", ""),
+            CustomQRenderDocJob::from_script("ok", "")
+                .with_support_file("_renderdog_qrenderdoc_bootstrap.py", ""),
+            CustomQRenderDocJob::from_script("renderdog_qrenderdoc", ""),
+            CustomQRenderDocJob::from_script("renderdog_action_query", ""),
+            CustomQRenderDocJob::from_script("_renderdog_qrenderdoc_bootstrap", ""),
+        ] {
+            assert!(job.validate().is_err(), "{job:?}");
+        }
+    }
+}
@@ -724,6 +724,7 @@ impl Drop for HandleGuard {
 
 #[cfg(test)]
 mod tests {
+    use crate::test_support::fixture_dir;
     use crate::version_policy::workspace_renderdoc_replay_version;
 
     use super::{
@@ -733,23 +734,7 @@ mod tests {
         parse_vulkan_layer_diagnosis, paths_match, split_search_path_list,
         vulkan_layer_manifest_dirs,
     };
-    use std::{
-        path::{Path, PathBuf},
-        time::{SystemTime, UNIX_EPOCH},
-    };
-
-    fn make_temp_dir() -> PathBuf {
-        let unique = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time before unix epoch")
-            .as_nanos();
-        let dir = std::env::temp_dir().join(format!(
-            "renderdog-diagnostics-test-{}-{unique}",
-            std::process::id()
-        ));
-        std::fs::create_dir_all(&dir).expect("failed to create temp dir");
-        dir
-    }
+    use std::path::Path;
 
     fn workspace_replay_version() -> &'static str {
         workspace_renderdoc_replay_version()
@@ -1054,7 +1039,7 @@ administrator privileges required"
 
     #[test]
     fn find_vulkan_layer_manifests_with_hints_only_reads_known_candidates() {
-        let root_dir = make_temp_dir();
+        let root_dir = fixture_dir("diagnostics-test");
         let home_dir = root_dir.join("home");
         let xdg_data_home = root_dir.join("xdg");
 
@@ -4,6 +4,9 @@
 //! - `renderdoccmd capture` for injection-based capture
 //! - `qrenderdoc --python` for replay/analysis/export (e.g. `.actions.jsonl`)
 //!
+//! Target control (triggering and collecting captures from a running app) is also available as a
+//! native TCP client, [`TargetControlClient`], which needs no RenderDoc tooling at all.
+//!
 //! Most failures are surfaced with detailed context (args/cwd/status/stdout/stderr) to make
 //! debugging environment issues easier.
 //!
@@ -12,28 +15,50 @@
 //!
 //! Low-level command execution and qrenderdoc scripting helpers are intentionally kept out of the
 //! public crate surface. Most consumers should use `RenderDocInstallation` plus the replay/workflow
-//! request/response types exported here.
+//! request/response types exported here; custom analysis scripts can run on the same job runtime
+//! through [`CustomQRenderDocJob`].
 
+mod artifact_schema;
+#[cfg(feature = "artifact-validation")]
+mod artifact_validation;
 mod capture;
 mod command;
+mod custom_job;
 mod diagnostics;
 mod normalize;
+mod offline;
+mod process;
+mod progress;
 mod qrenderdoc_jobs;
 mod renderdoccmd;
 mod replay;
 mod scripting;
+mod target_control;
+#[cfg(test)]
+mod test_support;
 mod toolchain;
 mod ui;
 mod version_policy;
 mod workflows;
 
+pub use artifact_schema::*;
+#[cfg(feature = "artifact-validation")]
+pub use artifact_validation::*;
 pub use capture::*;
 pub use command::ToolInvocationError;
-pub(crate) use command::{CommandSpec, run_command_expect_success, run_command_output_text};
+pub(crate) use command::{
+    CommandSpec, run_command_collecting_output, run_command_expect_success, run_command_output_text,
+};
+pub use custom_job::CustomQRenderDocJob;
 pub use diagnostics::*;
 pub(crate) use normalize::{normalize_capture_path, prepare_export_target};
+pub use offline::*;
+pub use process::{TargetExitReport, TargetProcessOptions};
+pub use progress::{QRenderDocJobProgress, QRenderDocProgressHandler};
+pub use renderdog::{CAPTURE_PROVENANCE_KEY, CaptureProvenance};
 pub use replay::*;
-pub use scripting::{QRenderDocExecutionError, QRenderDocJobError};
+pub use scripting::{QRenderDocExecutionError, QRenderDocJobError, QRenderDocScriptError};
+pub use target_control::*;
 pub use toolchain::{DetectInstallationError, RenderDocInstallation, default_artifacts_dir};
 pub(crate) use toolchain::{
     default_capture_basename, default_scripts_dir, path_to_api_string,
@@ -0,0 +1,588 @@
+//! Typed readers for exported bundle artifacts, usable without a RenderDoc installation.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{
+    CaptureProvenance, CaptureRef, DrawcallScope, EventFilter, EventFlags, EventQueryError,
+    EventQueryMatcher, FindEventsLimit, FindEventsResponse, FindEventsSummary, FoundEvent,
+    MarkerPath, fold_case, resolve_path_string_from_cwd,
+};
+
+/// One line of `<basename>.actions.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionRecord {
+    /// Artifact schema version of the exporter; `0` when read from an artifact written before
+    /// versioning.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub event_id: u32,
+    pub parent_event_id: Option<u32>,
+    pub depth: u32,
+    pub name: String,
+    pub flags: EventFlags,
+    pub marker_path: MarkerPath,
+    #[serde(default)]
+    pub num_children: u32,
+}
+
+impl From<ActionRecord> for FoundEvent {
+    fn from(record: ActionRecord) -> Self {
+        Self {
+            event_id: record.event_id,
+            parent_event_id: record.parent_event_id,
+            depth: record.depth,
+            name: record.name,
+            flags: record.flags,
+            marker_path: record.marker_path,
+        }
+    }
+}
+
+/// One line of `<basename>.bindings.jsonl`: the pipeline bindings at a draw or dispatch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BindingsRecord {
+    /// Artifact schema version of the exporter; `0` when read from an artifact written before
+    /// versioning.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub event_id: u32,
+    pub depth: u32,
+    pub name: String,
+    pub marker_path: MarkerPath,
+    /// Bound shader stages keyed by RenderDoc's stage name (e.g. `Vertex`, `Pixel`).
+    #[serde(default)]
+    pub stages: BTreeMap<String, StageBindings>,
+    #[serde(default)]
+    pub shader_names: Vec<String>,
+    #[serde(default)]
+    pub resource_names: Vec<String>,
+    /// Present when the bundle was exported with `include_outputs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<BoundOutputs>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StageBindings {
+    pub shader: BoundShader,
+    #[serde(default)]
+    pub srvs: Vec<BoundResource>,
+    #[serde(default)]
+    pub uavs: Vec<BoundResource>,
+    /// Only exported with `include_cbuffers`.
+    #[serde(default)]
+    pub cbuffers: Vec<BoundConstantBuffer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundShader {
+    pub resource_id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub entry_point: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundResource {
+    pub slot: u32,
+    /// Binding name from shader reflection.
+    #[serde(default)]
+    pub name: String,
+    pub resource_id: String,
+    #[serde(default)]
+    pub resource_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundConstantBuffer {
+    pub slot: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub resource_id: Option<String>,
+    #[serde(default)]
+    pub resource_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundOutputs {
+    #[serde(default)]
+    pub render_targets: Vec<BoundRenderTarget>,
+    #[serde(default)]
+    pub depth_target: Option<BoundDepthTarget>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundRenderTarget {
+    pub index: u32,
+    pub resource_id: String,
+    #[serde(default)]
+    pub resource_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BoundDepthTarget {
+    pub resource_id: String,
+    #[serde(default)]
+    pub resource_name: String,
+}
+
+/// One line of `<basename>.annotations.jsonl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnnotationRecord {
+    /// Annotations attached to a resource.
+    Object {
+        #[serde(default)]
+        #[schemars(required, extend("const" = crate::ARTIFACT_SCHEMA_VERSION))]
+        schema_version: u32,
+        resource_id: String,
+        #[serde(default)]
+        resource_name: String,
+        #[serde(default)]
+        resource_type: String,
+        annotations: Vec<AnnotationEntry>,
+    },
+    /// Annotations attached to the command at an event.
+    Command {
+        #[serde(default)]
+        #[schemars(required, extend("const" = crate::ARTIFACT_SCHEMA_VERSION))]
+        schema_version: u32,
+        event_id: u32,
+        depth: u32,
+        name: String,
+        marker_path: MarkerPath,
+        annotations: Vec<AnnotationEntry>,
+    },
+}
+
+/// One flattened annotation; `key` is the full `.`-separated path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationEntry {
+    pub key: String,
+    /// Structured-data base type (e.g. `UnsignedInteger`, `String`, `Array`).
+    #[serde(rename = "type")]
+    pub value_type: String,
+    pub value: serde_json::Value,
+}
+
+/// `<basename>.summary.json`, written next to the actions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionsSummary {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub capture_path: String,
+    pub api: String,
+    pub total_actions: u64,
+    pub drawcall_actions: u64,
+    pub actions_jsonl_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<CaptureProvenance>,
+}
+
+/// `<basename>.bindings_summary.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BindingsSummary {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub capture_path: String,
+    pub api: String,
+    pub total_drawcalls: u64,
+    pub bindings_jsonl_path: String,
+}
+
+/// `<basename>.annotations_summary.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationsSummary {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub capture_path: String,
+    pub api: String,
+    pub annotations_api_available: bool,
+    pub object_annotations: u64,
+    pub command_annotations: u64,
+    pub annotations_jsonl_path: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ArtifactReadError {
+    #[error("failed to open {0}: {1}")]
+    Open(PathBuf, std::io::Error),
+    #[error("failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("failed to parse {path} line {line}: {source}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("failed to parse {0}: {1}")]
+    ParseDocument(PathBuf, serde_json::Error),
+}
+
+/// Streams records from a JSONL artifact one line at a time; blank lines are skipped.
+pub struct JsonlRecords<T> {
+    path: PathBuf,
+    lines: std::io::Lines<BufReader<std::fs::File>>,
+    line: usize,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> JsonlRecords<T> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArtifactReadError> {
+        let path = path.as_ref().to_path_buf();
+        let file =
+            std::fs::File::open(&path).map_err(|err| ArtifactReadError::Open(path.clone(), err))?;
+        Ok(Self {
+            path,
+            lines: BufReader::new(file).lines(),
+            line: 0,
+            _record: PhantomData,
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonlRecords<T> {
+    type Item = Result<T, ArtifactReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(ArtifactReadError::Read(self.path.clone(), err))),
+            };
+            self.line += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                serde_json::from_str(&line).map_err(|source| ArtifactReadError::Parse {
+                    path: self.path.clone(),
+                    line: self.line,
+                    source,
+                }),
+            );
+        }
+    }
+}
+
+pub fn read_actions_jsonl(
+    path: impl AsRef<Path>,
+) -> Result<JsonlRecords<ActionRecord>, ArtifactReadError> {
+    JsonlRecords::open(path)
+}
+
+pub fn read_bindings_jsonl(
+    path: impl AsRef<Path>,
+) -> Result<JsonlRecords<BindingsRecord>, ArtifactReadError> {
+    JsonlRecords::open(path)
+}
+
+pub fn read_annotations_jsonl(
+    path: impl AsRef<Path>,
+) -> Result<JsonlRecords<AnnotationRecord>, ArtifactReadError> {
+    JsonlRecords::open(path)
+}
+
+/// Reads a single-document artifact such as [`ActionsSummary`].
+pub fn read_artifact_json<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> Result<T, ArtifactReadError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|err| ArtifactReadError::Read(path.into(), err))?;
+    serde_json::from_slice(&bytes).map_err(|err| ArtifactReadError::ParseDocument(path.into(), err))
+}
+
+/// [`FindEventsRequest`](crate::FindEventsRequest) over an exported `actions.jsonl` instead of a
+/// capture.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindEventsOfflineRequest {
+    pub actions_jsonl_path: String,
+    #[serde(flatten)]
+    pub drawcall_scope: DrawcallScope,
+    #[serde(flatten)]
+    pub filter: EventFilter,
+    #[serde(flatten)]
+    pub limit: FindEventsLimit,
+}
+
+#[derive(Debug, Error)]
+pub enum FindEventsOfflineError {
+    #[error(transparent)]
+    Read(#[from] ArtifactReadError),
+    #[error("invalid query: {0}")]
+    Query(#[from] EventQueryError),
+}
+
+/// Applies the same filter as the `find_events` qrenderdoc job to exported actions.
+///
+/// The capture path is taken from the `<basename>.summary.json` written next to the actions, when
+/// present.
+pub fn find_events_offline(
+    cwd: &Path,
+    req: &FindEventsOfflineRequest,
+) -> Result<FindEventsResponse, FindEventsOfflineError> {
+    let actions_path = resolve_path_string_from_cwd(cwd, &req.actions_jsonl_path);
+    let mut filter = OfflineEventFilter::new(&req.filter, req.drawcall_scope)?;
+
+    let mut matches = Vec::new();
+    let mut summary = FindEventsSummary {
+        total_matches: 0,
+        truncated: false,
+        first_event_id: None,
+        last_event_id: None,
+    };
+    for record in read_actions_jsonl(&actions_path)? {
+        let event = FoundEvent::from(record?);
+        if !filter.matches(&event) {
+            continue;
+        }
+        summary.total_matches += 1;
+        summary.first_event_id.get_or_insert(event.event_id);
+        summary.last_event_id = Some(event.event_id);
+        if req
+            .limit
+            .max_results
+            .is_none_or(|max| matches.len() < max as usize)
+        {
+            matches.push(event);
+        } else {
+            summary.truncated = true;
+        }
+    }
+
+    Ok(FindEventsResponse {
+        capture: CaptureRef::new(capture_path_from_summary(&actions_path).unwrap_or_default()),
+        summary,
+        matches,
+    })
+}
+
+fn capture_path_from_summary(actions_path: &str) -> Option<String> {
+    let summary_path = actions_path.strip_suffix(".actions.jsonl")?.to_string() + ".summary.json";
+    let summary: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(summary_path).ok()?).ok()?;
+    summary.get("capture_path")?.as_str().map(str::to_string)
+}
+
+/// Rust counterpart of `ActionFilter` in `renderdog_action_query.py`.
+struct OfflineEventFilter<'a> {
+    filter: &'a EventFilter,
+    only_drawcalls: bool,
+    name_contains: String,
+    marker_contains: String,
+    query: Option<EventQueryMatcher>,
+}
+
+impl<'a> OfflineEventFilter<'a> {
+    fn new(filter: &'a EventFilter, scope: DrawcallScope) -> Result<Self, EventQueryError> {
+        let fold = |value: &Option<String>| {
+            fold_case(value.as_deref().unwrap_or_default(), filter.case_sensitive)
+        };
+        Ok(Self {
+            filter,
+            only_drawcalls: scope.only_drawcalls,
+            name_contains: fold(&filter.name_contains),
+            marker_contains: fold(&filter.marker_contains),
+            query: filter
+                .query
+                .as_ref()
+                .map(|query| query.matcher())
+                .transpose()?,
+        })
+    }
+
+    fn matches(&mut self, event: &FoundEvent) -> bool {
+        let marker_path = event.marker_path.joined();
+        if let Some(prefix) = self
+            .filter
+            .marker_prefix
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            && marker_path != prefix
+            && !marker_path.starts_with(&format!("{prefix}/"))
+        {
+            return false;
+        }
+        if self.only_drawcalls && !event.flags.is_drawcall_like() {
+            return false;
+        }
+        if self
+            .filter
+            .event_id_min
+            .is_some_and(|min| event.event_id < min)
+            || self
+                .filter
+                .event_id_max
+                .is_some_and(|max| event.event_id > max)
+        {
+            return false;
+        }
+        if !self.name_contains.is_empty()
+            && !fold_case(&event.name, self.filter.case_sensitive).contains(&self.name_contains)
+        {
+            return false;
+        }
+        if !self.marker_contains.is_empty()
+            && !fold_case(&marker_path, self.filter.case_sensitive).contains(&self.marker_contains)
+        {
+            return false;
+        }
+        self.query.as_mut().is_none_or(|query| query.matches(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        ArtifactReadError, FindEventsOfflineRequest, find_events_offline, read_actions_jsonl,
+        read_bindings_jsonl,
+    };
+    use crate::test_support::{action, fixture_dir, frame_actions, write_jsonl};
+    use crate::{DrawcallScope, EventFilter, EventQuery, FindEventsLimit};
+
+    fn write_bundle(dir: &std::path::Path) {
+        write_jsonl(&dir.join("frame.actions.jsonl"), &frame_actions());
+        std::fs::write(
+            dir.join("frame.summary.json"),
+            json!({"capture_path": "/captures/frame.rdc"}).to_string(),
+        )
+        .expect("write summary");
+    }
+
+    #[test]
+    fn find_events_offline_applies_filter_scope_and_limit() {
+        let dir = fixture_dir("offline-find");
+        write_bundle(&dir);
+
+        let res = find_events_offline(
+            &dir,
+            &FindEventsOfflineRequest {
+                actions_jsonl_path: "frame.actions.jsonl".into(),
+                drawcall_scope: DrawcallScope {
+                    only_drawcalls: true,
+                },
+                filter: EventFilter {
+                    marker_contains: Some("OPAQUE".into()),
+                    query: Some(EventQuery::Not {
+                        query: Box::new(EventQuery::Flags {
+                            required: vec!["Instanced".into()],
+                            forbidden: Vec::new(),
+                        }),
+                    }),
+                    ..EventFilter::default()
+                },
+                limit: FindEventsLimit::default(),
+            },
+        )
+        .expect("find events offline");
+        assert_eq!(res.capture.capture_path, "/captures/frame.rdc");
+        assert_eq!(res.summary.total_matches, 2);
+        assert_eq!(
+            res.matches.iter().map(|e| e.event_id).collect::<Vec<_>>(),
+            [5, 6]
+        );
+
+        let res = find_events_offline(
+            &dir,
+            &FindEventsOfflineRequest {
+                actions_jsonl_path: dir.join("frame.actions.jsonl").display().to_string(),
+                drawcall_scope: DrawcallScope::default(),
+                filter: EventFilter {
+                    marker_prefix: Some("Opaque".into()),
+                    ..EventFilter::default()
+                },
+                limit: FindEventsLimit {
+                    max_results: Some(2),
+                },
+            },
+        )
+        .expect("find events offline");
+        assert_eq!(res.summary.total_matches, 4);
+        assert!(res.summary.truncated);
+        assert_eq!(res.summary.last_event_id, Some(6));
+        assert_eq!(
+            res.matches.iter().map(|e| e.event_id).collect::<Vec<_>>(),
+            [3, 4]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_bindings_jsonl_parses_stages_and_outputs() {
+        let dir = fixture_dir("offline-bindings");
+        let path = dir.join("frame.bindings.jsonl");
+        write_jsonl(
+            &path,
+            &[json!({
+                "event_id": 4,
+                "depth": 1,
+                "name": "DrawIndexedInstanced(36, 8)",
+                "marker_path": ["Opaque"],
+                "stages": {
+                    "Pixel": {
+                        "shader": {"resource_id": "ResourceId::12", "name": "ps_main", "entry_point": "main"},
+                        "srvs": [{"slot": 0, "name": "albedo", "resource_id": "ResourceId::40", "resource_name": "Albedo"}],
+                        "uavs": [],
+                        "cbuffers": [{"slot": 0, "name": "Globals", "size": 256, "resource_id": null, "resource_name": ""}],
+                    },
+                },
+                "shader_names": ["ps_main", "main"],
+                "resource_names": ["albedo", "Albedo"],
+                "outputs": {
+                    "render_targets": [{"index": 0, "resource_id": "ResourceId::50", "resource_name": "GBuffer0"}],
+                    "depth_target": null,
+                },
+            })],
+        );
+
+        let records = read_bindings_jsonl(&path)
+            .expect("open bindings")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("parse bindings");
+        assert_eq!(records.len(), 1);
+        let pixel = &records[0].stages["Pixel"];
+        assert_eq!(pixel.shader.name, "ps_main");
+        assert_eq!(pixel.srvs[0].resource_name, "Albedo");
+        assert_eq!(pixel.cbuffers[0].resource_id, None);
+        let outputs = records[0].outputs.as_ref().expect("outputs");
+        assert_eq!(outputs.render_targets[0].resource_name, "GBuffer0");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_actions_jsonl_reports_the_bad_line() {
+        let dir = fixture_dir("offline-bad");
+        let path = dir.join("frame.actions.jsonl");
+        std::fs::write(
+            &path,
+            format!("{}\n\n{{not json\n", action(1, "Draw", 0x0002, &[])),
+        )
+        .expect("write fixture");
+
+        let results: Vec<_> = read_actions_jsonl(&path).expect("open actions").collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(ArtifactReadError::Parse { line: 3, .. })
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
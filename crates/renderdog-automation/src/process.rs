@@ -0,0 +1,579 @@
+#[cfg(windows)]
+use std::process::{Command, Stdio};
+use std::{
+    io::Read,
+    process::Child,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn default_terminate_grace_ms() -> u64 {
+    5_000
+}
+
+/// What to do with the launched application once captures and exports are done.
+///
+/// The target is identified by the PID it reports over target control, so these options only take
+/// effect once a capture connection succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TargetProcessOptions {
+    /// Ask the target to exit after the workflow finishes, then kill it if it is still running
+    /// after `terminate_grace_ms`.
+    #[serde(default)]
+    pub terminate_after_capture: bool,
+    #[serde(default = "default_terminate_grace_ms")]
+    pub terminate_grace_ms: u64,
+    /// Wait up to this long for the target to exit on its own (before terminating, if enabled).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for_exit_s: Option<u32>,
+}
+
+impl Default for TargetProcessOptions {
+    fn default() -> Self {
+        Self {
+            terminate_after_capture: false,
+            terminate_grace_ms: default_terminate_grace_ms(),
+            wait_for_exit_s: None,
+        }
+    }
+}
+
+impl TargetProcessOptions {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.terminate_after_capture || self.wait_for_exit_s.is_some()
+    }
+}
+
+/// Final state of the launched application.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TargetExitReport {
+    pub pid: u32,
+    pub exited: bool,
+    /// Whether the workflow asked the target to exit.
+    pub terminated: bool,
+    /// Whether the target had to be killed after ignoring the graceful request.
+    pub forced: bool,
+    /// Known on Windows only: on Unix the exit status goes to the target's parent process.
+    pub exit_code: Option<i32>,
+    /// Output the target wrote after launch, when it inherited `renderdoccmd`'s console.
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Collects a command's stdout/stderr on background threads.
+///
+/// `renderdoccmd capture` exits as soon as the target is injected, but the target may inherit its
+/// pipes and keep writing to them, so output is gathered until every writer has closed the pipe.
+/// Once the collector is dropped the readers keep draining the pipes, so the target never blocks
+/// on a full pipe, but discard what they read.
+pub(crate) struct OutputCollector {
+    stdout: Arc<Mutex<Vec<u8>>>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+    discard: Arc<AtomicBool>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl OutputCollector {
+    pub(crate) fn spawn(child: &mut Child) -> Self {
+        let stdout = Arc::new(Mutex::new(Vec::new()));
+        let stderr = Arc::new(Mutex::new(Vec::new()));
+        let discard = Arc::new(AtomicBool::new(false));
+        let mut readers = Vec::new();
+
+        if let Some(pipe) = child.stdout.take() {
+            readers.push(spawn_reader(
+                pipe,
+                Arc::clone(&stdout),
+                Arc::clone(&discard),
+            ));
+        }
+        if let Some(pipe) = child.stderr.take() {
+            readers.push(spawn_reader(
+                pipe,
+                Arc::clone(&stderr),
+                Arc::clone(&discard),
+            ));
+        }
+
+        Self {
+            stdout,
+            stderr,
+            discard,
+            readers,
+        }
+    }
+
+    /// Waits up to `timeout` for all writers to close the pipes.
+    pub(crate) fn wait_closed(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.readers.iter().all(JoinHandle::is_finished) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Returns and clears everything read so far.
+    pub(crate) fn take(&self) -> (String, String) {
+        (drain_text(&self.stdout), drain_text(&self.stderr))
+    }
+}
+
+impl Drop for OutputCollector {
+    fn drop(&mut self) {
+        self.discard.store(true, Ordering::Relaxed);
+        self.take();
+    }
+}
+
+fn spawn_reader(
+    mut pipe: impl Read + Send + 'static,
+    sink: Arc<Mutex<Vec<u8>>>,
+    discard: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut chunk = [0_u8; 4096];
+        while let Ok(read) = pipe.read(&mut chunk) {
+            if read == 0 {
+                break;
+            }
+            if discard.load(Ordering::Relaxed) {
+                continue;
+            }
+            sink.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend_from_slice(&chunk[..read]);
+        }
+    })
+}
+
+fn drain_text(buffer: &Mutex<Vec<u8>>) -> String {
+    let bytes = std::mem::take(&mut *buffer.lock().unwrap_or_else(PoisonError::into_inner));
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A running process identified by PID, e.g. a target reported by target control.
+///
+/// The process is never reaped: it was started by `renderdoccmd` (or belongs to whoever embeds this
+/// crate), and `waitpid` would steal its exit status from its actual parent.
+pub(crate) struct TargetProcess {
+    pid: u32,
+    #[cfg(windows)]
+    handle: Option<isize>,
+}
+
+impl TargetProcess {
+    /// Fails if the process exists but can't be opened, e.g. for lack of access rights.
+    pub(crate) fn attach(pid: u32) -> Result<Self, std::io::Error> {
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::Foundation::ERROR_INVALID_PARAMETER;
+            use windows_sys::Win32::System::Threading::{
+                OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE,
+            };
+
+            // Holding a handle keeps the exit code available after the process is gone.
+            let handle = unsafe {
+                OpenProcess(
+                    PROCESS_TERMINATE | PROCESS_QUERY_LIMITED_INFORMATION,
+                    0,
+                    pid,
+                )
+            };
+            if handle == 0 {
+                let err = std::io::Error::last_os_error();
+                // No process with that PID: it already exited.
+                if err.raw_os_error() != Some(ERROR_INVALID_PARAMETER as i32) {
+                    return Err(err);
+                }
+            }
+            Ok(Self {
+                pid,
+                handle: (handle != 0).then_some(handle),
+            })
+        }
+
+        #[cfg(unix)]
+        {
+            Ok(Self { pid })
+        }
+    }
+
+    pub(crate) fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        #[cfg(windows)]
+        {
+            match self.handle {
+                Some(_) => self.exit_code().is_none(),
+                None => false,
+            }
+        }
+
+        #[cfg(unix)]
+        {
+            // Signal 0 only checks existence; EPERM means it exists but belongs to someone else.
+            let exists = (unsafe { libc::kill(self.pid as libc::pid_t, 0) }) == 0
+                || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+            exists && !self.is_zombie()
+        }
+    }
+
+    /// Polls until the process exits or `timeout` elapses; returns whether it exited.
+    pub(crate) fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !self.is_running() {
+                return true;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            std::thread::sleep(remaining.min(POLL_INTERVAL));
+        }
+    }
+
+    /// Asks the process to exit (SIGTERM, or a close request on Windows).
+    pub(crate) fn request_exit(&self) -> Result<(), std::io::Error> {
+        #[cfg(windows)]
+        let requested = Command::new("taskkill")
+            .arg("/PID")
+            .arg(self.pid.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+
+        #[cfg(unix)]
+        let requested = self.signal(libc::SIGTERM);
+
+        // The process may exit between the liveness check and the signal; that is not an error.
+        if requested || !self.is_running() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!(
+                "failed to ask process {} to exit",
+                self.pid
+            )))
+        }
+    }
+
+    pub(crate) fn kill(&self) -> Result<(), std::io::Error> {
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::Threading::TerminateProcess;
+
+            let Some(handle) = self.handle else {
+                return Ok(());
+            };
+            if unsafe { TerminateProcess(handle, 1) } == 0 && self.is_running() {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        {
+            if self.signal(libc::SIGKILL) || !self.is_running() {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        }
+    }
+
+    /// An exited process whose parent hasn't reaped it yet still answers signal 0. Its parent may
+    /// never do so, e.g. an init process in a container that doesn't reap re-parented orphans.
+    #[cfg(unix)]
+    fn is_zombie(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            // The state follows the parenthesised command name, which may itself contain spaces.
+            std::fs::read_to_string(format!("/proc/{}/stat", self.pid))
+                .ok()
+                .and_then(|stat| {
+                    let (_, rest) = stat.rsplit_once(')')?;
+                    rest.split_whitespace().next().map(|state| state == "Z")
+                })
+                .unwrap_or(false)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            false
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> bool {
+        unsafe { libc::kill(self.pid as libc::pid_t, signal) == 0 }
+    }
+
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::Threading::GetExitCodeProcess;
+
+            const STILL_ACTIVE: u32 = 259;
+
+            let handle = self.handle?;
+            let mut code = 0_u32;
+            if unsafe { GetExitCodeProcess(handle, &mut code) } == 0 || code == STILL_ACTIVE {
+                return None;
+            }
+            Some(code as i32)
+        }
+
+        #[cfg(unix)]
+        {
+            None
+        }
+    }
+
+    /// Asks the process to exit and kills it if it is still running after `grace`.
+    ///
+    /// Returns whether the process had to be killed. A failed exit request is not fatal: the
+    /// process is killed instead.
+    pub(crate) fn terminate(&self, grace: Duration) -> Result<bool, std::io::Error> {
+        if self.request_exit().is_ok() && self.wait_timeout(grace) {
+            return Ok(false);
+        }
+        self.kill()?;
+        Ok(true)
+    }
+}
+
+#[cfg(windows)]
+impl Drop for TargetProcess {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle {
+            unsafe {
+                windows_sys::Win32::Foundation::CloseHandle(handle);
+            }
+        }
+    }
+}
+
+/// Terminates a launched target when dropped, unless disarmed first.
+///
+/// Guards the window between launching a target and handing it over to
+/// [`finish_target_process`], so a failed trigger doesn't leave the application running.
+pub(crate) struct TerminateOnDrop {
+    pid: Option<u32>,
+    grace: Duration,
+}
+
+impl TerminateOnDrop {
+    pub(crate) fn new(pid: Option<u32>, grace: Duration) -> Self {
+        Self { pid, grace }
+    }
+
+    pub(crate) fn disarm(mut self) {
+        self.pid = None;
+    }
+}
+
+impl Drop for TerminateOnDrop {
+    fn drop(&mut self) {
+        // Best effort: the error that triggered the drop is the one worth reporting.
+        if let Some(process) = self.pid.and_then(|pid| TargetProcess::attach(pid).ok())
+            && process.is_running()
+        {
+            let _ = process.terminate(self.grace);
+        }
+    }
+}
+
+/// Applies `options` to the target with `pid` and collects the rest of its output.
+pub(crate) fn finish_target_process(
+    pid: u32,
+    options: &TargetProcessOptions,
+    output: &OutputCollector,
+) -> Result<TargetExitReport, std::io::Error> {
+    let process = TargetProcess::attach(pid)?;
+
+    let mut exited = match options.wait_for_exit_s {
+        Some(seconds) => process.wait_timeout(Duration::from_secs(u64::from(seconds))),
+        None => !process.is_running(),
+    };
+
+    let mut terminated = false;
+    let mut forced = false;
+    if !exited && options.terminate_after_capture {
+        terminated = true;
+        forced = process.terminate(Duration::from_millis(options.terminate_grace_ms))?;
+        exited = !forced || process.wait_timeout(Duration::from_secs(5));
+    }
+
+    // Once the target is gone the inherited pipes close; don't block on output otherwise.
+    if exited {
+        output.wait_closed(Duration::from_secs(1));
+    }
+    let (stdout, stderr) = output.take();
+
+    Ok(TargetExitReport {
+        pid: process.pid(),
+        exited,
+        terminated,
+        forced,
+        exit_code: process.exit_code(),
+        stdout,
+        stderr,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        os::unix::process::ExitStatusExt,
+        process::{Command, Stdio},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use super::{
+        OutputCollector, TargetProcess, TargetProcessOptions, TerminateOnDrop, drain_text,
+        finish_target_process,
+    };
+
+    #[test]
+    fn finish_target_process_terminates_running_target_and_collects_output() {
+        // Like `renderdoccmd capture`: the launcher starts the target and exits, and the target
+        // keeps the inherited pipes.
+        let mut launcher = Command::new("sh")
+            .arg("-c")
+            .arg("(echo ready; exec sleep 30) & echo $! >&2")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("spawn launcher");
+        let output = OutputCollector::spawn(&mut launcher);
+        launcher.wait().expect("wait launcher");
+        while output.stdout.lock().expect("stdout buffer").is_empty()
+            || !output
+                .stderr
+                .lock()
+                .expect("stderr buffer")
+                .ends_with(b"\n")
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let pid: u32 = drain_text(&output.stderr)
+            .trim()
+            .parse()
+            .expect("target pid");
+
+        let report = finish_target_process(
+            pid,
+            &TargetProcessOptions {
+                terminate_after_capture: true,
+                terminate_grace_ms: 2_000,
+                wait_for_exit_s: None,
+            },
+            &output,
+        )
+        .expect("finish target");
+
+        assert_eq!(report.pid, pid);
+        assert!(report.exited);
+        assert!(report.terminated);
+        assert!(!report.forced);
+        // The target is not our child, so its exit status isn't ours to collect.
+        assert_eq!(report.exit_code, None);
+        assert_eq!(report.stdout, "ready\n");
+    }
+
+    #[test]
+    fn terminate_on_drop_stops_target_unless_disarmed() {
+        let spawn = || {
+            Command::new("sleep")
+                .arg("30")
+                .stdin(Stdio::null())
+                .spawn()
+                .expect("spawn target")
+        };
+
+        let mut disarmed = spawn();
+        TerminateOnDrop::new(Some(disarmed.id()), Duration::from_millis(500)).disarm();
+        assert!(
+            TargetProcess::attach(disarmed.id())
+                .expect("attach")
+                .is_running()
+        );
+        disarmed.kill().expect("kill disarmed target");
+        disarmed.wait().expect("wait disarmed target");
+
+        let mut guarded = spawn();
+        drop(TerminateOnDrop::new(
+            Some(guarded.id()),
+            Duration::from_millis(500),
+        ));
+        // The liveness checks must not reap the target: its status still belongs to its parent.
+        let status = guarded.wait().expect("wait guarded target");
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+    }
+
+    #[test]
+    fn dropped_output_collector_discards_later_output() {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("sleep 0.2; echo late")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn target");
+        let output = OutputCollector::spawn(&mut child);
+        let stdout = Arc::clone(&output.stdout);
+        drop(output);
+        child.wait().expect("wait child");
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert!(stdout.lock().expect("stdout buffer").is_empty());
+    }
+
+    #[test]
+    fn finish_target_process_reports_target_that_already_exited() {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("echo done >&2")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("spawn target");
+        let output = OutputCollector::spawn(&mut child);
+        let pid = child.id();
+        child.wait().expect("wait child");
+        assert!(output.wait_closed(Duration::from_secs(5)));
+
+        let report = finish_target_process(
+            pid,
+            &TargetProcessOptions {
+                terminate_after_capture: true,
+                ..TargetProcessOptions::default()
+            },
+            &output,
+        )
+        .expect("finish target");
+
+        assert!(report.exited);
+        assert!(!report.terminated);
+        assert_eq!(report.stderr, "done\n");
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::RenderDocInstallation;
+
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A progress update reported by a qrenderdoc job script via `report_progress`.
+///
+/// Bundled jobs report an `open_capture` stage and, for jobs that walk the action tree, an
+/// `actions` stage counting visited actions against the capture's total.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct QRenderDocJobProgress {
+    pub stage: String,
+    pub current: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Callback receiving [`QRenderDocJobProgress`] updates while a job runs.
+#[derive(Clone)]
+pub struct QRenderDocProgressHandler(Arc<dyn Fn(&QRenderDocJobProgress) + Send + Sync>);
+
+impl QRenderDocProgressHandler {
+    pub fn new(handler: impl Fn(&QRenderDocJobProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    fn report(&self, progress: &QRenderDocJobProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for QRenderDocProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QRenderDocProgressHandler(..)")
+    }
+}
+
+thread_local! {
+    /// Handler for jobs started on this thread, set by [`RenderDocInstallation::with_job_progress`].
+    static JOB_PROGRESS: RefCell<Option<QRenderDocProgressHandler>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous handler when a progress scope ends, even by unwinding.
+struct JobProgressScope(Option<QRenderDocProgressHandler>);
+
+impl Drop for JobProgressScope {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        JOB_PROGRESS.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+pub(crate) fn current_job_progress() -> Option<QRenderDocProgressHandler> {
+    JOB_PROGRESS.with(|current| current.borrow().clone())
+}
+
+impl RenderDocInstallation {
+    /// Runs `op`, reporting progress of every qrenderdoc job it starts on this thread to `handler`.
+    ///
+    /// Updates are delivered on the thread that started the job, between polls of the script's
+    /// progress file. Scopes nest; the innermost handler wins.
+    pub fn with_job_progress<T>(
+        &self,
+        handler: &QRenderDocProgressHandler,
+        op: impl FnOnce(&Self) -> T,
+    ) -> T {
+        let previous = JOB_PROGRESS.with(|current| current.replace(Some(handler.clone())));
+        let _scope = JobProgressScope(previous);
+        op(self)
+    }
+}
+
+/// Reads complete JSON lines appended to a job's progress file since the last poll.
+pub(crate) struct ProgressTail {
+    path: PathBuf,
+    offset: u64,
+    pending: String,
+}
+
+impl ProgressTail {
+    pub(crate) fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            offset: 0,
+            pending: String::new(),
+        }
+    }
+
+    pub(crate) fn poll(&mut self) -> Vec<QRenderDocJobProgress> {
+        let Ok(mut file) = std::fs::File::open(&self.path) else {
+            return Vec::new();
+        };
+        let mut bytes = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut bytes).is_err()
+        {
+            return Vec::new();
+        }
+        self.offset += bytes.len() as u64;
+        self.pending.push_str(&String::from_utf8_lossy(&bytes));
+
+        let Some(end) = self.pending.rfind('\n') else {
+            return Vec::new();
+        };
+        let complete: String = self.pending.drain(..=end).collect();
+        complete
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+/// Runs `job` on a scoped thread and forwards updates from `progress_path` to `handler` until it
+/// finishes.
+pub(crate) fn run_with_progress<T: Send>(
+    progress_path: &Path,
+    handler: &QRenderDocProgressHandler,
+    job: impl FnOnce() -> T + Send,
+) -> T {
+    let mut tail = ProgressTail::new(progress_path);
+    std::thread::scope(|scope| {
+        let job = scope.spawn(job);
+        while !job.is_finished() {
+            for progress in tail.poll() {
+                handler.report(&progress);
+            }
+            std::thread::sleep(PROGRESS_POLL_INTERVAL);
+        }
+        for progress in tail.poll() {
+            handler.report(&progress);
+        }
+        job.join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use super::{
+        ProgressTail, QRenderDocJobProgress, QRenderDocProgressHandler, current_job_progress,
+        run_with_progress,
+    };
+    use crate::RenderDocInstallation;
+    use crate::test_support::temp_path;
+
+    #[test]
+    fn progress_tail_yields_only_complete_lines() {
+        let path = temp_path("progress-tail").with_extension("progress");
+        let mut file = std::fs::File::create(&path).expect("create progress file");
+        let mut tail = ProgressTail::new(&path);
+        assert!(tail.poll().is_empty());
+
+        write!(
+            file,
+            "{{\"stage\":\"actions\",\"current\":1,\"total\":3}}\n{{\"stage\""
+        )
+        .expect("write");
+        file.flush().expect("flush");
+        assert_eq!(
+            tail.poll(),
+            [QRenderDocJobProgress {
+                stage: "actions".into(),
+                current: 1,
+                total: Some(3),
+                message: None,
+            }]
+        );
+
+        writeln!(file, ":\"actions\",\"current\":3,\"total\":3}}").expect("write");
+        file.flush().expect("flush");
+        let updates = tail.poll();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].current, 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn run_with_progress_delivers_updates_written_by_the_job() {
+        let path = temp_path("progress-run").with_extension("progress");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let seen = Arc::clone(&seen);
+            QRenderDocProgressHandler::new(move |progress| {
+                seen.lock().expect("seen").push(progress.current)
+            })
+        };
+
+        let result = run_with_progress(&path, &handler, || {
+            for current in 1..=3 {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .expect("open progress file");
+                writeln!(file, "{{\"stage\":\"actions\",\"current\":{current}}}").expect("write");
+            }
+            "done"
+        });
+
+        assert_eq!(result, "done");
+        assert_eq!(*seen.lock().expect("seen"), [1, 2, 3]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn job_progress_applies_only_inside_its_scope() {
+        let install = RenderDocInstallation {
+            root_dir: "renderdoc".into(),
+            qrenderdoc_exe: "renderdoc/qrenderdoc".into(),
+            renderdoccmd_exe: "renderdoc/renderdoccmd".into(),
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let handler = |stage: &'static str| {
+            let seen = Arc::clone(&seen);
+            QRenderDocProgressHandler::new(move |_| seen.lock().expect("seen").push(stage))
+        };
+        let report = || {
+            current_job_progress()
+                .expect("handler in scope")
+                .report(&QRenderDocJobProgress {
+                    stage: "actions".into(),
+                    current: 1,
+                    total: None,
+                    message: None,
+                })
+        };
+
+        assert!(current_job_progress().is_none());
+        install.with_job_progress(&handler("outer"), |install| {
+            report();
+            install.with_job_progress(&handler("inner"), |_| report());
+            report();
+        });
+        assert!(current_job_progress().is_none());
+        assert_eq!(*seen.lock().expect("seen"), ["outer", "inner", "outer"]);
+    }
+}
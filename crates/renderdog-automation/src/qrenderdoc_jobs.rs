@@ -13,7 +13,7 @@ const ACTION_QUERY_SUPPORT_FILE: QRenderDocScriptFile = QRenderDocScriptFile::ne
 const QRENDERDOC_RUNTIME_SUPPORT_FILES: &[QRenderDocScriptFile] =
     &[QRENDERDOC_RUNTIME_SUPPORT_FILE];
 
-const ACTION_QUERY_SUPPORT_FILES: &[QRenderDocScriptFile] =
+pub(crate) const ACTION_QUERY_SUPPORT_FILES: &[QRenderDocScriptFile] =
     &[QRENDERDOC_RUNTIME_SUPPORT_FILE, ACTION_QUERY_SUPPORT_FILE];
 
 pub(crate) const EXPORT_ACTIONS_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
@@ -30,6 +30,27 @@ pub(crate) const EXPORT_BINDINGS_INDEX_JOB: QRenderDocJob = QRenderDocJob::with_
     ACTION_QUERY_SUPPORT_FILES,
 );
 
+pub(crate) const EXPORT_ANNOTATIONS_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "export_annotations",
+    "export_annotations.py",
+    include_str!("../scripts/export_annotations.py"),
+    ACTION_QUERY_SUPPORT_FILES,
+);
+
+pub(crate) const FRAME_STATS_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "frame_stats",
+    "frame_stats.py",
+    include_str!("../scripts/frame_stats.py"),
+    ACTION_QUERY_SUPPORT_FILES,
+);
+
+pub(crate) const FRAME_GRAPH_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "frame_graph",
+    "frame_graph.py",
+    include_str!("../scripts/frame_graph.py"),
+    ACTION_QUERY_SUPPORT_FILES,
+);
+
 pub(crate) const FIND_EVENTS_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
     "find_events",
     "find_events.py",
@@ -37,13 +58,6 @@ pub(crate) const FIND_EVENTS_JOB: QRenderDocJob = QRenderDocJob::with_support_fi
     ACTION_QUERY_SUPPORT_FILES,
 );
 
-pub(crate) const TRIGGER_CAPTURE_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
-    "trigger_capture",
-    "trigger_capture.py",
-    include_str!("../scripts/trigger_capture.py"),
-    QRENDERDOC_RUNTIME_SUPPORT_FILES,
-);
-
 pub(crate) const REPLAY_LIST_TEXTURES_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
     "replay_list_textures",
     "replay_list_textures.py",
@@ -51,6 +65,35 @@ pub(crate) const REPLAY_LIST_TEXTURES_JOB: QRenderDocJob = QRenderDocJob::with_s
     QRENDERDOC_RUNTIME_SUPPORT_FILES,
 );
 
+pub(crate) const REPLAY_LIST_RESOURCES_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "replay_list_resources",
+    "replay_list_resources.py",
+    include_str!("../scripts/replay_list_resources.py"),
+    QRENDERDOC_RUNTIME_SUPPORT_FILES,
+);
+
+pub(crate) const REPLAY_GPU_TIMINGS_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "replay_gpu_timings",
+    "replay_gpu_timings.py",
+    include_str!("../scripts/replay_gpu_timings.py"),
+    ACTION_QUERY_SUPPORT_FILES,
+);
+
+pub(crate) const REPLAY_RESOURCE_USAGE_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
+    "replay_resource_usage",
+    "replay_resource_usage.py",
+    include_str!("../scripts/replay_resource_usage.py"),
+    ACTION_QUERY_SUPPORT_FILES,
+);
+
+pub(crate) const SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB: QRenderDocJob =
+    QRenderDocJob::with_support_files(
+        "scan_outputs_for_invalid_values",
+        "scan_outputs_for_invalid_values.py",
+        include_str!("../scripts/scan_outputs_for_invalid_values.py"),
+        ACTION_QUERY_SUPPORT_FILES,
+    );
+
 pub(crate) const REPLAY_PICK_PIXEL_JOB: QRenderDocJob = QRenderDocJob::with_support_files(
     "replay_pick_pixel",
     "replay_pick_pixel.py",
@@ -77,19 +120,27 @@ mod tests {
     use std::collections::BTreeSet;
 
     use super::{
-        EXPORT_ACTIONS_JOB, EXPORT_BINDINGS_INDEX_JOB, FIND_EVENTS_JOB, REPLAY_LIST_TEXTURES_JOB,
-        REPLAY_PICK_PIXEL_JOB, REPLAY_SAVE_OUTPUTS_PNG_JOB, REPLAY_SAVE_TEXTURE_PNG_JOB,
-        TRIGGER_CAPTURE_JOB,
+        EXPORT_ACTIONS_JOB, EXPORT_ANNOTATIONS_JOB, EXPORT_BINDINGS_INDEX_JOB, FIND_EVENTS_JOB,
+        FRAME_GRAPH_JOB, FRAME_STATS_JOB, REPLAY_GPU_TIMINGS_JOB, REPLAY_LIST_RESOURCES_JOB,
+        REPLAY_LIST_TEXTURES_JOB, REPLAY_PICK_PIXEL_JOB, REPLAY_RESOURCE_USAGE_JOB,
+        REPLAY_SAVE_OUTPUTS_PNG_JOB, REPLAY_SAVE_TEXTURE_PNG_JOB,
+        SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB,
     };
 
     #[test]
     fn qrenderdoc_job_registry_uses_unique_prefixes_and_script_names() {
         let jobs = [
             &EXPORT_ACTIONS_JOB,
+            &EXPORT_ANNOTATIONS_JOB,
             &EXPORT_BINDINGS_INDEX_JOB,
+            &FRAME_STATS_JOB,
+            &FRAME_GRAPH_JOB,
             &FIND_EVENTS_JOB,
-            &TRIGGER_CAPTURE_JOB,
             &REPLAY_LIST_TEXTURES_JOB,
+            &REPLAY_GPU_TIMINGS_JOB,
+            &REPLAY_RESOURCE_USAGE_JOB,
+            &REPLAY_LIST_RESOURCES_JOB,
+            &SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB,
             &REPLAY_PICK_PIXEL_JOB,
             &REPLAY_SAVE_TEXTURE_PNG_JOB,
             &REPLAY_SAVE_OUTPUTS_PNG_JOB,
@@ -112,7 +163,13 @@ mod tests {
     fn action_query_jobs_bundle_shared_support_module() {
         let jobs = [
             &EXPORT_ACTIONS_JOB,
+            &EXPORT_ANNOTATIONS_JOB,
             &EXPORT_BINDINGS_INDEX_JOB,
+            &FRAME_STATS_JOB,
+            &REPLAY_GPU_TIMINGS_JOB,
+            &REPLAY_RESOURCE_USAGE_JOB,
+            &FRAME_GRAPH_JOB,
+            &SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB,
             &FIND_EVENTS_JOB,
         ];
 
@@ -129,10 +186,16 @@ mod tests {
     fn all_jobs_bundle_shared_qrenderdoc_runtime_module() {
         let jobs = [
             &EXPORT_ACTIONS_JOB,
+            &EXPORT_ANNOTATIONS_JOB,
             &EXPORT_BINDINGS_INDEX_JOB,
+            &FRAME_STATS_JOB,
+            &FRAME_GRAPH_JOB,
             &FIND_EVENTS_JOB,
-            &TRIGGER_CAPTURE_JOB,
             &REPLAY_LIST_TEXTURES_JOB,
+            &REPLAY_GPU_TIMINGS_JOB,
+            &REPLAY_RESOURCE_USAGE_JOB,
+            &REPLAY_LIST_RESOURCES_JOB,
+            &SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB,
             &REPLAY_PICK_PIXEL_JOB,
             &REPLAY_SAVE_TEXTURE_PNG_JOB,
             &REPLAY_SAVE_OUTPUTS_PNG_JOB,
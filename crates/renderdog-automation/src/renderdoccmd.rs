@@ -7,9 +7,10 @@ use thiserror::Error;
 
 use crate::RenderDocInstallation;
 use crate::command::CommandError;
+use crate::process::OutputCollector;
 use crate::{
-    CommandSpec, ToolInvocationError, path_to_api_string, run_command_expect_success,
-    run_command_output_text,
+    CommandSpec, ToolInvocationError, path_to_api_string, run_command_collecting_output,
+    run_command_expect_success, run_command_output_text,
 };
 
 #[derive(Debug, Clone)]
@@ -18,14 +19,20 @@ pub(crate) struct CaptureLaunchCommand {
     pub args: Vec<OsString>,
     pub working_dir: Option<PathBuf>,
     pub capture_file_template: Option<PathBuf>,
+    /// `--opt-*` flags, see `CaptureLaunchOptions::renderdoccmd_args`.
+    pub options: Vec<OsString>,
+    /// Set on `renderdoccmd` itself; the launched target inherits its environment.
+    pub env: Vec<(OsString, OsString)>,
+    pub wait_for_exit: bool,
 }
 
-#[derive(Debug, Clone)]
 pub(crate) struct CaptureLaunchOutcome {
     pub target_ident: u32,
     pub capture_file_template: Option<String>,
     pub stdout: String,
     pub stderr: String,
+    /// Output written after `renderdoccmd` exited, by a target that inherited its pipes.
+    pub target_output: OutputCollector,
 }
 
 #[derive(Debug, Error)]
@@ -59,12 +66,16 @@ impl RenderDocInstallation {
             spec.args.push(template.as_os_str().to_owned());
         }
 
+        if req.wait_for_exit {
+            spec.args.push(OsString::from("--wait-for-exit"));
+        }
+        spec.args.extend(req.options.iter().cloned());
+        spec.env.extend(req.env.iter().cloned());
+
         spec.args.push(req.executable.as_os_str().to_owned());
         spec.args.extend(req.args.iter().cloned());
 
-        let output = run_command_output_text(&spec)?;
-        let stdout = output.stdout;
-        let stderr = output.stderr;
+        let (output, target_output) = run_command_collecting_output(&spec)?;
         let code = output.status;
         let target_ident =
             u32::try_from(code).map_err(|_| CaptureLaunchError::InvalidTargetIdent(code))?;
@@ -75,8 +86,9 @@ impl RenderDocInstallation {
                 .capture_file_template
                 .as_ref()
                 .map(|path| path_to_api_string(path)),
-            stdout,
-            stderr,
+            stdout: output.stdout,
+            stderr: output.stderr,
+            target_output,
         })
     }
 
@@ -277,7 +277,7 @@ impl PrepareQRenderDocJobRequest for ReplaySaveOutputsPngRequest {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ReplaySavedImageKind {
     Color,
@@ -4,21 +4,153 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use serde::{Serialize, de::DeserializeOwned};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 
-use crate::RenderDocInstallation;
 use crate::command::CommandError;
 use crate::default_scripts_dir;
+use crate::progress::{current_job_progress, run_with_progress};
 use crate::{CommandSpec, ToolInvocationError, path_to_api_string, run_command_expect_success};
+use crate::{EventQueryError, RenderDocInstallation};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub(crate) struct QRenderDocJobResponse<T> {
     pub ok: bool,
     pub result: Option<T>,
-    pub error: Option<String>,
+    pub error: Option<serde_json::Value>,
 }
 
+/// Why a qrenderdoc job script failed, as classified by `renderdog_qrenderdoc.py`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QRenderDocScriptError {
+    /// The capture file couldn't be opened or its replay couldn't be initialised.
+    #[error(
+        "couldn't open capture {capture_path} ({result_code}); check that the file exists and was written by a compatible RenderDoc version"
+    )]
+    CaptureOpenFailed {
+        capture_path: String,
+        /// RenderDoc `ResultCode` name, e.g. `FileCorrupted`.
+        result_code: String,
+    },
+    /// The capture's API or driver can't be replayed on this machine.
+    #[error(
+        "capture {capture_path} ({driver}) can't be replayed locally; replay it on a machine with a matching GPU and driver"
+    )]
+    ReplayUnsupported {
+        capture_path: String,
+        #[serde(default)]
+        driver: String,
+    },
+    /// The replay driver for the capture's graphics API is not available in this RenderDoc build.
+    #[error(
+        "graphics API of capture {capture_path} ({driver}) is not supported by this RenderDoc installation"
+    )]
+    ApiUnsupported {
+        capture_path: String,
+        #[serde(default)]
+        driver: String,
+    },
+    /// The requested `event_id` is past the last event in the capture.
+    #[error(
+        "event_id {event_id} is out of range; the capture's events run from 0 to {max_event_id}"
+    )]
+    EventOutOfRange { event_id: i64, max_event_id: u32 },
+    /// The requested `texture_index` doesn't index the capture's texture list.
+    #[error(
+        "texture_index {texture_index} is out of range; the capture has {texture_count} textures"
+    )]
+    TextureIndexOutOfRange {
+        texture_index: i64,
+        texture_count: usize,
+    },
+    /// The replay driver doesn't expose a GPU counter the job needs.
+    #[error(
+        "GPU counter {counter} is not available when replaying {capture_path} ({driver}); replay on hardware and drivers that expose RenderDoc's GPU counters"
+    )]
+    CounterUnavailable {
+        capture_path: String,
+        counter: String,
+        #[serde(default)]
+        driver: String,
+    },
+    /// The replay driver reports a GPU counter in a unit the job can't convert.
+    #[error(
+        "GPU counter {counter} is reported in {unit} when replaying {capture_path}; only seconds are supported"
+    )]
+    CounterUnitUnsupported {
+        capture_path: String,
+        counter: String,
+        unit: String,
+    },
+    /// No resource in the capture matches the requested id or name.
+    #[error("no resource matching {resource} in capture {capture_path}")]
+    ResourceNotFound {
+        capture_path: String,
+        resource: String,
+    },
+    /// Any other exception raised by the script.
+    #[error("{exception_type}: {message}")]
+    PythonException {
+        exception_type: String,
+        message: String,
+        #[serde(default)]
+        traceback: String,
+    },
+}
+
+impl QRenderDocScriptError {
+    /// Interprets the `error` field of a job response.
+    ///
+    /// Scripts that predate structured errors report a bare traceback string; unknown kinds fall
+    /// back to their message so nothing the script said is lost.
+    pub(crate) fn from_response(error: Option<serde_json::Value>) -> Self {
+        let Some(error) = error else {
+            return Self::python_exception("unknown error", String::new());
+        };
+        match error {
+            serde_json::Value::String(traceback) => {
+                let message = traceback.trim_end().lines().last().unwrap_or_default();
+                Self::python_exception(message, traceback.clone())
+            }
+            value => serde_json::from_value(value.clone()).unwrap_or_else(|_| {
+                let field = |name: &str| {
+                    value
+                        .get(name)
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                Self::python_exception(&field("message"), field("traceback"))
+            }),
+        }
+    }
+
+    fn python_exception(message: &str, traceback: String) -> Self {
+        let (exception_type, message) = match message.split_once(": ") {
+            Some((ty, rest)) if !ty.is_empty() && !ty.contains(' ') => (ty, rest),
+            _ => ("Exception", message),
+        };
+        Self::PythonException {
+            exception_type: exception_type.to_string(),
+            message: message.to_string(),
+            traceback,
+        }
+    }
+
+    /// Python traceback captured by the script, if any.
+    pub fn traceback(&self) -> Option<&str> {
+        match self {
+            Self::PythonException { traceback, .. } if !traceback.is_empty() => Some(traceback),
+            _ => None,
+        }
+    }
+}
+
+/// Entry point `qrenderdoc --python` runs; it imports the job script from the run dir.
+pub(crate) const BOOTSTRAP_SCRIPT_FILE_NAME: &str = "_renderdog_qrenderdoc_bootstrap.py";
+
 pub(crate) fn create_qrenderdoc_run_dir(
     scripts_dir: &Path,
     prefix: &str,
@@ -60,7 +192,29 @@ pub enum QRenderDocJobError {
     #[error("failed to deserialize job response: {0}")]
     DeserializeResponse(serde_json::Error),
     #[error("qrenderdoc script error: {0}")]
-    ScriptError(String),
+    ScriptError(#[source] QRenderDocScriptError),
+    #[error("qrenderdoc job reported success without a result")]
+    MissingResult,
+    #[error("invalid custom qrenderdoc job: {0}")]
+    InvalidCustomJob(String),
+    #[error("invalid event query: {0}")]
+    InvalidEventQuery(#[source] EventQueryError),
+}
+
+impl QRenderDocJobError {
+    /// The classified script failure, if the job got as far as running its script.
+    pub fn script_error(&self) -> Option<&QRenderDocScriptError> {
+        match self {
+            Self::ScriptError(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Python traceback of an unclassified script failure, if any.
+    pub fn traceback(&self) -> Option<&str> {
+        self.script_error()
+            .and_then(QRenderDocScriptError::traceback)
+    }
 }
 
 impl From<QRenderDocPythonError> for QRenderDocJobError {
@@ -149,6 +303,29 @@ pub(crate) trait PrepareQRenderDocJobRequest: Serialize + Sized {
     fn prepare_in_cwd(&self, cwd: &Path) -> Result<Self, Self::Error>;
 }
 
+/// Borrowed view of a job's files, shared by bundled and custom jobs.
+pub(crate) struct QRenderDocJobFiles<'a> {
+    pub run_dir_prefix: &'a str,
+    pub script_file_name: &'a str,
+    pub script_content: &'a str,
+    pub support_files: Vec<(&'a str, &'a str)>,
+}
+
+impl From<QRenderDocJob> for QRenderDocJobFiles<'static> {
+    fn from(job: QRenderDocJob) -> Self {
+        Self {
+            run_dir_prefix: job.run_dir_prefix,
+            script_file_name: job.script_file_name,
+            script_content: job.script_content,
+            support_files: job
+                .support_files
+                .iter()
+                .map(|file| (file.file_name, file.content))
+                .collect(),
+        }
+    }
+}
+
 impl RenderDocInstallation {
     // Use this when the request is already normalized and ready to serialize as-is.
     pub(crate) fn run_qrenderdoc_job<TReq, TResp>(
@@ -157,6 +334,19 @@ impl RenderDocInstallation {
         job: QRenderDocJob,
         request: &TReq,
     ) -> Result<TResp, QRenderDocJobError>
+    where
+        TReq: Serialize,
+        TResp: DeserializeOwned,
+    {
+        self.run_qrenderdoc_job_files(cwd, &QRenderDocJobFiles::from(job), request)
+    }
+
+    pub(crate) fn run_qrenderdoc_job_files<TReq, TResp>(
+        &self,
+        cwd: &Path,
+        job: &QRenderDocJobFiles<'_>,
+        request: &TReq,
+    ) -> Result<TResp, QRenderDocJobError>
     where
         TReq: Serialize,
         TResp: DeserializeOwned,
@@ -167,10 +357,9 @@ impl RenderDocInstallation {
         let script_path = scripts_dir.join(job.script_file_name);
         write_script_file(&script_path, job.script_content)
             .map_err(QRenderDocJobError::WriteScript)?;
-        for support_file in job.support_files {
-            let support_path = scripts_dir.join(support_file.file_name);
-            write_script_file(&support_path, support_file.content)
-                .map_err(QRenderDocJobError::WriteScript)?;
+        for (file_name, content) in &job.support_files {
+            let support_path = scripts_dir.join(file_name);
+            write_script_file(&support_path, content).map_err(QRenderDocJobError::WriteScript)?;
         }
 
         let run_dir = create_qrenderdoc_run_dir(&scripts_dir, job.run_dir_prefix)
@@ -178,12 +367,11 @@ impl RenderDocInstallation {
         let run_script_path = run_dir.join(job.script_file_name);
         write_script_file(&run_script_path, job.script_content)
             .map_err(QRenderDocJobError::WriteScript)?;
-        for support_file in job.support_files {
-            let support_path = run_dir.join(support_file.file_name);
-            write_script_file(&support_path, support_file.content)
-                .map_err(QRenderDocJobError::WriteScript)?;
+        for (file_name, content) in &job.support_files {
+            let support_path = run_dir.join(file_name);
+            write_script_file(&support_path, content).map_err(QRenderDocJobError::WriteScript)?;
         }
-        let bootstrap_path = run_dir.join("_renderdog_qrenderdoc_bootstrap.py");
+        let bootstrap_path = run_dir.join(BOOTSTRAP_SCRIPT_FILE_NAME);
         write_script_file(
             &bootstrap_path,
             &qrenderdoc_bootstrap_script(&run_dir, job.script_file_name),
@@ -196,6 +384,7 @@ impl RenderDocInstallation {
             .unwrap_or(job.script_file_name);
         let request_path = run_dir.join(format!("{job_file_stem}.request"));
         let response_path = run_dir.join(format!("{job_file_stem}.response"));
+        let progress_path = run_dir.join(format!("{job_file_stem}.progress"));
         remove_if_exists(&response_path).map_err(QRenderDocJobError::WriteRequest)?;
 
         std::fs::write(
@@ -204,11 +393,17 @@ impl RenderDocInstallation {
         )
         .map_err(QRenderDocJobError::WriteRequest)?;
 
-        let execution_result = self.run_qrenderdoc_python(&QRenderDocPythonRequest {
+        let python_request = QRenderDocPythonRequest {
             script_path: bootstrap_path,
             args: Vec::new(),
             working_dir: Some(run_dir),
-        });
+        };
+        let execution_result = match current_job_progress() {
+            Some(handler) => run_with_progress(&progress_path, &handler, || {
+                self.run_qrenderdoc_python(&python_request)
+            }),
+            None => self.run_qrenderdoc_python(&python_request),
+        };
 
         let bytes = match std::fs::read(&response_path) {
             Ok(bytes) => bytes,
@@ -222,12 +417,10 @@ impl RenderDocInstallation {
         let response: QRenderDocJobResponse<TResp> =
             serde_json::from_slice(&bytes).map_err(QRenderDocJobError::DeserializeResponse)?;
         if response.ok {
-            response
-                .result
-                .ok_or_else(|| QRenderDocJobError::ScriptError("missing result".into()))
+            response.result.ok_or(QRenderDocJobError::MissingResult)
         } else {
             Err(QRenderDocJobError::ScriptError(
-                response.error.unwrap_or_else(|| "unknown error".into()),
+                QRenderDocScriptError::from_response(response.error),
             ))
         }
     }
@@ -300,3 +493,79 @@ fn qrenderdoc_bootstrap_script(run_dir: &Path, script_file_name: &str) -> String
          exec(code, {{'__name__': '__main__'}})\n"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{QRenderDocJobError, QRenderDocScriptError};
+
+    #[test]
+    fn script_error_parses_structured_kinds() {
+        let error = QRenderDocScriptError::from_response(Some(json!({
+            "kind": "capture_open_failed",
+            "message": "Couldn't open file: FileCorrupted",
+            "capture_path": "frame.rdc",
+            "result_code": "FileCorrupted",
+            "traceback": "Traceback ...",
+        })));
+        assert_eq!(
+            error,
+            QRenderDocScriptError::CaptureOpenFailed {
+                capture_path: "frame.rdc".into(),
+                result_code: "FileCorrupted".into(),
+            }
+        );
+
+        let error = QRenderDocScriptError::from_response(Some(json!({
+            "kind": "event_out_of_range",
+            "message": "event_id 900 out of range",
+            "event_id": 900,
+            "max_event_id": 412,
+        })));
+        assert_eq!(
+            error.to_string(),
+            "event_id 900 is out of range; the capture's events run from 0 to 412"
+        );
+
+        let error = QRenderDocScriptError::from_response(Some(json!({
+            "kind": "counter_unit_unsupported",
+            "message": "EventGPUDuration is reported in CounterUnit.Cycles, not seconds",
+            "capture_path": "frame.rdc",
+            "counter": "EventGPUDuration",
+            "unit": "CounterUnit.Cycles",
+        })));
+        assert!(matches!(
+            error,
+            QRenderDocScriptError::CounterUnitUnsupported { ref unit, .. } if unit == "CounterUnit.Cycles"
+        ));
+    }
+
+    #[test]
+    fn script_error_falls_back_to_python_exception() {
+        let error = QRenderDocScriptError::from_response(Some(json!(
+            "Traceback (most recent call last):\n  File \"job.py\", line 3\nKeyError: 'texture_index'\n"
+        )));
+        assert!(matches!(
+            &error,
+            QRenderDocScriptError::PythonException { exception_type, message, .. }
+                if exception_type == "KeyError" && message == "'texture_index'"
+        ));
+        assert!(
+            error
+                .traceback()
+                .is_some_and(|tb| tb.starts_with("Traceback"))
+        );
+
+        let job_error = QRenderDocJobError::ScriptError(error.clone());
+        assert_eq!(job_error.traceback(), error.traceback());
+        let source = std::error::Error::source(&job_error).expect("script error is the source");
+        assert_eq!(source.downcast_ref::<QRenderDocScriptError>(), Some(&error));
+
+        let error = QRenderDocScriptError::from_response(Some(json!({
+            "kind": "something_new",
+            "message": "RuntimeError: boom",
+        })));
+        assert_eq!(error.to_string(), "RuntimeError: boom");
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    io::ErrorKind,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    TARGET_CONTROL_FIRST_PORT, TARGET_CONTROL_LAST_PORT, TargetControlClient,
+    TargetControlConnectOptions, TargetControlError, TargetControlMessage,
+};
+
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+// Targets announce their API right after the handshake; don't wait long for apps that haven't
+// initialised one yet.
+const PROBE_API_TIMEOUT: Duration = Duration::from_millis(250);
+
+fn default_host() -> String {
+    "localhost".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListTargetsRequest {
+    #[serde(default = "default_host")]
+    pub host: String,
+}
+
+impl Default for ListTargetsRequest {
+    fn default() -> Self {
+        Self {
+            host: default_host(),
+        }
+    }
+}
+
+/// A running RenderDoc-enabled application found on a host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RemoteTarget {
+    /// Target-control port; pass as `target_ident` to capture workflows.
+    pub target_ident: u32,
+    pub executable: String,
+    /// `None` when the target is busy, since it only reports its PID to the controlling client.
+    pub pid: Option<u32>,
+    /// Graphics API the target has initialised, if it announced one during the probe.
+    pub api: Option<String>,
+    pub busy: bool,
+    /// Name of the client currently connected, when `busy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub busy_client: Option<String>,
+}
+
+/// A port that answered the probe but couldn't be read as a RenderDoc target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TargetProbeFailure {
+    pub target_ident: u32,
+    /// Why the probe failed, e.g. a protocol version mismatch or a malformed packet.
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListTargetsResponse {
+    pub host: String,
+    pub targets: Vec<RemoteTarget>,
+    /// Ports with something listening that failed the probe. Ports that refused the connection
+    /// or never answered are empty and not listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<TargetProbeFailure>,
+}
+
+/// Enumerates RenderDoc targets on `host`, like RenderDoc's `EnumerateRemoteTargets`.
+///
+/// Each target-control port is probed without forcing the connection, so a target that is already
+/// controlled (for example by the RenderDoc UI) is reported as busy rather than taken over. Only an
+/// unresolvable host fails the whole scan; other per-port errors land in
+/// [`ListTargetsResponse::failed`].
+pub fn list_targets(host: &str) -> Result<ListTargetsResponse, TargetControlError> {
+    list_targets_on_ports(
+        host,
+        u32::from(TARGET_CONTROL_FIRST_PORT)..=u32::from(TARGET_CONTROL_LAST_PORT),
+    )
+}
+
+pub(super) fn list_targets_on_ports(
+    host: &str,
+    idents: RangeInclusive<u32>,
+) -> Result<ListTargetsResponse, TargetControlError> {
+    let mut response = ListTargetsResponse {
+        host: host.to_string(),
+        targets: Vec::new(),
+        failed: Vec::new(),
+    };
+    for ident in idents {
+        match probe_target(host, ident) {
+            Ok(Some(target)) => response.targets.push(target),
+            Ok(None) => {}
+            Err(err @ TargetControlError::Resolve { .. }) => return Err(err),
+            Err(err) if is_empty_port(&err) => {}
+            Err(err) => response.failed.push(TargetProbeFailure {
+                target_ident: ident,
+                error: err.to_string(),
+            }),
+        }
+    }
+    Ok(response)
+}
+
+/// Nothing is listening on the port, or whatever is there never answered the handshake.
+fn is_empty_port(err: &TargetControlError) -> bool {
+    match err {
+        TargetControlError::Connect { source, .. } => matches!(
+            source.kind(),
+            ErrorKind::ConnectionRefused | ErrorKind::TimedOut | ErrorKind::WouldBlock
+        ),
+        TargetControlError::Timeout(..) => true,
+        _ => false,
+    }
+}
+
+fn probe_target(host: &str, ident: u32) -> Result<Option<RemoteTarget>, TargetControlError> {
+    let options = TargetControlConnectOptions {
+        force_connection: false,
+        timeout: PROBE_CONNECT_TIMEOUT,
+        ..TargetControlConnectOptions::default()
+    };
+
+    let mut client = match TargetControlClient::connect(host, ident, &options) {
+        Ok(client) => client,
+        Err(TargetControlError::Busy { target, client }) => {
+            return Ok(Some(RemoteTarget {
+                target_ident: ident,
+                executable: target,
+                pid: None,
+                api: None,
+                busy: true,
+                busy_client: Some(client),
+            }));
+        }
+        Err(err) => return Err(err),
+    };
+
+    let deadline = Instant::now() + PROBE_API_TIMEOUT;
+    while client.api().is_none() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match client.receive_message(remaining)? {
+            TargetControlMessage::Noop | TargetControlMessage::Disconnected => break,
+            _ => {}
+        }
+    }
+
+    Ok(Some(RemoteTarget {
+        target_ident: ident,
+        executable: client.target_name().to_string(),
+        pid: Some(client.pid()),
+        api: client.api().map(str::to_string),
+        busy: false,
+        busy_client: None,
+    }))
+}
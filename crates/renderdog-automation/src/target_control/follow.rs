@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    TargetControlClient, TargetControlConnectOptions, TargetControlError, TargetControlMessage,
+};
+use crate::TargetProcessNode;
+
+// Short per-target receive slices so one quiet process doesn't starve the others.
+const POLL_SLICE: Duration = Duration::from_millis(50);
+const CHILD_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+struct FollowedTarget {
+    client: Option<TargetControlClient>,
+    node: TargetProcessNode,
+    exited: bool,
+}
+
+/// Follows `NewChild` messages from the target at `ident` until some process in the tree has
+/// registered a graphics API, then returns a connection to that process plus the tree seen so far.
+///
+/// Children are connected as soon as they are announced; a child whose target-control server is
+/// not up yet is retried on the next pass.
+pub(super) fn connect_capturable_target(
+    host: &str,
+    ident: u32,
+    options: &TargetControlConnectOptions,
+    timeout: Duration,
+) -> Result<(TargetControlClient, Vec<TargetProcessNode>), TargetControlError> {
+    let root = TargetControlClient::connect(host, ident, options)?;
+    let mut targets = vec![FollowedTarget {
+        node: TargetProcessNode {
+            pid: root.pid(),
+            target_ident: ident,
+            parent_pid: None,
+            executable: root.target_name().to_string(),
+            api: root.api().map(str::to_string),
+            captured: false,
+        },
+        client: Some(root),
+        exited: false,
+    }];
+    let child_options = TargetControlConnectOptions {
+        timeout: CHILD_CONNECT_TIMEOUT,
+        ..options.clone()
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(index) = targets
+            .iter()
+            .position(|target| target.node.api.is_some() && target.client.is_some())
+        {
+            return Ok(into_captured(targets, index));
+        }
+        if Instant::now() >= deadline {
+            return Err(TargetControlError::Timeout(timeout, "RegisterAPI"));
+        }
+
+        let mut children = Vec::new();
+        for target in &mut targets {
+            if target.exited {
+                continue;
+            }
+            if target.client.is_none() {
+                target.client =
+                    TargetControlClient::connect(host, target.node.target_ident, &child_options)
+                        .ok();
+                if let Some(client) = &target.client {
+                    target.node.pid = client.pid();
+                    target.node.executable = client.target_name().to_string();
+                    target.node.api = client.api().map(str::to_string);
+                }
+            }
+            let Some(client) = target.client.as_mut() else {
+                continue;
+            };
+
+            match client.receive_message(POLL_SLICE)? {
+                TargetControlMessage::RegisterApi(api) => target.node.api = Some(api.api),
+                TargetControlMessage::NewChild { pid, ident } => children.push(TargetProcessNode {
+                    pid,
+                    target_ident: ident,
+                    parent_pid: Some(target.node.pid),
+                    executable: String::new(),
+                    api: None,
+                    captured: false,
+                }),
+                // Launchers commonly exit once their child is running; keep the node for the tree.
+                TargetControlMessage::Disconnected => {
+                    target.client = None;
+                    target.exited = true;
+                }
+                _ => {}
+            }
+        }
+
+        for node in children {
+            if !targets
+                .iter()
+                .any(|target| target.node.target_ident == node.target_ident)
+            {
+                targets.push(FollowedTarget {
+                    client: None,
+                    node,
+                    exited: false,
+                });
+            }
+        }
+    }
+}
+
+fn into_captured(
+    targets: Vec<FollowedTarget>,
+    index: usize,
+) -> (TargetControlClient, Vec<TargetProcessNode>) {
+    let mut captured = None;
+    let mut tree = Vec::with_capacity(targets.len());
+    for (position, mut target) in targets.into_iter().enumerate() {
+        if position == index {
+            target.node.captured = true;
+            captured = target.client.take();
+        }
+        tree.push(target.node);
+    }
+
+    (captured.expect("captured target is connected"), tree)
+}
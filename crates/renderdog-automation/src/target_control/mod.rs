@@ -0,0 +1,1346 @@
+//! Native client for RenderDoc's target-control protocol.
+//!
+//! Every RenderDoc-injected application listens on a TCP port in
+//! [`TARGET_CONTROL_FIRST_PORT`]..=[`TARGET_CONTROL_LAST_PORT`]; that port number is the target
+//! ident reported by `renderdoccmd capture`. [`TargetControlClient`] speaks the protocol directly,
+//! so triggering a capture no longer needs a `qrenderdoc` process.
+
+mod enumerate;
+mod follow;
+mod protocol;
+
+pub use enumerate::{
+    ListTargetsRequest, ListTargetsResponse, RemoteTarget, TargetProbeFailure, list_targets,
+};
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    TargetProcessNode, TriggerCaptureOptions, TriggerCaptureRequest, TriggerCaptureResponse,
+    TriggeredCapture,
+};
+
+use protocol::{
+    ClientPacket, HandshakeReply, MAX_PACKET_LEN, NewCapturePacket, ProtocolError, ServerPacket,
+};
+
+/// First TCP port RenderDoc uses for target control.
+pub const TARGET_CONTROL_FIRST_PORT: u16 = 38920;
+/// Last TCP port RenderDoc uses for target control.
+pub const TARGET_CONTROL_LAST_PORT: u16 = TARGET_CONTROL_FIRST_PORT + 7;
+/// Target-control protocol version sent in the handshake.
+pub const TARGET_CONTROL_PROTOCOL_VERSION: u32 = 9;
+
+const DEFAULT_CLIENT_NAME: &str = "renderdog";
+const READ_CHUNK_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum TargetControlError {
+    #[error("target ident {0} is not a valid TCP port")]
+    InvalidIdent(u32),
+    #[error("failed to resolve {host}: {source}")]
+    Resolve {
+        host: String,
+        source: std::io::Error,
+    },
+    #[error("failed to connect to {host}:{ident}: {source}")]
+    Connect {
+        host: String,
+        ident: u32,
+        source: std::io::Error,
+    },
+    #[error("target control I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed target control packet: {0}")]
+    Protocol(String),
+    #[error("target `{target}` is already controlled by `{client}`")]
+    Busy { target: String, client: String },
+    #[error(
+        "target speaks target control protocol version {target}, but renderdog speaks version \
+         {supported}; use a RenderDoc build whose target control version matches"
+    )]
+    VersionMismatch { target: u32, supported: u32 },
+    #[error("unexpected packet during handshake: {0}")]
+    UnexpectedHandshake(String),
+    #[error("target disconnected")]
+    Disconnected,
+    #[error("timed out after {0:?} waiting for {1}")]
+    Timeout(Duration, &'static str),
+    #[error("failed to write copied capture to {path}: {source}")]
+    WriteCopiedCapture {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl From<ProtocolError> for TargetControlError {
+    fn from(value: ProtocolError) -> Self {
+        Self::Protocol(value.to_string())
+    }
+}
+
+/// Options for [`TargetControlClient::connect`].
+#[derive(Debug, Clone)]
+pub struct TargetControlConnectOptions {
+    /// Name shown by the target as the connected client.
+    pub client_name: String,
+    /// Take over the target even if another client is connected. Off by default, so a target
+    /// already controlled by the RenderDoc UI reports [`TargetControlError::Busy`] instead of
+    /// being taken over.
+    pub force_connection: bool,
+    /// Applies to the TCP connect and to waiting for the handshake reply.
+    pub timeout: Duration,
+}
+
+impl Default for TargetControlConnectOptions {
+    fn default() -> Self {
+        Self {
+            client_name: DEFAULT_CLIENT_NAME.to_string(),
+            force_connection: false,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A capture the target finished writing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NewCaptureInfo {
+    pub capture_id: u32,
+    pub frame_number: u32,
+    /// Path on the target's machine.
+    pub path: String,
+    pub byte_size: u64,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub api: String,
+    pub title: String,
+    /// Whether `path` exists on the client's machine, which is how RenderDoc decides it too.
+    pub local: bool,
+    /// JPEG thumbnail, empty when the target sent none.
+    #[serde(skip)]
+    pub thumbnail: Vec<u8>,
+}
+
+impl From<NewCapturePacket> for NewCaptureInfo {
+    fn from(value: NewCapturePacket) -> Self {
+        Self {
+            local: Path::new(&value.path).exists(),
+            capture_id: value.capture_id,
+            frame_number: value.frame_number,
+            path: value.path,
+            byte_size: value.byte_size,
+            timestamp: value.timestamp,
+            api: value.api,
+            title: value.title,
+            thumbnail: value.thumbnail,
+        }
+    }
+}
+
+/// A graphics API the target initialised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RegisteredApi {
+    pub api: String,
+    pub presenting: bool,
+    pub supported: bool,
+    pub support_message: String,
+}
+
+/// Messages received from a target, mirroring RenderDoc's `TargetControlMessageType`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetControlMessage {
+    /// Nothing arrived before the receive timeout, or the target sent a keep-alive.
+    Noop,
+    /// Another client took over the target.
+    Busy {
+        client_name: String,
+    },
+    NewCapture(NewCaptureInfo),
+    RegisterApi(RegisteredApi),
+    /// A capture requested with [`TargetControlClient::copy_capture`] was written locally.
+    CaptureCopied {
+        capture_id: u32,
+        path: PathBuf,
+    },
+    NewChild {
+        pid: u32,
+        ident: u32,
+    },
+    CaptureProgress(f32),
+    CapturableWindowCount(u32),
+    RequestShow,
+    Disconnected,
+}
+
+/// Capture bytes streaming in after a `CopyCapture` packet.
+struct CaptureCopy {
+    capture_id: u32,
+    /// `None` when nobody asked for this capture; the bytes are read and dropped.
+    destination: Option<(PathBuf, File)>,
+    remaining: u64,
+}
+
+/// A connected target-control session.
+pub struct TargetControlClient {
+    stream: TcpStream,
+    rx: Vec<u8>,
+    read_buf: Box<[u8]>,
+    copy: Option<CaptureCopy>,
+    ident: u32,
+    target: String,
+    pid: u32,
+    api: Option<String>,
+    pending_copies: HashMap<u32, PathBuf>,
+    disconnected: bool,
+}
+
+impl std::fmt::Debug for TargetControlClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TargetControlClient")
+            .field("ident", &self.ident)
+            .field("target", &self.target)
+            .field("pid", &self.pid)
+            .field("api", &self.api)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TargetControlClient {
+    /// Connects to `host:ident` and performs the handshake.
+    pub fn connect(
+        host: &str,
+        ident: u32,
+        options: &TargetControlConnectOptions,
+    ) -> Result<Self, TargetControlError> {
+        let port = u16::try_from(ident).map_err(|_| TargetControlError::InvalidIdent(ident))?;
+        let addrs =
+            (host, port)
+                .to_socket_addrs()
+                .map_err(|source| TargetControlError::Resolve {
+                    host: host.to_string(),
+                    source,
+                })?;
+
+        let mut last_error = std::io::Error::new(ErrorKind::NotFound, "no addresses resolved");
+        let mut stream = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, options.timeout) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(err) => last_error = err,
+            }
+        }
+        let stream = stream.ok_or_else(|| TargetControlError::Connect {
+            host: host.to_string(),
+            ident,
+            source: last_error,
+        })?;
+        stream.set_nodelay(true)?;
+
+        let mut client = Self {
+            stream,
+            rx: Vec::new(),
+            read_buf: vec![0_u8; READ_CHUNK_LEN].into_boxed_slice(),
+            copy: None,
+            ident,
+            target: String::new(),
+            pid: 0,
+            api: None,
+            pending_copies: HashMap::new(),
+            disconnected: false,
+        };
+        client.handshake(options)?;
+        Ok(client)
+    }
+
+    fn handshake(
+        &mut self,
+        options: &TargetControlConnectOptions,
+    ) -> Result<(), TargetControlError> {
+        self.send(&ClientPacket::Handshake {
+            version: TARGET_CONTROL_PROTOCOL_VERSION,
+            client_name: options.client_name.clone(),
+            force_connection: options.force_connection,
+        })?;
+
+        let deadline = Instant::now() + options.timeout;
+        let reply = loop {
+            if let Some((reply, used)) = HandshakeReply::decode(&self.rx)? {
+                self.rx.drain(..used);
+                break reply;
+            }
+            self.check_rx_len()?;
+            if !self.fill_rx(deadline)? {
+                return Err(if self.disconnected {
+                    TargetControlError::Disconnected
+                } else {
+                    TargetControlError::Timeout(options.timeout, "handshake")
+                });
+            }
+        };
+
+        match reply {
+            HandshakeReply::Accepted {
+                version,
+                target,
+                pid,
+            } => {
+                check_version(version)?;
+                self.target = target;
+                self.pid = pid;
+                Ok(())
+            }
+            HandshakeReply::Busy {
+                version,
+                target,
+                busy_client,
+            } => {
+                check_version(version)?;
+                Err(TargetControlError::Busy {
+                    target,
+                    client: busy_client,
+                })
+            }
+        }
+    }
+
+    pub fn ident(&self) -> u32 {
+        self.ident
+    }
+
+    /// Executable name reported by the target.
+    pub fn target_name(&self) -> &str {
+        &self.target
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Most recent API announced by the target, if any message has been received yet.
+    pub fn api(&self) -> Option<&str> {
+        self.api.as_deref()
+    }
+
+    pub fn trigger_capture(&mut self, num_frames: u32) -> Result<(), TargetControlError> {
+        self.send(&ClientPacket::TriggerCapture { num_frames })
+    }
+
+    pub fn queue_capture(
+        &mut self,
+        frame_number: u32,
+        num_frames: u32,
+    ) -> Result<(), TargetControlError> {
+        self.send(&ClientPacket::QueueCapture {
+            frame_number,
+            num_frames,
+        })
+    }
+
+    /// Requests the capture bytes; a [`TargetControlMessage::CaptureCopied`] follows once
+    /// `local_path` has been written.
+    pub fn copy_capture(
+        &mut self,
+        capture_id: u32,
+        local_path: impl Into<PathBuf>,
+    ) -> Result<(), TargetControlError> {
+        self.pending_copies.insert(capture_id, local_path.into());
+        self.send(&ClientPacket::CopyCapture { capture_id })
+    }
+
+    pub fn delete_capture(&mut self, capture_id: u32) -> Result<(), TargetControlError> {
+        self.send(&ClientPacket::DeleteCapture { capture_id })
+    }
+
+    pub fn cycle_active_window(&mut self) -> Result<(), TargetControlError> {
+        self.send(&ClientPacket::CycleActiveWindow)
+    }
+
+    /// Waits up to `timeout` for the next message.
+    ///
+    /// Returns [`TargetControlMessage::Noop`] when nothing arrived and
+    /// [`TargetControlMessage::Disconnected`] once the target closed the connection.
+    pub fn receive_message(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<TargetControlMessage, TargetControlError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.copy.is_some() {
+                if let Some(message) = self.continue_copy()? {
+                    return Ok(message);
+                }
+            } else if let Some((packet, used)) = ServerPacket::decode(&self.rx)? {
+                self.rx.drain(..used);
+                if let Some(message) = self.handle_packet(packet)? {
+                    return Ok(message);
+                }
+                continue;
+            } else {
+                self.check_rx_len()?;
+            }
+
+            if !self.fill_rx(deadline)? {
+                return Ok(if self.disconnected {
+                    TargetControlMessage::Disconnected
+                } else {
+                    TargetControlMessage::Noop
+                });
+            }
+        }
+    }
+
+    /// Receives messages until a new capture arrives.
+    pub fn wait_for_new_capture(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<NewCaptureInfo, TargetControlError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(TargetControlError::Timeout(timeout, "NewCapture"));
+            }
+
+            match self.receive_message(remaining)? {
+                TargetControlMessage::NewCapture(capture) => return Ok(capture),
+                TargetControlMessage::Disconnected => return Err(TargetControlError::Disconnected),
+                TargetControlMessage::Busy { client_name } => {
+                    return Err(TargetControlError::Busy {
+                        target: self.target.clone(),
+                        client: client_name,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_packet(
+        &mut self,
+        packet: ServerPacket,
+    ) -> Result<Option<TargetControlMessage>, TargetControlError> {
+        Ok(Some(match packet {
+            ServerPacket::Noop => TargetControlMessage::Noop,
+            ServerPacket::Busy { client_name } => TargetControlMessage::Busy { client_name },
+            ServerPacket::NewCapture(capture) => {
+                if self.api.is_none() && !capture.api.is_empty() {
+                    self.api = Some(capture.api.clone());
+                }
+                TargetControlMessage::NewCapture(capture.into())
+            }
+            ServerPacket::RegisterApi {
+                api,
+                presenting,
+                supported,
+                support_message,
+            } => {
+                self.api = Some(api.clone());
+                TargetControlMessage::RegisterApi(RegisteredApi {
+                    api,
+                    presenting,
+                    supported,
+                    support_message,
+                })
+            }
+            ServerPacket::CopyCapture {
+                capture_id,
+                byte_len,
+            } => {
+                let destination = match self.pending_copies.remove(&capture_id) {
+                    Some(path) => {
+                        let file = create_copied_capture(&path)?;
+                        Some((path, file))
+                    }
+                    None => None,
+                };
+                self.copy = Some(CaptureCopy {
+                    capture_id,
+                    destination,
+                    remaining: byte_len,
+                });
+                return Ok(None);
+            }
+            ServerPacket::NewChild { pid, ident } => TargetControlMessage::NewChild { pid, ident },
+            ServerPacket::CaptureProgress { progress } => {
+                TargetControlMessage::CaptureProgress(progress)
+            }
+            ServerPacket::CapturableWindowCount { count } => {
+                TargetControlMessage::CapturableWindowCount(count)
+            }
+            ServerPacket::RequestShow => TargetControlMessage::RequestShow,
+        }))
+    }
+
+    /// Moves buffered capture bytes to disk, returning the message once the copy is complete.
+    fn continue_copy(&mut self) -> Result<Option<TargetControlMessage>, TargetControlError> {
+        let copy = self.copy.as_mut().expect("copy in progress");
+        let available = self
+            .rx
+            .len()
+            .min(usize::try_from(copy.remaining).unwrap_or(usize::MAX));
+        if let Some((path, file)) = &mut copy.destination {
+            file.write_all(&self.rx[..available]).map_err(|source| {
+                TargetControlError::WriteCopiedCapture {
+                    path: path.clone(),
+                    source,
+                }
+            })?;
+        }
+        self.rx.drain(..available);
+        copy.remaining -= available as u64;
+        if copy.remaining > 0 {
+            return Ok(None);
+        }
+
+        let copy = self.copy.take().expect("copy in progress");
+        Ok(copy
+            .destination
+            .map(|(path, _)| TargetControlMessage::CaptureCopied {
+                capture_id: copy.capture_id,
+                path,
+            }))
+    }
+
+    fn send(&mut self, packet: &ClientPacket) -> Result<(), TargetControlError> {
+        if self.disconnected {
+            return Err(TargetControlError::Disconnected);
+        }
+        self.stream.write_all(&packet.encode())?;
+        Ok(())
+    }
+
+    // Packets aren't length-prefixed, so a target that never completes one must not be able to
+    // grow the buffer without bound.
+    fn check_rx_len(&self) -> Result<(), TargetControlError> {
+        if self.rx.len() > MAX_PACKET_LEN {
+            return Err(ProtocolError::TooLarge {
+                what: "incomplete packet",
+                len: self.rx.len() as u64,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Reads whatever has arrived by `deadline` into the receive buffer. Returns `false` on
+    /// timeout or once the target has closed the connection.
+    ///
+    /// Partial packets stay buffered, so a timeout never leaves the stream mid-packet.
+    fn fill_rx(&mut self, deadline: Instant) -> Result<bool, TargetControlError> {
+        loop {
+            if self.disconnected {
+                return Ok(false);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+            self.stream.set_read_timeout(Some(remaining))?;
+
+            match self.stream.read(&mut self.read_buf) {
+                Ok(0) => self.disconnected = true,
+                Ok(read) => {
+                    self.rx.extend_from_slice(&self.read_buf[..read]);
+                    return Ok(true);
+                }
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(false);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+                    ) =>
+                {
+                    self.disconnected = true;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), TargetControlError> {
+    if version == TARGET_CONTROL_PROTOCOL_VERSION {
+        Ok(())
+    } else {
+        Err(TargetControlError::VersionMismatch {
+            target: version,
+            supported: TARGET_CONTROL_PROTOCOL_VERSION,
+        })
+    }
+}
+
+fn create_copied_capture(path: &Path) -> Result<File, TargetControlError> {
+    let create = || -> Result<File, std::io::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        File::create(path)
+    };
+    create().map_err(|source| TargetControlError::WriteCopiedCapture {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Writes the JPEG thumbnail a target sent with a local capture next to it, if there is one.
+///
+/// Thumbnails are a convenience, so a failed write is reported as "no thumbnail".
+fn write_capture_thumbnail(capture: &NewCaptureInfo) -> Option<String> {
+    if capture.thumbnail.is_empty() || !capture.local {
+        return None;
+    }
+    let path = Path::new(&capture.path).with_extension("thumb.jpg");
+    std::fs::write(&path, &capture.thumbnail).ok()?;
+    Some(crate::path_to_api_string(&path))
+}
+
+/// A connection to the process a trigger captures from, before anything was triggered.
+///
+/// Separate from [`trigger_capture_native`] so the one-shot workflow learns the PID of a followed
+/// child before waiting for its captures.
+pub(crate) struct CaptureTargetConnection {
+    client: TargetControlClient,
+    process_tree: Vec<TargetProcessNode>,
+}
+
+impl CaptureTargetConnection {
+    /// Connects to `req.target`, or with `follow_children` to the first process in its tree that
+    /// registers a graphics API.
+    pub(crate) fn connect(req: &TriggerCaptureRequest) -> Result<Self, TargetControlError> {
+        let timeout = Duration::from_secs(u64::from(req.trigger.timeout_s));
+        let connect_options = TargetControlConnectOptions {
+            timeout,
+            ..TargetControlConnectOptions::default()
+        };
+        let (client, process_tree) = if req.trigger.follow_children {
+            follow::connect_capturable_target(
+                &req.trigger.host,
+                req.target.target_ident,
+                &connect_options,
+                timeout,
+            )?
+        } else {
+            let client = TargetControlClient::connect(
+                &req.trigger.host,
+                req.target.target_ident,
+                &connect_options,
+            )?;
+            (client, Vec::new())
+        };
+        Ok(Self {
+            client,
+            process_tree,
+        })
+    }
+
+    pub(crate) fn pid(&self) -> u32 {
+        self.client.pid()
+    }
+
+    /// Triggers or queues the captures described by `trigger` and waits for them.
+    pub(crate) fn trigger(
+        mut self,
+        trigger: &TriggerCaptureOptions,
+    ) -> Result<TriggerCaptureResponse, TargetControlError> {
+        let timeout = Duration::from_secs(u64::from(trigger.timeout_s));
+        if trigger.delay_ms > 0 {
+            std::thread::sleep(Duration::from_millis(trigger.delay_ms));
+        }
+
+        let queued = trigger.queued_captures();
+        if queued.is_empty() {
+            self.client.trigger_capture(trigger.num_frames.max(1))?;
+        }
+        for (frame_number, num_frames) in queued {
+            self.client.queue_capture(frame_number, num_frames)?;
+        }
+
+        let expected = trigger.expected_captures();
+        let deadline = Instant::now() + timeout;
+        let mut captures = Vec::new();
+        while captures.len() < expected {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let capture = match self.client.wait_for_new_capture(remaining) {
+                Ok(capture) => capture,
+                Err(TargetControlError::Timeout(..) | TargetControlError::Disconnected)
+                    if !captures.is_empty() =>
+                {
+                    break;
+                }
+                Err(TargetControlError::Timeout(..)) => {
+                    return Err(TargetControlError::Timeout(timeout, "NewCapture"));
+                }
+                Err(err) => return Err(err),
+            };
+
+            captures.push(TriggeredCapture {
+                thumbnail_path: write_capture_thumbnail(&capture),
+                capture: crate::CaptureRef::new(capture.path),
+                frame_number: capture.frame_number,
+                api: capture.api,
+                byte_size: capture.byte_size,
+            });
+        }
+
+        Ok(TriggerCaptureResponse {
+            pid: self.client.pid(),
+            captures,
+            process_tree: self.process_tree,
+        })
+    }
+}
+
+/// Triggers a capture over target control without launching `qrenderdoc`.
+///
+/// This is the implementation behind `RenderDocInstallation::trigger_capture_via_target_control`
+/// and the one-shot capture workflows.
+pub fn trigger_capture_native(
+    req: &TriggerCaptureRequest,
+) -> Result<TriggerCaptureResponse, TargetControlError> {
+    CaptureTargetConnection::connect(req)?.trigger(&req.trigger)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        path::PathBuf,
+        thread::JoinHandle,
+        time::Duration,
+    };
+
+    use super::enumerate::list_targets_on_ports;
+    use super::protocol::{ClientPacket, HandshakeReply, NewCapturePacket, ServerPacket};
+    use super::{
+        RemoteTarget, TARGET_CONTROL_PROTOCOL_VERSION, TargetControlClient,
+        TargetControlConnectOptions, TargetControlError, TargetControlMessage,
+        trigger_capture_native,
+    };
+    use crate::test_support::temp_path;
+    use crate::{
+        TargetControlRef, TargetProcessNode, TriggerCaptureOptions, TriggerCaptureRequest,
+    };
+
+    /// Minimal in-process stand-in for a RenderDoc-injected application.
+    struct StandInTarget {
+        stream: TcpStream,
+        rx: Vec<u8>,
+    }
+
+    impl StandInTarget {
+        fn recv(&mut self) -> ClientPacket {
+            let mut chunk = [0_u8; 1024];
+            loop {
+                if let Some((packet, used)) =
+                    ClientPacket::decode(&self.rx).expect("decode client packet")
+                {
+                    self.rx.drain(..used);
+                    return packet;
+                }
+                let read = self.stream.read(&mut chunk).expect("read from client");
+                assert!(read > 0, "client disconnected");
+                self.rx.extend_from_slice(&chunk[..read]);
+            }
+        }
+
+        fn send(&mut self, packet: ServerPacket) {
+            self.send_bytes(&packet.encode());
+        }
+
+        fn send_bytes(&mut self, bytes: &[u8]) {
+            self.stream.write_all(bytes).expect("write to client");
+        }
+
+        fn accept_handshake(&mut self) {
+            match self.recv() {
+                ClientPacket::Handshake {
+                    version,
+                    client_name,
+                    ..
+                } => {
+                    assert_eq!(version, TARGET_CONTROL_PROTOCOL_VERSION);
+                    assert_eq!(client_name, "renderdog");
+                }
+                other => panic!("expected handshake, got {other:?}"),
+            }
+            self.send_bytes(
+                &HandshakeReply::Accepted {
+                    version: TARGET_CONTROL_PROTOCOL_VERSION,
+                    target: "viewer.exe".to_string(),
+                    pid: 4242,
+                }
+                .encode(),
+            );
+        }
+    }
+
+    fn spawn_target(
+        script: impl FnOnce(&mut StandInTarget) + Send + 'static,
+    ) -> (u32, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in target");
+        let ident = u32::from(listener.local_addr().expect("local addr").port());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("accept client");
+            let mut target = StandInTarget {
+                stream,
+                rx: Vec::new(),
+            };
+            script(&mut target);
+        });
+        (ident, handle)
+    }
+
+    fn new_capture(capture_id: u32, frame_number: u32) -> NewCapturePacket {
+        NewCapturePacket {
+            capture_id,
+            frame_number,
+            timestamp: 1_700_000_000,
+            path: format!("/tmp/viewer_frame{frame_number}.rdc"),
+            byte_size: 5,
+            thumbnail: Vec::new(),
+            api: "Vulkan".to_string(),
+            title: String::new(),
+        }
+    }
+
+    fn connect(ident: u32) -> TargetControlClient {
+        TargetControlClient::connect("127.0.0.1", ident, &TargetControlConnectOptions::default())
+            .expect("connect to stand-in target")
+    }
+
+    #[test]
+    fn connect_reports_target_name_and_pid_from_handshake() {
+        let (ident, server) = spawn_target(|target| target.accept_handshake());
+
+        let client = connect(ident);
+
+        assert_eq!(client.ident(), ident);
+        assert_eq!(client.target_name(), "viewer.exe");
+        assert_eq!(client.pid(), 4242);
+        server.join().expect("stand-in target");
+    }
+
+    #[test]
+    fn connect_surfaces_busy_target() {
+        let (ident, server) = spawn_target(|target| {
+            target.recv();
+            target.send_bytes(
+                &HandshakeReply::Busy {
+                    version: TARGET_CONTROL_PROTOCOL_VERSION,
+                    target: "viewer.exe".to_string(),
+                    busy_client: "qrenderdoc".to_string(),
+                }
+                .encode(),
+            );
+        });
+
+        let err = TargetControlClient::connect(
+            "127.0.0.1",
+            ident,
+            &TargetControlConnectOptions::default(),
+        )
+        .expect_err("busy target");
+
+        assert!(matches!(
+            err,
+            TargetControlError::Busy { ref client, .. } if client == "qrenderdoc"
+        ));
+        server.join().expect("stand-in target");
+    }
+
+    #[test]
+    fn connect_reports_protocol_version_mismatch() {
+        let (ident, server) = spawn_target(|target| {
+            target.recv();
+            target.send_bytes(
+                &HandshakeReply::Accepted {
+                    version: 8,
+                    target: "viewer.exe".to_string(),
+                    pid: 4242,
+                }
+                .encode(),
+            );
+        });
+
+        let err = TargetControlClient::connect(
+            "127.0.0.1",
+            ident,
+            &TargetControlConnectOptions::default(),
+        )
+        .expect_err("old target");
+
+        assert!(matches!(
+            err,
+            TargetControlError::VersionMismatch {
+                target: 8,
+                supported: TARGET_CONTROL_PROTOCOL_VERSION
+            }
+        ));
+        server.join().expect("stand-in target");
+    }
+
+    #[test]
+    fn trigger_capture_receives_api_and_new_capture_messages() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            target.send(ServerPacket::RegisterApi {
+                api: "Vulkan".to_string(),
+                presenting: true,
+                supported: true,
+                support_message: String::new(),
+            });
+            assert_eq!(
+                target.recv(),
+                ClientPacket::TriggerCapture { num_frames: 1 }
+            );
+            target.send(ServerPacket::CaptureProgress { progress: 0.5 });
+            target.send(ServerPacket::NewCapture(new_capture(0, 120)));
+        });
+
+        let mut client = connect(ident);
+        client.trigger_capture(1).expect("trigger capture");
+
+        let mut messages = Vec::new();
+        loop {
+            let message = client
+                .receive_message(Duration::from_secs(5))
+                .expect("receive message");
+            let done = matches!(message, TargetControlMessage::NewCapture(_));
+            messages.push(message);
+            if done {
+                break;
+            }
+        }
+
+        assert!(matches!(
+            messages[0],
+            TargetControlMessage::RegisterApi(ref api) if api.api == "Vulkan"
+        ));
+        assert_eq!(messages[1], TargetControlMessage::CaptureProgress(0.5));
+        let TargetControlMessage::NewCapture(capture) = &messages[2] else {
+            panic!("expected new capture");
+        };
+        assert_eq!(capture.frame_number, 120);
+        assert_eq!(capture.path, "/tmp/viewer_frame120.rdc");
+        assert_eq!(client.api(), Some("Vulkan"));
+        server.join().expect("stand-in target");
+    }
+
+    #[test]
+    fn queue_copy_and_delete_capture_round_trip() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            assert_eq!(
+                target.recv(),
+                ClientPacket::QueueCapture {
+                    frame_number: 300,
+                    num_frames: 2
+                }
+            );
+            target.send(ServerPacket::NewCapture(new_capture(3, 300)));
+            assert_eq!(target.recv(), ClientPacket::CopyCapture { capture_id: 3 });
+            target.send(ServerPacket::CopyCapture {
+                capture_id: 3,
+                byte_len: 5,
+            });
+            // Split the capture bytes so the client has to stitch them together.
+            target.send_bytes(b"RD");
+            target.send_bytes(b"OC!");
+            assert_eq!(target.recv(), ClientPacket::DeleteCapture { capture_id: 3 });
+        });
+
+        let local_dir = temp_path("target-control");
+        let local_path = local_dir.join("copied.rdc");
+
+        let mut client = connect(ident);
+        client.queue_capture(300, 2).expect("queue capture");
+        let capture = client
+            .wait_for_new_capture(Duration::from_secs(5))
+            .expect("new capture");
+        client
+            .copy_capture(capture.capture_id, &local_path)
+            .expect("copy capture");
+
+        let copied = loop {
+            match client
+                .receive_message(Duration::from_secs(5))
+                .expect("receive message")
+            {
+                TargetControlMessage::CaptureCopied { capture_id, path } => {
+                    break (capture_id, path);
+                }
+                TargetControlMessage::Noop => panic!("timed out waiting for copy"),
+                _ => {}
+            }
+        };
+        client
+            .delete_capture(capture.capture_id)
+            .expect("delete capture");
+        server.join().expect("stand-in target");
+
+        let contents = std::fs::read(&local_path).expect("read copied capture");
+        let _ = std::fs::remove_dir_all(&local_dir);
+
+        assert_eq!(copied, (3, PathBuf::from(&local_path)));
+        assert_eq!(contents, b"RDOC!");
+    }
+
+    #[test]
+    fn receive_message_reports_noop_on_timeout_and_disconnected_on_close() {
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let (ident, server) = spawn_target(move |target| {
+            target.accept_handshake();
+            release_rx.recv().expect("release signal");
+        });
+
+        let mut client = connect(ident);
+
+        assert_eq!(
+            client
+                .receive_message(Duration::from_millis(20))
+                .expect("receive"),
+            TargetControlMessage::Noop
+        );
+
+        release_tx.send(()).expect("release stand-in target");
+        server.join().expect("stand-in target");
+
+        assert_eq!(
+            client
+                .receive_message(Duration::from_secs(5))
+                .expect("receive"),
+            TargetControlMessage::Disconnected
+        );
+        assert!(matches!(
+            client.trigger_capture(1),
+            Err(TargetControlError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn trigger_capture_native_reports_the_queued_captures() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            assert_eq!(
+                target.recv(),
+                ClientPacket::TriggerCapture { num_frames: 1 }
+            );
+            target.send(ServerPacket::NewCapture(new_capture(0, 42)));
+        });
+
+        let response = trigger_capture_native(&TriggerCaptureRequest {
+            target: TargetControlRef::new(ident),
+            trigger: TriggerCaptureOptions {
+                host: "127.0.0.1".to_string(),
+                num_frames: 1,
+                timeout_s: 5,
+                ..TriggerCaptureOptions::default()
+            },
+        })
+        .expect("trigger capture");
+        server.join().expect("stand-in target");
+
+        assert_eq!(response.pid, 4242);
+        assert_eq!(response.captures.len(), 1);
+        let capture = &response.captures[0];
+        assert_eq!(capture.capture.capture_path, "/tmp/viewer_frame42.rdc");
+        assert_eq!(capture.frame_number, 42);
+        assert_eq!(capture.api, "Vulkan");
+    }
+
+    #[test]
+    fn trigger_capture_native_collects_every_frame_of_a_multi_frame_trigger() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            assert_eq!(
+                target.recv(),
+                ClientPacket::TriggerCapture { num_frames: 3 }
+            );
+            for (capture_id, frame_number) in [(0, 10), (1, 11), (2, 12)] {
+                target.send(ServerPacket::NewCapture(new_capture(
+                    capture_id,
+                    frame_number,
+                )));
+            }
+        });
+
+        let response = trigger_capture_native(&TriggerCaptureRequest {
+            target: TargetControlRef::new(ident),
+            trigger: TriggerCaptureOptions {
+                host: "127.0.0.1".to_string(),
+                num_frames: 3,
+                timeout_s: 5,
+                ..TriggerCaptureOptions::default()
+            },
+        })
+        .expect("trigger capture");
+        server.join().expect("stand-in target");
+
+        let frames: Vec<u32> = response
+            .captures
+            .iter()
+            .map(|capture| capture.frame_number)
+            .collect();
+        assert_eq!(frames, vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn trigger_capture_native_returns_partial_captures_when_target_goes_away() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            target.recv();
+            target.send(ServerPacket::NewCapture(new_capture(0, 10)));
+        });
+
+        let response = trigger_capture_native(&TriggerCaptureRequest {
+            target: TargetControlRef::new(ident),
+            trigger: TriggerCaptureOptions {
+                host: "127.0.0.1".to_string(),
+                num_frames: 2,
+                timeout_s: 5,
+                ..TriggerCaptureOptions::default()
+            },
+        })
+        .expect("trigger capture");
+        server.join().expect("stand-in target");
+
+        assert_eq!(response.captures.len(), 1);
+        assert_eq!(response.captures[0].frame_number, 10);
+    }
+
+    #[test]
+    fn trigger_capture_native_queues_captures_at_requested_frames() {
+        let (ident, server) = spawn_target(|target| {
+            target.accept_handshake();
+            let mut queued = Vec::new();
+            for _ in 0..3 {
+                match target.recv() {
+                    ClientPacket::QueueCapture {
+                        frame_number,
+                        num_frames,
+                    } => {
+                        assert_eq!(num_frames, 1);
+                        queued.push(frame_number);
+                    }
+                    other => panic!("expected queue capture, got {other:?}"),
+                }
+            }
+            assert_eq!(queued, vec![120, 600, 1200]);
+            for (capture_id, frame_number) in queued.into_iter().enumerate() {
+                target.send(ServerPacket::NewCapture(new_capture(
+                    capture_id as u32,
+                    frame_number,
+                )));
+            }
+        });
+
+        let response = trigger_capture_native(&TriggerCaptureRequest {
+            target: TargetControlRef::new(ident),
+            trigger: TriggerCaptureOptions {
+                host: "127.0.0.1".to_string(),
+                timeout_s: 5,
+                capture_at_frames: vec![600, 120, 1200],
+                ..TriggerCaptureOptions::default()
+            },
+        })
+        .expect("queue captures");
+        server.join().expect("stand-in target");
+
+        let frames: Vec<u32> = response
+            .captures
+            .iter()
+            .map(|capture| capture.frame_number)
+            .collect();
+        assert_eq!(frames, vec![120, 600, 1200]);
+    }
+
+    #[test]
+    fn trigger_capture_native_follows_launcher_to_child_with_api() {
+        let (child_ident, child) = spawn_target(|target| {
+            target.recv();
+            target.send_bytes(
+                &HandshakeReply::Accepted {
+                    version: TARGET_CONTROL_PROTOCOL_VERSION,
+                    target: "renderer".to_string(),
+                    pid: 5151,
+                }
+                .encode(),
+            );
+            target.send(ServerPacket::RegisterApi {
+                api: "Vulkan".to_string(),
+                presenting: true,
+                supported: true,
+                support_message: String::new(),
+            });
+            assert_eq!(
+                target.recv(),
+                ClientPacket::TriggerCapture { num_frames: 1 }
+            );
+            target.send(ServerPacket::NewCapture(new_capture(0, 7)));
+        });
+        let (launcher_ident, launcher) = spawn_target(move |target| {
+            target.accept_handshake();
+            target.send(ServerPacket::NewChild {
+                pid: 5151,
+                ident: child_ident,
+            });
+        });
+
+        let response = trigger_capture_native(&TriggerCaptureRequest {
+            target: TargetControlRef::new(launcher_ident),
+            trigger: TriggerCaptureOptions {
+                host: "127.0.0.1".to_string(),
+                timeout_s: 5,
+                follow_children: true,
+                ..TriggerCaptureOptions::default()
+            },
+        })
+        .expect("trigger capture");
+        launcher.join().expect("launcher");
+        child.join().expect("child");
+
+        assert_eq!(response.pid, 5151);
+        assert_eq!(response.captures.len(), 1);
+        assert_eq!(
+            response.process_tree,
+            vec![
+                TargetProcessNode {
+                    pid: 4242,
+                    target_ident: launcher_ident,
+                    parent_pid: None,
+                    executable: "viewer.exe".to_string(),
+                    api: None,
+                    captured: false,
+                },
+                TargetProcessNode {
+                    pid: 5151,
+                    target_ident: child_ident,
+                    parent_pid: Some(4242),
+                    executable: "renderer".to_string(),
+                    api: Some("Vulkan".to_string()),
+                    captured: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn list_targets_reports_idle_target_with_api() {
+        let (ident, server) = spawn_target(|target| {
+            match target.recv() {
+                ClientPacket::Handshake {
+                    force_connection, ..
+                } => assert!(!force_connection, "probe must not take over targets"),
+                other => panic!("expected handshake, got {other:?}"),
+            }
+            target.send_bytes(
+                &HandshakeReply::Accepted {
+                    version: TARGET_CONTROL_PROTOCOL_VERSION,
+                    target: "viewer.exe".to_string(),
+                    pid: 4242,
+                }
+                .encode(),
+            );
+            target.send(ServerPacket::RegisterApi {
+                api: "D3D12".to_string(),
+                presenting: true,
+                supported: true,
+                support_message: String::new(),
+            });
+        });
+
+        let response = list_targets_on_ports("127.0.0.1", ident..=ident).expect("list targets");
+        server.join().expect("stand-in target");
+
+        assert!(response.failed.is_empty());
+        assert_eq!(
+            response.targets,
+            vec![RemoteTarget {
+                target_ident: ident,
+                executable: "viewer.exe".to_string(),
+                pid: Some(4242),
+                api: Some("D3D12".to_string()),
+                busy: false,
+                busy_client: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn list_targets_reports_busy_targets_and_skips_closed_ports() {
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("bind closed port");
+            u32::from(listener.local_addr().expect("local addr").port())
+        };
+        let (ident, server) = spawn_target(|target| {
+            target.recv();
+            target.send_bytes(
+                &HandshakeReply::Busy {
+                    version: TARGET_CONTROL_PROTOCOL_VERSION,
+                    target: "viewer.exe".to_string(),
+                    busy_client: "qrenderdoc".to_string(),
+                }
+                .encode(),
+            );
+        });
+
+        let mut targets = list_targets_on_ports("127.0.0.1", ident..=ident)
+            .expect("list busy")
+            .targets;
+        let closed = list_targets_on_ports("127.0.0.1", closed..=closed).expect("list closed");
+        targets.extend(closed.targets);
+        server.join().expect("stand-in target");
+
+        assert!(closed.failed.is_empty());
+
+        assert_eq!(targets.len(), 1);
+        assert!(targets[0].busy);
+        assert_eq!(targets[0].pid, None);
+        assert_eq!(targets[0].busy_client.as_deref(), Some("qrenderdoc"));
+    }
+
+    #[test]
+    fn list_targets_reports_probe_errors_per_port() {
+        let (ident, server) = spawn_target(|target| {
+            target.recv();
+            target.send_bytes(
+                &HandshakeReply::Accepted {
+                    version: 7,
+                    target: "old.exe".to_string(),
+                    pid: 1,
+                }
+                .encode(),
+            );
+        });
+
+        let response = list_targets_on_ports("127.0.0.1", ident..=ident).expect("list targets");
+        server.join().expect("stand-in target");
+
+        assert!(response.targets.is_empty());
+        assert_eq!(response.failed.len(), 1);
+        assert_eq!(response.failed[0].target_ident, ident);
+        assert!(
+            response.failed[0].error.contains("version 7"),
+            "{}",
+            response.failed[0].error
+        );
+    }
+}
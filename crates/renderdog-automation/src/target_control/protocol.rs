@@ -0,0 +1,808 @@
+//! Wire format for RenderDoc's target-control channel.
+//!
+//! Both ends wrap the socket in RenderDoc's `WriteSerialiser` / `ReadSerialiser` with streaming
+//! mode enabled, and every packet is one serialiser chunk:
+//!
+//! - a little-endian `u32` chunk header: the packet type in the low 16 bits (`ChunkIndexMask`),
+//!   flag bits above that;
+//! - the chunk metadata those flags announce: a callstack (`u32` frame count, then one `u64` per
+//!   frame), a thread ID (`u64`), a duration and a timestamp (`i64` microseconds each);
+//! - the chunk length, a `u64` when `Chunk64BitSize` is set and a `u32` otherwise. A streaming
+//!   writer can't seek back to patch it, so it writes 0 and the chunk ends where its last field
+//!   does;
+//! - the fields in `SERIALISE_ELEMENT` order. Integers and floats are little-endian, `bool` is one
+//!   byte, `rdcstr` is a `u32` byte length followed by UTF-8, and `bytebuf` is a `u64` length
+//!   followed by the bytes.
+//!
+//! Streaming chunks carry none of the 64-byte `ChunkAlignment` padding used in capture files.
+//! Since the length can't be used for framing, packets are delimited by decoding them, and every
+//! variable-length field is checked against [`MAX_PACKET_LEN`] before it is buffered. The capture
+//! bytes of a `CopyCapture` packet are the exception: they follow the packet as a raw stream of
+//! the announced size and are written straight to disk by the client.
+
+use thiserror::Error;
+
+/// Largest packet, string or thumbnail a target may send, excluding copied capture data.
+pub(crate) const MAX_PACKET_LEN: usize = 16 * 1024 * 1024;
+
+const CHUNK_INDEX_MASK: u32 = 0x0000_ffff;
+const CHUNK_CALLSTACK: u32 = 0x0001_0000;
+const CHUNK_THREAD_ID: u32 = 0x0002_0000;
+const CHUNK_DURATION: u32 = 0x0004_0000;
+const CHUNK_TIMESTAMP: u32 = 0x0008_0000;
+const CHUNK_64BIT_SIZE: u32 = 0x0010_0000;
+
+#[derive(Debug, Error)]
+pub(crate) enum ProtocolError {
+    /// More bytes are needed; never surfaced by the `decode` functions.
+    #[error("packet is incomplete")]
+    Incomplete,
+    #[error("{0} is not valid UTF-8")]
+    InvalidUtf8(&'static str),
+    #[error("unknown packet type {0}")]
+    UnknownPacketType(u32),
+    #[error("unexpected {actual:?} packet, expected {expected}")]
+    UnexpectedPacket {
+        actual: PacketType,
+        expected: &'static str,
+    },
+    #[error("{what} is {len} bytes, over the {MAX_PACKET_LEN} byte packet limit")]
+    TooLarge { what: &'static str, len: u64 },
+}
+
+/// RenderDoc's `PacketType`, which doubles as the chunk index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub(crate) enum PacketType {
+    Noop = 1,
+    Handshake = 2,
+    Busy = 3,
+    NewCapture = 4,
+    RegisterApi = 5,
+    TriggerCapture = 6,
+    CopyCapture = 7,
+    DeleteCapture = 8,
+    QueueCapture = 9,
+    NewChild = 10,
+    CaptureProgress = 11,
+    CycleActiveWindow = 12,
+    CapturableWindowCount = 13,
+    RequestShow = 14,
+}
+
+impl PacketType {
+    fn from_u32(value: u32) -> Result<Self, ProtocolError> {
+        Ok(match value {
+            1 => Self::Noop,
+            2 => Self::Handshake,
+            3 => Self::Busy,
+            4 => Self::NewCapture,
+            5 => Self::RegisterApi,
+            6 => Self::TriggerCapture,
+            7 => Self::CopyCapture,
+            8 => Self::DeleteCapture,
+            9 => Self::QueueCapture,
+            10 => Self::NewChild,
+            11 => Self::CaptureProgress,
+            12 => Self::CycleActiveWindow,
+            13 => Self::CapturableWindowCount,
+            14 => Self::RequestShow,
+            other => return Err(ProtocolError::UnknownPacketType(other)),
+        })
+    }
+}
+
+/// Packets sent from the controlling client to the target application.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ClientPacket {
+    Handshake {
+        version: u32,
+        client_name: String,
+        force_connection: bool,
+    },
+    TriggerCapture {
+        num_frames: u32,
+    },
+    QueueCapture {
+        frame_number: u32,
+        num_frames: u32,
+    },
+    CopyCapture {
+        capture_id: u32,
+    },
+    DeleteCapture {
+        capture_id: u32,
+    },
+    CycleActiveWindow,
+}
+
+/// The target's answer to the client handshake.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum HandshakeReply {
+    Accepted {
+        version: u32,
+        target: String,
+        pid: u32,
+    },
+    Busy {
+        version: u32,
+        target: String,
+        busy_client: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NewCapturePacket {
+    pub capture_id: u32,
+    pub frame_number: u32,
+    pub timestamp: u64,
+    pub path: String,
+    pub byte_size: u64,
+    /// JPEG thumbnail, empty when the target has none.
+    pub thumbnail: Vec<u8>,
+    pub api: String,
+    pub title: String,
+}
+
+/// Packets sent from the target application to the controlling client after the handshake.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ServerPacket {
+    Noop,
+    /// Another client took over the target.
+    Busy {
+        client_name: String,
+    },
+    NewCapture(NewCapturePacket),
+    RegisterApi {
+        api: String,
+        presenting: bool,
+        supported: bool,
+        support_message: String,
+    },
+    /// `byte_len` bytes of capture data follow the packet on the stream.
+    CopyCapture {
+        capture_id: u32,
+        byte_len: u64,
+    },
+    NewChild {
+        pid: u32,
+        ident: u32,
+    },
+    CaptureProgress {
+        progress: f32,
+    },
+    CapturableWindowCount {
+        count: u32,
+    },
+    RequestShow,
+}
+
+struct ChunkWriter {
+    buf: Vec<u8>,
+}
+
+impl ChunkWriter {
+    /// Starts a chunk the way a streaming `WriteSerialiser` does: no flags and a zero length.
+    fn new(kind: PacketType) -> Self {
+        let mut writer = Self { buf: Vec::new() };
+        writer.u32(kind as u32).u32(0);
+        writer
+    }
+
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    #[cfg(test)]
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    #[cfg(test)]
+    fn f32(&mut self, value: f32) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bool(&mut self, value: bool) -> &mut Self {
+        self.buf.push(u8::from(value));
+        self
+    }
+
+    fn str(&mut self, value: &str) -> &mut Self {
+        self.u32(value.len() as u32);
+        self.buf.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    #[cfg(test)]
+    fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.u64(value.len() as u64);
+        self.buf.extend_from_slice(value);
+        self
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+struct ChunkHeader {
+    kind: PacketType,
+    body_start: usize,
+    length: u64,
+}
+
+struct ChunkReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ChunkReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProtocolError> {
+        if self.buf.len() - self.pos < len {
+            return Err(ProtocolError::Incomplete);
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ProtocolError> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64, ProtocolError> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().expect("8 bytes"),
+        ))
+    }
+
+    fn f32(&mut self) -> Result<f32, ProtocolError> {
+        Ok(f32::from_le_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+
+    fn bool(&mut self) -> Result<bool, ProtocolError> {
+        Ok(self.take(1)?[0] != 0)
+    }
+
+    fn bounded(what: &'static str, len: u64) -> Result<usize, ProtocolError> {
+        usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= MAX_PACKET_LEN)
+            .ok_or(ProtocolError::TooLarge { what, len })
+    }
+
+    fn str(&mut self, what: &'static str) -> Result<String, ProtocolError> {
+        let len = Self::bounded(what, u64::from(self.u32()?))?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| ProtocolError::InvalidUtf8(what))
+    }
+
+    fn bytes(&mut self, what: &'static str) -> Result<Vec<u8>, ProtocolError> {
+        let len = Self::bounded(what, self.u64()?)?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn begin_chunk(&mut self) -> Result<ChunkHeader, ProtocolError> {
+        let header = self.u32()?;
+        let kind = PacketType::from_u32(header & CHUNK_INDEX_MASK)?;
+        if header & CHUNK_CALLSTACK != 0 {
+            let frames = Self::bounded("chunk callstack", u64::from(self.u32()?) * 8)?;
+            self.take(frames)?;
+        }
+        if header & CHUNK_THREAD_ID != 0 {
+            self.u64()?;
+        }
+        if header & CHUNK_DURATION != 0 {
+            self.u64()?;
+        }
+        if header & CHUNK_TIMESTAMP != 0 {
+            self.u64()?;
+        }
+        let length = if header & CHUNK_64BIT_SIZE != 0 {
+            self.u64()?
+        } else {
+            u64::from(self.u32()?)
+        };
+        Ok(ChunkHeader {
+            kind,
+            body_start: self.pos,
+            length,
+        })
+    }
+
+    /// Skips fields a newer writer appended, when the chunk declares its length.
+    fn end_chunk(&mut self, header: &ChunkHeader) -> Result<(), ProtocolError> {
+        let read = (self.pos - header.body_start) as u64;
+        if header.length > read {
+            let rest = Self::bounded("chunk", header.length - read)?;
+            self.take(rest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes one chunk from the front of `buf`, returning it with the number of bytes it used, or
+/// `None` until the whole chunk has arrived.
+fn decode_chunk<T>(
+    buf: &[u8],
+    body: impl FnOnce(&ChunkHeader, &mut ChunkReader<'_>) -> Result<T, ProtocolError>,
+) -> Result<Option<(T, usize)>, ProtocolError> {
+    let mut reader = ChunkReader { buf, pos: 0 };
+    let decoded = reader
+        .begin_chunk()
+        .and_then(|header| body(&header, &mut reader));
+    match decoded {
+        Ok(value) => Ok(Some((value, reader.pos))),
+        Err(ProtocolError::Incomplete) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+impl ClientPacket {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Handshake {
+                version,
+                client_name,
+                force_connection,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::Handshake);
+                w.u32(*version).str(client_name).bool(*force_connection);
+                w.finish()
+            }
+            Self::TriggerCapture { num_frames } => {
+                let mut w = ChunkWriter::new(PacketType::TriggerCapture);
+                w.u32(*num_frames);
+                w.finish()
+            }
+            Self::QueueCapture {
+                frame_number,
+                num_frames,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::QueueCapture);
+                w.u32(*frame_number).u32(*num_frames);
+                w.finish()
+            }
+            Self::CopyCapture { capture_id } => {
+                let mut w = ChunkWriter::new(PacketType::CopyCapture);
+                w.u32(*capture_id);
+                w.finish()
+            }
+            Self::DeleteCapture { capture_id } => {
+                let mut w = ChunkWriter::new(PacketType::DeleteCapture);
+                w.u32(*capture_id);
+                w.finish()
+            }
+            Self::CycleActiveWindow => ChunkWriter::new(PacketType::CycleActiveWindow).finish(),
+        }
+    }
+
+    // Only the stand-in target used in tests reads client packets.
+    #[cfg(test)]
+    pub(crate) fn decode(buf: &[u8]) -> Result<Option<(Self, usize)>, ProtocolError> {
+        decode_chunk(buf, |header, r| {
+            let packet = match header.kind {
+                PacketType::Handshake => Self::Handshake {
+                    version: r.u32()?,
+                    client_name: r.str("client name")?,
+                    force_connection: r.bool()?,
+                },
+                PacketType::TriggerCapture => Self::TriggerCapture {
+                    num_frames: r.u32()?,
+                },
+                PacketType::QueueCapture => Self::QueueCapture {
+                    frame_number: r.u32()?,
+                    num_frames: r.u32()?,
+                },
+                PacketType::CopyCapture => Self::CopyCapture {
+                    capture_id: r.u32()?,
+                },
+                PacketType::DeleteCapture => Self::DeleteCapture {
+                    capture_id: r.u32()?,
+                },
+                PacketType::CycleActiveWindow => Self::CycleActiveWindow,
+                actual => {
+                    return Err(ProtocolError::UnexpectedPacket {
+                        actual,
+                        expected: "a client packet",
+                    });
+                }
+            };
+            r.end_chunk(header)?;
+            Ok(packet)
+        })
+    }
+}
+
+impl HandshakeReply {
+    // Only the stand-in target used in tests writes handshake replies.
+    #[cfg(test)]
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Accepted {
+                version,
+                target,
+                pid,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::Handshake);
+                w.u32(*version).str(target).u32(*pid);
+                w.finish()
+            }
+            Self::Busy {
+                version,
+                target,
+                busy_client,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::Busy);
+                w.u32(*version).str(target).str(busy_client);
+                w.finish()
+            }
+        }
+    }
+
+    pub(crate) fn decode(buf: &[u8]) -> Result<Option<(Self, usize)>, ProtocolError> {
+        decode_chunk(buf, |header, r| {
+            let reply = match header.kind {
+                PacketType::Handshake => Self::Accepted {
+                    version: r.u32()?,
+                    target: r.str("target name")?,
+                    pid: r.u32()?,
+                },
+                PacketType::Busy => Self::Busy {
+                    version: r.u32()?,
+                    target: r.str("target name")?,
+                    busy_client: r.str("busy client name")?,
+                },
+                actual => {
+                    return Err(ProtocolError::UnexpectedPacket {
+                        actual,
+                        expected: "Handshake or Busy",
+                    });
+                }
+            };
+            r.end_chunk(header)?;
+            Ok(reply)
+        })
+    }
+}
+
+impl ServerPacket {
+    // Only the stand-in target used in tests writes server packets. `CopyCapture` data is
+    // appended by the caller.
+    #[cfg(test)]
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Noop => ChunkWriter::new(PacketType::Noop).finish(),
+            Self::Busy { client_name } => {
+                let mut w = ChunkWriter::new(PacketType::Busy);
+                w.str(client_name);
+                w.finish()
+            }
+            Self::NewCapture(capture) => {
+                let mut w = ChunkWriter::new(PacketType::NewCapture);
+                w.u32(capture.capture_id)
+                    .u32(capture.frame_number)
+                    .u64(capture.timestamp)
+                    .str(&capture.path)
+                    .u64(capture.byte_size)
+                    .bytes(&capture.thumbnail)
+                    .str(&capture.api)
+                    .str(&capture.title);
+                w.finish()
+            }
+            Self::RegisterApi {
+                api,
+                presenting,
+                supported,
+                support_message,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::RegisterApi);
+                w.str(api)
+                    .bool(*presenting)
+                    .bool(*supported)
+                    .str(support_message);
+                w.finish()
+            }
+            Self::CopyCapture {
+                capture_id,
+                byte_len,
+            } => {
+                let mut w = ChunkWriter::new(PacketType::CopyCapture);
+                w.u32(*capture_id).u64(*byte_len);
+                w.finish()
+            }
+            Self::NewChild { pid, ident } => {
+                let mut w = ChunkWriter::new(PacketType::NewChild);
+                w.u32(*pid).u32(*ident);
+                w.finish()
+            }
+            Self::CaptureProgress { progress } => {
+                let mut w = ChunkWriter::new(PacketType::CaptureProgress);
+                w.f32(*progress);
+                w.finish()
+            }
+            Self::CapturableWindowCount { count } => {
+                let mut w = ChunkWriter::new(PacketType::CapturableWindowCount);
+                w.u32(*count);
+                w.finish()
+            }
+            Self::RequestShow => ChunkWriter::new(PacketType::RequestShow).finish(),
+        }
+    }
+
+    pub(crate) fn decode(buf: &[u8]) -> Result<Option<(Self, usize)>, ProtocolError> {
+        decode_chunk(buf, |header, r| {
+            let packet = match header.kind {
+                PacketType::Noop => Self::Noop,
+                PacketType::Busy => Self::Busy {
+                    client_name: r.str("busy client name")?,
+                },
+                PacketType::NewCapture => Self::NewCapture(NewCapturePacket {
+                    capture_id: r.u32()?,
+                    frame_number: r.u32()?,
+                    timestamp: r.u64()?,
+                    path: r.str("capture path")?,
+                    byte_size: r.u64()?,
+                    thumbnail: r.bytes("capture thumbnail")?,
+                    api: r.str("capture API")?,
+                    title: r.str("capture title")?,
+                }),
+                PacketType::RegisterApi => Self::RegisterApi {
+                    api: r.str("API name")?,
+                    presenting: r.bool()?,
+                    supported: r.bool()?,
+                    support_message: r.str("API support message")?,
+                },
+                // The capture bytes stream after this header and are not part of the packet.
+                PacketType::CopyCapture => {
+                    return Ok(Self::CopyCapture {
+                        capture_id: r.u32()?,
+                        byte_len: r.u64()?,
+                    });
+                }
+                PacketType::NewChild => Self::NewChild {
+                    pid: r.u32()?,
+                    ident: r.u32()?,
+                },
+                PacketType::CaptureProgress => Self::CaptureProgress { progress: r.f32()? },
+                PacketType::CapturableWindowCount => {
+                    Self::CapturableWindowCount { count: r.u32()? }
+                }
+                PacketType::RequestShow => Self::RequestShow,
+                actual => {
+                    return Err(ProtocolError::UnexpectedPacket {
+                        actual,
+                        expected: "a target packet",
+                    });
+                }
+            };
+            r.end_chunk(header)?;
+            Ok(packet)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientPacket, HandshakeReply, MAX_PACKET_LEN, NewCapturePacket, PacketType, ProtocolError,
+        ServerPacket,
+    };
+
+    // Fixtures spell out the serialiser layout byte by byte rather than going through the
+    // encoder, so a change to the encoder can't silently change what the tests expect.
+
+    /// Client handshake: version 9, client name "renderdog", not forcing the connection.
+    const CLIENT_HANDSHAKE: &[u8] = &[
+        0x02, 0x00, 0x00, 0x00, // chunk header: ePacket_Handshake, no flags
+        0x00, 0x00, 0x00, 0x00, // streaming chunk length
+        0x09, 0x00, 0x00, 0x00, // TargetControlProtocolVersion
+        0x09, 0x00, 0x00, 0x00, b'r', b'e', b'n', b'd', b'e', b'r', b'd', b'o',
+        b'g', // clientName
+        0x00, // forceConnection
+    ];
+
+    /// Target handshake reply: version 9, target "viewer.exe", PID 4242.
+    const TARGET_HANDSHAKE: &[u8] = &[
+        0x02, 0x00, 0x00, 0x00, // chunk header: ePacket_Handshake
+        0x00, 0x00, 0x00, 0x00, // streaming chunk length
+        0x09, 0x00, 0x00, 0x00, // version
+        0x0a, 0x00, 0x00, 0x00, b'v', b'i', b'e', b'w', b'e', b'r', b'.', b'e', b'x', b'e', 0x92,
+        0x10, 0x00, 0x00, // pid
+    ];
+
+    /// Busy reply to a handshake: version 8, target "viewer", busy client "qrenderdoc".
+    const TARGET_BUSY: &[u8] = &[
+        0x03, 0x00, 0x00, 0x00, // chunk header: ePacket_Busy
+        0x00, 0x00, 0x00, 0x00, // streaming chunk length
+        0x08, 0x00, 0x00, 0x00, // version
+        0x06, 0x00, 0x00, 0x00, b'v', b'i', b'e', b'w', b'e', b'r', // target
+        0x0a, 0x00, 0x00, 0x00, b'q', b'r', b'e', b'n', b'd', b'e', b'r', b'd', b'o', b'c',
+    ];
+
+    /// NewCapture for capture 7 at frame 120 with a 3-byte thumbnail.
+    const NEW_CAPTURE: &[u8] = &[
+        0x04, 0x00, 0x00, 0x00, // chunk header: ePacket_NewCapture
+        0x00, 0x00, 0x00, 0x00, // streaming chunk length
+        0x07, 0x00, 0x00, 0x00, // capture id
+        0x78, 0x00, 0x00, 0x00, // frame number
+        0x00, 0xf1, 0x53, 0x65, 0x00, 0x00, 0x00, 0x00, // timestamp 1_700_000_000
+        0x0a, 0x00, 0x00, 0x00, b'/', b't', b'm', b'p', b'/', b'a', b'.', b'r', b'd', b'c', 0x00,
+        0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // byte size 4096
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xd8, 0xff, // thumbnail
+        0x06, 0x00, 0x00, 0x00, b'V', b'u', b'l', b'k', b'a', b'n', // api
+        0x00, 0x00, 0x00, 0x00, // title
+    ];
+
+    /// Capture progress 0.5, written by a serialiser recording thread IDs and timestamps, with
+    /// an explicit length covering one byte of padding.
+    const PROGRESS_WITH_METADATA: &[u8] = &[
+        0x0b, 0x00, 0x0a, 0x00, // ePacket_CaptureProgress | ChunkThreadID | ChunkTimestamp
+        0x2a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // thread id
+        0x40, 0x42, 0x0f, 0x00, 0x00, 0x00, 0x00, 0x00, // timestamp
+        0x05, 0x00, 0x00, 0x00, // chunk length
+        0x00, 0x00, 0x00, 0x3f, // progress
+        0x00, // padding
+    ];
+
+    #[test]
+    fn client_handshake_matches_fixture() {
+        let encoded = ClientPacket::Handshake {
+            version: 9,
+            client_name: "renderdog".to_string(),
+            force_connection: false,
+        }
+        .encode();
+
+        assert_eq!(encoded, CLIENT_HANDSHAKE);
+    }
+
+    #[test]
+    fn handshake_replies_decode_from_fixtures() {
+        assert_eq!(
+            HandshakeReply::decode(TARGET_HANDSHAKE).expect("decode handshake"),
+            Some((
+                HandshakeReply::Accepted {
+                    version: 9,
+                    target: "viewer.exe".to_string(),
+                    pid: 4242,
+                },
+                TARGET_HANDSHAKE.len()
+            ))
+        );
+        assert_eq!(
+            HandshakeReply::decode(TARGET_BUSY).expect("decode busy"),
+            Some((
+                HandshakeReply::Busy {
+                    version: 8,
+                    target: "viewer".to_string(),
+                    busy_client: "qrenderdoc".to_string(),
+                },
+                TARGET_BUSY.len()
+            ))
+        );
+    }
+
+    #[test]
+    fn server_packets_decode_from_fixtures() {
+        let (packet, used) = ServerPacket::decode(NEW_CAPTURE)
+            .expect("decode new capture")
+            .expect("complete packet");
+        assert_eq!(used, NEW_CAPTURE.len());
+        assert_eq!(
+            packet,
+            ServerPacket::NewCapture(NewCapturePacket {
+                capture_id: 7,
+                frame_number: 120,
+                timestamp: 1_700_000_000,
+                path: "/tmp/a.rdc".to_string(),
+                byte_size: 4096,
+                thumbnail: vec![0xff, 0xd8, 0xff],
+                api: "Vulkan".to_string(),
+                title: String::new(),
+            })
+        );
+        assert_eq!(packet.encode(), NEW_CAPTURE);
+
+        assert_eq!(
+            ServerPacket::decode(PROGRESS_WITH_METADATA).expect("decode progress"),
+            Some((
+                ServerPacket::CaptureProgress { progress: 0.5 },
+                PROGRESS_WITH_METADATA.len()
+            ))
+        );
+    }
+
+    #[test]
+    fn decode_waits_for_complete_packet() {
+        for len in 0..NEW_CAPTURE.len() {
+            assert_eq!(
+                ServerPacket::decode(&NEW_CAPTURE[..len]).expect("partial packet"),
+                None,
+                "{len} bytes"
+            );
+        }
+
+        let mut stream = NEW_CAPTURE.to_vec();
+        stream.extend_from_slice(&ServerPacket::Noop.encode());
+        let (_, used) = ServerPacket::decode(&stream)
+            .expect("decode")
+            .expect("complete packet");
+        assert_eq!(
+            ServerPacket::decode(&stream[used..]).expect("decode noop"),
+            Some((ServerPacket::Noop, 8))
+        );
+    }
+
+    #[test]
+    fn copy_capture_header_stops_before_streamed_data() {
+        let mut stream = ServerPacket::CopyCapture {
+            capture_id: 3,
+            byte_len: 5,
+        }
+        .encode();
+        stream.extend_from_slice(b"RDOC!");
+
+        assert_eq!(
+            ServerPacket::decode(&stream).expect("decode copy"),
+            Some((
+                ServerPacket::CopyCapture {
+                    capture_id: 3,
+                    byte_len: 5
+                },
+                stream.len() - 5
+            ))
+        );
+    }
+
+    #[test]
+    fn client_packets_round_trip() {
+        let packets = [
+            ClientPacket::TriggerCapture { num_frames: 2 },
+            ClientPacket::QueueCapture {
+                frame_number: 120,
+                num_frames: 1,
+            },
+            ClientPacket::CopyCapture { capture_id: 3 },
+            ClientPacket::DeleteCapture { capture_id: 3 },
+            ClientPacket::CycleActiveWindow,
+        ];
+
+        for packet in packets {
+            let encoded = packet.encode();
+            assert_eq!(
+                ClientPacket::decode(&encoded).expect("decode client packet"),
+                Some((packet, encoded.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn decode_rejects_oversized_unknown_and_unexpected_packets() {
+        let mut oversized = vec![0x03, 0, 0, 0, 0, 0, 0, 0];
+        oversized.extend_from_slice(&(MAX_PACKET_LEN as u32 + 1).to_le_bytes());
+        assert!(matches!(
+            ServerPacket::decode(&oversized),
+            Err(ProtocolError::TooLarge { .. })
+        ));
+
+        assert!(matches!(
+            ServerPacket::decode(&[99, 0, 0, 0, 0, 0, 0, 0]),
+            Err(ProtocolError::UnknownPacketType(99))
+        ));
+        assert!(matches!(
+            HandshakeReply::decode(&ServerPacket::Noop.encode()),
+            Err(ProtocolError::UnexpectedPacket {
+                actual: PacketType::Noop,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+//! Fixtures shared by the crate's unit tests.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Value, json};
+
+use crate::{EventFlags, FoundEvent, MarkerPath};
+
+/// A path under the system temp dir that no other test (or test run) uses. Nothing is created.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("renderdog-{name}-{}-{nanos}", std::process::id()))
+}
+
+/// A fresh, empty directory at [`temp_path`].
+pub(crate) fn fixture_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    std::fs::create_dir_all(&dir).expect("create fixture dir");
+    dir
+}
+
+pub(crate) fn write_jsonl(path: &Path, records: &[Value]) {
+    let lines: Vec<String> = records.iter().map(|record| record.to_string()).collect();
+    std::fs::write(path, lines.join("\n") + "\n").expect("write fixture");
+}
+
+/// One `.actions.jsonl` record of a childless action.
+pub(crate) fn action(event_id: u32, name: &str, flags: u32, marker_path: &[&str]) -> Value {
+    json!({
+        "event_id": event_id,
+        "parent_event_id": null,
+        "depth": marker_path.len(),
+        "name": name,
+        "flags": flags,
+        "marker_path": marker_path,
+        "num_children": 0,
+    })
+}
+
+fn event(event_id: u32, name: &str, flags: u32, marker_path: &[&str]) -> FoundEvent {
+    FoundEvent {
+        event_id,
+        parent_event_id: None,
+        depth: marker_path.len() as u32,
+        name: name.into(),
+        flags: EventFlags::new(flags),
+        marker_path: MarkerPath(marker_path.iter().map(|s| s.to_string()).collect()),
+    }
+}
+
+/// A small frame: a shadow pass, an opaque pass with a nested culling dispatch, and a present
+/// outside any marker.
+pub(crate) fn frame_events() -> Vec<FoundEvent> {
+    vec![
+        event(1, "Shadows", 0x0040, &["Shadows"]),
+        event(2, "DrawIndexed(36)", 0x0002 | 0x010000, &["Shadows"]),
+        event(3, "Opaque", 0x0040, &["Opaque"]),
+        event(
+            4,
+            "DrawIndexedInstanced(36, 8)",
+            0x0002 | 0x030000,
+            &["Opaque"],
+        ),
+        event(5, "DrawIndirect", 0x0002 | 0x080000, &["Opaque"]),
+        event(6, "Dispatch(8, 8, 1)", 0x0004, &["Opaque", "Cull"]),
+        event(7, "Present", 0x0100, &[]),
+    ]
+}
+
+/// [`frame_events`] as `.actions.jsonl` records.
+pub(crate) fn frame_actions() -> Vec<Value> {
+    frame_events()
+        .iter()
+        .map(|event| {
+            let mut record = serde_json::to_value(event).expect("serialize action");
+            record["num_children"] = json!(0);
+            record
+        })
+        .collect()
+}
@@ -0,0 +1,458 @@
+use std::path::Path;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{EventFlags, FoundEvent};
+use crate::{ArtifactReadError, read_actions_jsonl};
+
+const PUSH_MARKER: u32 = 0x0040;
+
+/// Composable predicate over action events.
+///
+/// Requests carry it as `query` next to the plain [`EventFilter`](super::EventFilter) fields, which
+/// still apply first. The same AST is evaluated by the qrenderdoc job scripts and, offline, by
+/// [`EventQuery::matcher`] over exported `actions.jsonl` records.
+///
+/// Regexes match anywhere in the text. The `regex` crate's syntax is authoritative: every job
+/// compiles the query with [`EventQuery::matcher`] before `qrenderdoc` starts, so patterns it
+/// rejects (look-around, backreferences) fail up front. The job scripts then match with Python
+/// `re`, which agrees on the common subset (literals, classes, anchors, repetition, `(?i)`) but
+/// not everywhere: `$` also matches before a trailing newline in `re`, and `regex`-only syntax such
+/// as `\p{..}` classes or `[a&&b]` set operations doesn't mean the same thing there. Stick to the
+/// common subset when live and offline results must agree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EventQuery {
+    And {
+        queries: Vec<EventQuery>,
+    },
+    Or {
+        queries: Vec<EventQuery>,
+    },
+    Not {
+        query: Box<EventQuery>,
+    },
+    NameContains {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    NameRegex {
+        pattern: String,
+    },
+    /// Substring of the `/`-joined marker path.
+    MarkerContains {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    MarkerRegex {
+        pattern: String,
+    },
+    /// The joined marker path equals `prefix` or lies below it.
+    MarkerPrefix {
+        prefix: String,
+    },
+    /// The innermost marker enclosing the event is named `name`.
+    ParentMarker {
+        name: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    EventIdRange {
+        #[serde(default)]
+        min: Option<u32>,
+        #[serde(default)]
+        max: Option<u32>,
+    },
+    Depth {
+        #[serde(default)]
+        min: Option<u32>,
+        #[serde(default)]
+        max: Option<u32>,
+    },
+    /// Flag names as reported in [`EventFlags::names`], e.g. `Indexed`, `Indirect`, `Instanced`.
+    Flags {
+        #[serde(default)]
+        required: Vec<String>,
+        #[serde(default)]
+        forbidden: Vec<String>,
+    },
+    /// Draws, dispatches, mesh dispatches, and ray dispatches.
+    Drawcall,
+    /// Only the `index`-th (0-based) event matching `query`, in walk order.
+    ///
+    /// Every sub-query is evaluated for every event, so the count does not depend on where
+    /// `nth` sits inside `and`/`or`.
+    Nth {
+        index: u32,
+        query: Box<EventQuery>,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum EventQueryError {
+    #[error("invalid regex {pattern:?}: {source}")]
+    InvalidRegex {
+        pattern: String,
+        source: regex::Error,
+    },
+    #[error("unknown event flag: {0}")]
+    UnknownFlag(String),
+    #[error(transparent)]
+    ReadActions(#[from] ArtifactReadError),
+}
+
+impl EventQuery {
+    /// Validates the query and prepares it for evaluation.
+    pub fn matcher(&self) -> Result<EventQueryMatcher, EventQueryError> {
+        Ok(EventQueryMatcher {
+            root: CompiledQuery::compile(self)?,
+        })
+    }
+}
+
+/// A compiled [`EventQuery`]. Feed it events in walk order; `nth` keeps count across calls.
+#[derive(Debug)]
+pub struct EventQueryMatcher {
+    root: CompiledQuery,
+}
+
+impl EventQueryMatcher {
+    pub fn matches(&mut self, event: &FoundEvent) -> bool {
+        self.root.matches(event)
+    }
+}
+
+#[derive(Debug)]
+enum CompiledQuery {
+    And(Vec<CompiledQuery>),
+    Or(Vec<CompiledQuery>),
+    Not(Box<CompiledQuery>),
+    Contains {
+        field: TextField,
+        value: String,
+        case_sensitive: bool,
+    },
+    Regex {
+        field: TextField,
+        regex: Regex,
+    },
+    MarkerPrefix(String),
+    ParentMarker {
+        name: String,
+        case_sensitive: bool,
+    },
+    EventIdRange(Option<u32>, Option<u32>),
+    Depth(Option<u32>, Option<u32>),
+    Flags {
+        required: u32,
+        forbidden: u32,
+    },
+    Drawcall,
+    Nth {
+        index: u32,
+        seen: u32,
+        query: Box<CompiledQuery>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TextField {
+    Name,
+    MarkerPath,
+}
+
+impl CompiledQuery {
+    fn compile(query: &EventQuery) -> Result<Self, EventQueryError> {
+        let compile_all = |queries: &[EventQuery]| {
+            queries
+                .iter()
+                .map(Self::compile)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let regex = |pattern: &str| {
+            Regex::new(pattern).map_err(|source| EventQueryError::InvalidRegex {
+                pattern: pattern.to_string(),
+                source,
+            })
+        };
+        let flag_bits = |names: &[String]| {
+            names.iter().try_fold(0_u32, |bits, name| {
+                EventFlags::from_name(name)
+                    .map(|flag| bits | flag.bits)
+                    .ok_or_else(|| EventQueryError::UnknownFlag(name.clone()))
+            })
+        };
+
+        Ok(match query {
+            EventQuery::And { queries } => Self::And(compile_all(queries)?),
+            EventQuery::Or { queries } => Self::Or(compile_all(queries)?),
+            EventQuery::Not { query } => Self::Not(Box::new(Self::compile(query)?)),
+            EventQuery::NameContains {
+                value,
+                case_sensitive,
+            } => Self::Contains {
+                field: TextField::Name,
+                value: fold_case(value, *case_sensitive),
+                case_sensitive: *case_sensitive,
+            },
+            EventQuery::NameRegex { pattern } => Self::Regex {
+                field: TextField::Name,
+                regex: regex(pattern)?,
+            },
+            EventQuery::MarkerContains {
+                value,
+                case_sensitive,
+            } => Self::Contains {
+                field: TextField::MarkerPath,
+                value: fold_case(value, *case_sensitive),
+                case_sensitive: *case_sensitive,
+            },
+            EventQuery::MarkerRegex { pattern } => Self::Regex {
+                field: TextField::MarkerPath,
+                regex: regex(pattern)?,
+            },
+            EventQuery::MarkerPrefix { prefix } => Self::MarkerPrefix(prefix.clone()),
+            EventQuery::ParentMarker {
+                name,
+                case_sensitive,
+            } => Self::ParentMarker {
+                name: fold_case(name, *case_sensitive),
+                case_sensitive: *case_sensitive,
+            },
+            EventQuery::EventIdRange { min, max } => Self::EventIdRange(*min, *max),
+            EventQuery::Depth { min, max } => Self::Depth(*min, *max),
+            EventQuery::Flags {
+                required,
+                forbidden,
+            } => Self::Flags {
+                required: flag_bits(required)?,
+                forbidden: flag_bits(forbidden)?,
+            },
+            EventQuery::Drawcall => Self::Drawcall,
+            EventQuery::Nth { index, query } => Self::Nth {
+                index: *index,
+                seen: 0,
+                query: Box::new(Self::compile(query)?),
+            },
+        })
+    }
+
+    fn matches(&mut self, event: &FoundEvent) -> bool {
+        match self {
+            // No short-circuiting: nested `nth` counters must see every event.
+            Self::And(queries) => {
+                let mut all = true;
+                for query in queries {
+                    all &= query.matches(event);
+                }
+                all
+            }
+            Self::Or(queries) => {
+                let mut any = false;
+                for query in queries {
+                    any |= query.matches(event);
+                }
+                any
+            }
+            Self::Not(query) => !query.matches(event),
+            Self::Contains {
+                field,
+                value,
+                case_sensitive,
+            } => fold_case(&field.text(event), *case_sensitive).contains(value.as_str()),
+            Self::Regex { field, regex } => regex.is_match(&field.text(event)),
+            Self::MarkerPrefix(prefix) => {
+                let joined = event.marker_path.joined();
+                joined == *prefix
+                    || joined
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }
+            Self::ParentMarker {
+                name,
+                case_sensitive,
+            } => parent_marker(event)
+                .is_some_and(|marker| fold_case(marker, *case_sensitive) == *name),
+            Self::EventIdRange(min, max) => in_range(event.event_id, *min, *max),
+            Self::Depth(min, max) => in_range(event.depth, *min, *max),
+            Self::Flags {
+                required,
+                forbidden,
+            } => event.flags.bits & *required == *required && event.flags.bits & *forbidden == 0,
+            Self::Drawcall => event.flags.is_drawcall_like(),
+            Self::Nth { index, seen, query } => {
+                if !query.matches(event) {
+                    return false;
+                }
+                *seen += 1;
+                *seen - 1 == *index
+            }
+        }
+    }
+}
+
+impl TextField {
+    fn text(self, event: &FoundEvent) -> String {
+        match self {
+            Self::Name => event.name.clone(),
+            Self::MarkerPath => event.marker_path.joined(),
+        }
+    }
+}
+
+pub(crate) fn fold_case(value: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        value.to_string()
+    } else {
+        value.to_lowercase()
+    }
+}
+
+fn in_range(value: u32, min: Option<u32>, max: Option<u32>) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+// A push marker's own name is the last marker path entry; its parent is the one before.
+fn parent_marker(event: &FoundEvent) -> Option<&str> {
+    let path = &event.marker_path.0;
+    let own = usize::from(event.flags.bits & PUSH_MARKER != 0);
+    path.len()
+        .checked_sub(own + 1)
+        .map(|index| path[index].as_str())
+}
+
+/// Evaluates `query` over an exported `actions.jsonl`, returning matching events in file order.
+pub fn query_actions_jsonl(
+    path: &Path,
+    query: &EventQuery,
+) -> Result<Vec<FoundEvent>, EventQueryError> {
+    let mut matcher = query.matcher()?;
+
+    let mut matches = Vec::new();
+    for record in read_actions_jsonl(path)? {
+        let event = FoundEvent::from(record?);
+        if matcher.matches(&event) {
+            matches.push(event);
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{EventQuery, EventQueryError, query_actions_jsonl};
+    use crate::test_support::{frame_actions, frame_events, temp_path, write_jsonl};
+
+    fn matching_ids(query: serde_json::Value) -> Vec<u32> {
+        let query: EventQuery = serde_json::from_value(query).expect("query");
+        let mut matcher = query.matcher().expect("matcher");
+        frame_events()
+            .iter()
+            .filter(|event| matcher.matches(event))
+            .map(|event| event.event_id)
+            .collect()
+    }
+
+    #[test]
+    fn event_query_combines_flags_markers_and_boolean_ops() {
+        assert_eq!(
+            matching_ids(json!({
+                "op": "and",
+                "queries": [
+                    {"op": "drawcall"},
+                    {"op": "flags", "required": ["Indexed"], "forbidden": ["Instanced"]},
+                ],
+            })),
+            [2]
+        );
+        assert_eq!(
+            matching_ids(json!({
+                "op": "or",
+                "queries": [
+                    {"op": "name_regex", "pattern": "(?i)^drawindirect"},
+                    {"op": "parent_marker", "name": "cull"},
+                ],
+            })),
+            [5, 6]
+        );
+        assert_eq!(
+            matching_ids(json!({
+                "op": "and",
+                "queries": [
+                    {"op": "marker_prefix", "prefix": "Opaque"},
+                    {"op": "not", "query": {"op": "depth", "max": 1}},
+                ],
+            })),
+            [6]
+        );
+        // A push marker's parent is the marker around it, not itself.
+        assert_eq!(
+            matching_ids(json!({"op": "parent_marker", "name": "Opaque"})),
+            [4, 5]
+        );
+    }
+
+    #[test]
+    fn event_query_nth_counts_independently_of_short_circuiting() {
+        assert_eq!(
+            matching_ids(json!({"op": "nth", "index": 1, "query": {"op": "drawcall"}})),
+            [4]
+        );
+        assert_eq!(
+            matching_ids(json!({
+                "op": "and",
+                "queries": [
+                    {"op": "event_id_range", "min": 5},
+                    {"op": "nth", "index": 2, "query": {"op": "drawcall"}},
+                ],
+            })),
+            [5]
+        );
+    }
+
+    #[test]
+    fn event_query_rejects_unknown_flags_and_bad_regexes() {
+        let query = EventQuery::Flags {
+            required: vec!["Sparkly".into()],
+            forbidden: Vec::new(),
+        };
+        assert!(matches!(
+            query.matcher(),
+            Err(EventQueryError::UnknownFlag(flag)) if flag == "Sparkly"
+        ));
+
+        let query = EventQuery::NameRegex {
+            pattern: "(".into(),
+        };
+        assert!(matches!(
+            query.matcher(),
+            Err(EventQueryError::InvalidRegex { .. })
+        ));
+    }
+
+    #[test]
+    fn query_actions_jsonl_filters_exported_records() {
+        let path = temp_path("event-query").with_extension("actions.jsonl");
+        write_jsonl(&path, &frame_actions());
+
+        let matches = query_actions_jsonl(
+            &path,
+            &EventQuery::MarkerContains {
+                value: "opaque/cull".into(),
+                case_sensitive: false,
+            },
+        )
+        .expect("query actions");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].event_id, 6);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
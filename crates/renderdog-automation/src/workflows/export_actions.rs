@@ -11,6 +11,7 @@ impl RenderDocInstallation {
         cwd: &Path,
         req: &ExportActionsRequest,
     ) -> Result<ExportActionsResponse, QRenderDocJobError> {
+        req.filter.validate()?;
         self.run_qrenderdoc_job(cwd, EXPORT_ACTIONS_JOB, req)
     }
 }
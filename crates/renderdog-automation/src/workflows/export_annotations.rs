@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::EXPORT_ANNOTATIONS_JOB;
+use crate::{QRenderDocJobError, RenderDocInstallation};
+
+use super::{ExportAnnotationsRequest, ExportAnnotationsResponse};
+
+#[derive(Debug, Error)]
+pub enum ExportAnnotationsError {
+    #[error("failed to create output dir: {0}")]
+    CreateOutputDir(std::io::Error),
+    #[error("export job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+}
+
+impl RenderDocInstallation {
+    pub fn export_annotations(
+        &self,
+        cwd: &Path,
+        req: &ExportAnnotationsRequest,
+    ) -> Result<ExportAnnotationsResponse, ExportAnnotationsError> {
+        self.run_qrenderdoc_job_in_cwd(cwd, EXPORT_ANNOTATIONS_JOB, req)
+    }
+
+    // Bundle exports already normalized the capture and output target.
+    pub(super) fn export_annotations_prepared(
+        &self,
+        cwd: &Path,
+        req: &ExportAnnotationsRequest,
+    ) -> Result<ExportAnnotationsResponse, QRenderDocJobError> {
+        req.filter.validate()?;
+        self.run_qrenderdoc_job(cwd, EXPORT_ANNOTATIONS_JOB, req)
+    }
+}
@@ -11,6 +11,7 @@ impl RenderDocInstallation {
         cwd: &Path,
         req: &ExportBindingsIndexRequest,
     ) -> Result<ExportBindingsIndexResponse, QRenderDocJobError> {
+        req.filter.validate()?;
         self.run_qrenderdoc_job(cwd, EXPORT_BINDINGS_INDEX_JOB, req)
     }
 }
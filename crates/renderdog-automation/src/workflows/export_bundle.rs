@@ -4,7 +4,8 @@ use thiserror::Error;
 
 use super::{
     BundleExportArtifacts, CapturePostActionOutputs, CapturePostActions, ExportActionsRequest,
-    ExportBindingsIndexRequest, ExportBundleRequest, ExportBundleResponse,
+    ExportAnnotationsRequest, ExportBindingsIndexRequest, ExportBundleRequest,
+    ExportBundleResponse,
 };
 use crate::{
     OpenCaptureUiError, QRenderDocJobError, RenderDocInstallation, path_to_api_string,
@@ -42,6 +43,7 @@ fn default_thumbnail_output_path(actions_jsonl_path: &str) -> PathBuf {
 struct PreparedBundleExport {
     actions: ExportActionsRequest,
     bindings: ExportBindingsIndexRequest,
+    annotations: Option<ExportAnnotationsRequest>,
     post_actions: CapturePostActions,
 }
 
@@ -62,6 +64,13 @@ impl PreparedBundleExport {
                 drawcall_scope: req.bundle.drawcall_scope,
                 filter: req.bundle.filter.clone(),
             },
+            annotations: req.bundle.annotations.include_annotations.then(|| {
+                ExportAnnotationsRequest {
+                    capture: capture.clone(),
+                    output: output.clone(),
+                    filter: req.bundle.filter.clone(),
+                }
+            }),
             bindings: ExportBindingsIndexRequest {
                 capture,
                 output,
@@ -80,11 +89,12 @@ impl PreparedBundleExport {
         self,
         actions: super::ExportActionsResponse,
         bindings: super::ExportBindingsIndexResponse,
+        annotations: Option<super::ExportAnnotationsResponse>,
         post_actions: CapturePostActionOutputs,
     ) -> ExportBundleResponse {
         ExportBundleResponse::from_parts(
             self.actions.capture.capture_path,
-            BundleExportArtifacts::from_parts(actions, bindings, post_actions),
+            BundleExportArtifacts::from_parts(actions, bindings, annotations, post_actions),
         )
     }
 }
@@ -121,6 +131,11 @@ impl RenderDocInstallation {
 
         let actions = self.export_actions(cwd, &prepared.actions)?;
         let bindings = self.export_bindings_index(cwd, &prepared.bindings)?;
+        let annotations = prepared
+            .annotations
+            .as_ref()
+            .map(|req| self.export_annotations_prepared(cwd, req))
+            .transpose()?;
 
         let post_action_plan = PreparedCapturePostActions::resolved_in_cwd(
             cwd,
@@ -130,7 +145,7 @@ impl RenderDocInstallation {
         let post_actions =
             self.run_capture_post_actions(prepared.capture_path(), &post_action_plan)?;
 
-        Ok(prepared.into_response(actions, bindings, post_actions))
+        Ok(prepared.into_response(actions, bindings, annotations, post_actions))
     }
 
     fn run_capture_post_actions(
@@ -0,0 +1,846 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{Connection, OpenFlags, params, types::ValueRef};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    BindingsExportOptions, BundleExportOptions, CaptureInput, CaptureRef, ExportBundleError,
+    ExportBundleRequest, ExportOutput,
+};
+use crate::{
+    ARTIFACT_SCHEMA_VERSION, ArtifactReadError, QRenderDocJobError, RenderDocInstallation,
+    ReplayListTexturesRequest, ReplayRequestContext, ReplayTextureInfo, path_to_api_string,
+    read_actions_jsonl, read_bindings_jsonl, resolve_path_from_cwd,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE markers (
+    marker_id INTEGER PRIMARY KEY,
+    parent_marker_id INTEGER REFERENCES markers(marker_id),
+    name TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    depth INTEGER NOT NULL
+);
+CREATE TABLE actions (
+    event_id INTEGER PRIMARY KEY,
+    parent_event_id INTEGER,
+    depth INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    flags INTEGER NOT NULL,
+    flag_names TEXT NOT NULL,
+    is_drawcall INTEGER NOT NULL,
+    marker_id INTEGER REFERENCES markers(marker_id),
+    num_children INTEGER NOT NULL
+);
+CREATE TABLE resources (
+    resource_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL
+);
+CREATE TABLE textures (
+    resource_id INTEGER PRIMARY KEY REFERENCES resources(resource_id),
+    texture_index INTEGER NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    depth INTEGER NOT NULL,
+    mips INTEGER NOT NULL,
+    array_size INTEGER NOT NULL,
+    ms_samp INTEGER NOT NULL,
+    byte_size INTEGER NOT NULL
+);
+CREATE TABLE shaders (
+    resource_id INTEGER PRIMARY KEY REFERENCES resources(resource_id),
+    name TEXT NOT NULL
+);
+CREATE TABLE draw_shaders (
+    event_id INTEGER NOT NULL REFERENCES actions(event_id),
+    stage TEXT NOT NULL,
+    shader_id INTEGER NOT NULL REFERENCES shaders(resource_id),
+    entry_point TEXT NOT NULL,
+    PRIMARY KEY (event_id, stage)
+);
+CREATE TABLE bindings (
+    event_id INTEGER NOT NULL REFERENCES actions(event_id),
+    stage TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    resource_id INTEGER REFERENCES resources(resource_id),
+    size INTEGER
+);
+CREATE TABLE outputs (
+    event_id INTEGER NOT NULL REFERENCES actions(event_id),
+    kind TEXT NOT NULL,
+    slot INTEGER,
+    resource_id INTEGER NOT NULL REFERENCES resources(resource_id)
+);
+CREATE INDEX actions_marker ON actions(marker_id);
+CREATE INDEX actions_drawcall ON actions(is_drawcall, event_id);
+CREATE INDEX markers_name ON markers(name);
+CREATE INDEX resources_name ON resources(name);
+CREATE INDEX draw_shaders_shader ON draw_shaders(shader_id);
+CREATE INDEX bindings_event ON bindings(event_id);
+CREATE INDEX bindings_resource ON bindings(resource_id);
+CREATE INDEX outputs_event ON outputs(event_id);
+CREATE INDEX outputs_resource ON outputs(resource_id);
+";
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_rows() -> Option<u32> {
+    Some(200)
+}
+
+/// Builds a SQLite database from already exported artifacts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BuildSqliteRequest {
+    pub actions_jsonl_path: String,
+    #[serde(default)]
+    pub bindings_jsonl_path: Option<String>,
+    /// Defaults to `<basename>.sqlite` next to the actions.
+    #[serde(default)]
+    pub sqlite_path: Option<String>,
+    /// Texture metadata from `replay_list_textures`, if available.
+    #[serde(default)]
+    pub textures: Vec<ReplayTextureInfo>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SqliteTableCounts {
+    pub actions: u64,
+    pub markers: u64,
+    pub resources: u64,
+    pub textures: u64,
+    pub shaders: u64,
+    pub bindings: u64,
+    pub outputs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SqliteExportArtifacts {
+    pub sqlite_path: String,
+    pub tables: SqliteTableCounts,
+}
+
+/// Exports a capture's actions, bindings, and textures into one SQLite database.
+///
+/// Actions and bindings go through the bundle export (with constant buffers and outputs), so the
+/// JSONL artifacts are written alongside the database.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportSqliteRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub output: ExportOutput,
+    #[serde(default = "default_true")]
+    pub include_textures: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportSqliteResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    pub actions_jsonl_path: String,
+    pub bindings_jsonl_path: String,
+    #[serde(flatten)]
+    pub sqlite: SqliteExportArtifacts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SqliteQueryRequest {
+    pub sqlite_path: String,
+    /// A single read-only statement (`SELECT`, `WITH ... SELECT`, `EXPLAIN`, ...).
+    pub sql: String,
+    #[serde(default = "default_max_rows")]
+    pub max_rows: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SqliteQueryResponse {
+    pub columns: Vec<String>,
+    /// Row values in column order; blobs are returned as lowercase hex strings.
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum SqliteExportError {
+    #[error("failed to prepare sqlite output: {0}")]
+    CreateOutput(std::io::Error),
+    #[error("sqlite output {} is one of the input artifacts", .0.display())]
+    OutputIsInput(PathBuf),
+    #[error(transparent)]
+    ReadArtifact(#[from] ArtifactReadError),
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("bundle export failed: {0}")]
+    ExportBundle(#[from] ExportBundleError),
+    #[error("texture listing failed: {0}")]
+    ListTextures(#[from] QRenderDocJobError),
+}
+
+#[derive(Debug, Error)]
+pub enum SqliteQueryError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("only read-only statements are allowed")]
+    NotReadOnly,
+}
+
+impl RenderDocInstallation {
+    pub fn export_sqlite(
+        &self,
+        cwd: &Path,
+        req: &ExportSqliteRequest,
+    ) -> Result<ExportSqliteResponse, SqliteExportError> {
+        let bundle = self.export_bundle(
+            cwd,
+            &ExportBundleRequest {
+                capture: req.capture.clone(),
+                output: req.output.clone(),
+                bundle: BundleExportOptions {
+                    bindings: BindingsExportOptions {
+                        include_cbuffers: true,
+                        include_outputs: true,
+                    },
+                    ..BundleExportOptions::default()
+                },
+            },
+        )?;
+
+        let textures = if req.include_textures {
+            self.replay_list_textures(
+                cwd,
+                &ReplayListTexturesRequest {
+                    context: ReplayRequestContext {
+                        capture: bundle.capture.clone(),
+                        event_id: None,
+                    },
+                },
+            )?
+            .textures
+        } else {
+            Vec::new()
+        };
+
+        let actions_jsonl_path = bundle.artifacts.actions.actions_jsonl_path;
+        let bindings_jsonl_path = bundle.artifacts.bindings.bindings_jsonl_path;
+        let sqlite = build_sqlite(
+            cwd,
+            &BuildSqliteRequest {
+                actions_jsonl_path: actions_jsonl_path.clone(),
+                bindings_jsonl_path: Some(bindings_jsonl_path.clone()),
+                sqlite_path: None,
+                textures,
+            },
+        )?;
+
+        Ok(ExportSqliteResponse {
+            capture: bundle.capture,
+            actions_jsonl_path,
+            bindings_jsonl_path,
+            sqlite,
+        })
+    }
+}
+
+/// Writes `actions.jsonl` (plus bindings and textures, when given) into a fresh SQLite database.
+///
+/// Tables: `markers`, `actions`, `resources`, `textures`, `shaders`, `draw_shaders`, `bindings`
+/// (`kind` is `srv`, `uav`, or `cbuffer`), and `outputs` (`kind` is `color` or `depth`). Resource
+/// IDs are stored as integers, so `ResourceId::123` in the JSONL becomes `123`. `PRAGMA
+/// user_version` holds the [`ARTIFACT_SCHEMA_VERSION`] the database was built with. An existing
+/// database at the output path is replaced only once the new one is complete.
+pub fn build_sqlite(
+    cwd: &Path,
+    req: &BuildSqliteRequest,
+) -> Result<SqliteExportArtifacts, SqliteExportError> {
+    let actions_path = resolve_path_from_cwd(cwd, &req.actions_jsonl_path);
+    let bindings_path = req
+        .bindings_jsonl_path
+        .as_deref()
+        .map(|path| resolve_path_from_cwd(cwd, path));
+    let sqlite_path = match &req.sqlite_path {
+        Some(path) => resolve_path_from_cwd(cwd, path),
+        None => default_sqlite_path(&actions_path),
+    };
+    if std::iter::once(&actions_path)
+        .chain(&bindings_path)
+        .any(|input| is_same_file(input, &sqlite_path))
+    {
+        return Err(SqliteExportError::OutputIsInput(sqlite_path));
+    }
+    if let Some(parent) = sqlite_path.parent() {
+        std::fs::create_dir_all(parent).map_err(SqliteExportError::CreateOutput)?;
+    }
+
+    // Build next to the destination and rename, so a failed export leaves any previous database
+    // in place.
+    let partial_path = partial_sqlite_path(&sqlite_path);
+    remove_file_if_exists(&partial_path).map_err(SqliteExportError::CreateOutput)?;
+    let tables = write_sqlite(
+        &partial_path,
+        &actions_path,
+        bindings_path.as_deref(),
+        &req.textures,
+    )
+    .inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial_path);
+    })?;
+    std::fs::rename(&partial_path, &sqlite_path).map_err(SqliteExportError::CreateOutput)?;
+
+    Ok(SqliteExportArtifacts {
+        sqlite_path: path_to_api_string(&sqlite_path),
+        tables,
+    })
+}
+
+fn write_sqlite(
+    sqlite_path: &Path,
+    actions_path: &Path,
+    bindings_path: Option<&Path>,
+    textures: &[ReplayTextureInfo],
+) -> Result<SqliteTableCounts, SqliteExportError> {
+    let mut conn = Connection::open(sqlite_path)?;
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", ARTIFACT_SCHEMA_VERSION)?;
+    let tx = conn.transaction()?;
+    let mut writer = SqliteWriter::new(&tx);
+
+    for record in read_actions_jsonl(actions_path)? {
+        writer.insert_action(&record?)?;
+    }
+    if let Some(bindings_path) = bindings_path {
+        for record in read_bindings_jsonl(bindings_path)? {
+            writer.insert_bindings(&record?)?;
+        }
+    }
+    for texture in textures {
+        writer.insert_texture(texture)?;
+    }
+    let tables = writer.counts()?;
+    tx.commit()?;
+    Ok(tables)
+}
+
+/// Both paths exist and resolve to the same file.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn partial_sqlite_path(sqlite_path: &Path) -> PathBuf {
+    let mut path = sqlite_path.as_os_str().to_owned();
+    path.push(".partial");
+    PathBuf::from(path)
+}
+
+fn remove_file_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn default_sqlite_path(actions_path: &Path) -> PathBuf {
+    let basename = actions_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".actions.jsonl"))
+        .unwrap_or("capture");
+    actions_path.with_file_name(format!("{basename}.sqlite"))
+}
+
+/// RenderDoc prints resource IDs as `ResourceId::<n>`; keep the number.
+fn parse_resource_id(value: &str) -> Option<i64> {
+    let digits = value.trim().rsplit(|c: char| !c.is_ascii_digit()).next()?;
+    digits.parse().ok().filter(|id| *id != 0)
+}
+
+/// One row of the `bindings` table.
+struct BindingRow<'a> {
+    event_id: u32,
+    stage: &'a str,
+    kind: &'a str,
+    slot: u32,
+    name: &'a str,
+    resource_id: Option<i64>,
+    size: Option<u64>,
+}
+
+struct SqliteWriter<'a> {
+    conn: &'a Connection,
+    markers: HashMap<String, i64>,
+}
+
+impl<'a> SqliteWriter<'a> {
+    fn new(conn: &'a Connection) -> Self {
+        Self {
+            conn,
+            markers: HashMap::new(),
+        }
+    }
+
+    fn marker_id(&mut self, marker_path: &[String]) -> Result<Option<i64>, rusqlite::Error> {
+        let mut parent = None;
+        for depth in 0..marker_path.len() {
+            let path = marker_path[..=depth].join("/");
+            let id = match self.markers.get(&path) {
+                Some(id) => *id,
+                None => {
+                    self.conn.execute(
+                        "INSERT INTO markers (parent_marker_id, name, path, depth) VALUES (?1, ?2, ?3, ?4)",
+                        params![parent, marker_path[depth], path, depth as i64],
+                    )?;
+                    let id = self.conn.last_insert_rowid();
+                    self.markers.insert(path, id);
+                    id
+                }
+            };
+            parent = Some(id);
+        }
+        Ok(parent)
+    }
+
+    fn insert_action(&mut self, record: &crate::ActionRecord) -> Result<(), rusqlite::Error> {
+        let marker_id = self.marker_id(&record.marker_path.0)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO actions
+                (event_id, parent_event_id, depth, name, flags, flag_names, is_drawcall, marker_id, num_children)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record.event_id,
+                record.parent_event_id,
+                record.depth,
+                record.name,
+                record.flags.bits,
+                record.flags.names().join("|"),
+                record.flags.is_drawcall_like(),
+                marker_id,
+                record.num_children,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn insert_resource(
+        &self,
+        resource_id: &str,
+        name: &str,
+    ) -> Result<Option<i64>, rusqlite::Error> {
+        let Some(id) = parse_resource_id(resource_id) else {
+            return Ok(None);
+        };
+        self.upsert_resource(id, name)?;
+        Ok(Some(id))
+    }
+
+    fn upsert_resource(&self, id: i64, name: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO resources (resource_id, name) VALUES (?1, ?2)
+             ON CONFLICT(resource_id) DO UPDATE SET name = excluded.name
+             WHERE resources.name = '' AND excluded.name != ''",
+            params![id, name],
+        )?;
+        Ok(())
+    }
+
+    fn insert_bindings(&mut self, record: &crate::BindingsRecord) -> Result<(), rusqlite::Error> {
+        for (stage, bindings) in &record.stages {
+            if let Some(shader_id) =
+                self.insert_resource(&bindings.shader.resource_id, &bindings.shader.name)?
+            {
+                self.conn.execute(
+                    "INSERT INTO shaders (resource_id, name) VALUES (?1, ?2)
+                     ON CONFLICT(resource_id) DO NOTHING",
+                    params![shader_id, bindings.shader.name],
+                )?;
+                self.conn.execute(
+                    "INSERT OR REPLACE INTO draw_shaders (event_id, stage, shader_id, entry_point)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        record.event_id,
+                        stage,
+                        shader_id,
+                        bindings.shader.entry_point
+                    ],
+                )?;
+            }
+
+            let views = bindings
+                .srvs
+                .iter()
+                .map(|view| ("srv", view))
+                .chain(bindings.uavs.iter().map(|view| ("uav", view)));
+            for (kind, view) in views {
+                let resource_id = self.insert_resource(&view.resource_id, &view.resource_name)?;
+                self.insert_binding(&BindingRow {
+                    event_id: record.event_id,
+                    stage,
+                    kind,
+                    slot: view.slot,
+                    name: &view.name,
+                    resource_id,
+                    size: None,
+                })?;
+            }
+            for cbuffer in &bindings.cbuffers {
+                let resource_id = match &cbuffer.resource_id {
+                    Some(id) => self.insert_resource(id, &cbuffer.resource_name)?,
+                    None => None,
+                };
+                self.insert_binding(&BindingRow {
+                    event_id: record.event_id,
+                    stage,
+                    kind: "cbuffer",
+                    slot: cbuffer.slot,
+                    name: &cbuffer.name,
+                    resource_id,
+                    size: Some(cbuffer.size),
+                })?;
+            }
+        }
+
+        if let Some(outputs) = &record.outputs {
+            for target in &outputs.render_targets {
+                if let Some(id) =
+                    self.insert_resource(&target.resource_id, &target.resource_name)?
+                {
+                    self.insert_output(record.event_id, "color", Some(target.index), id)?;
+                }
+            }
+            if let Some(depth) = &outputs.depth_target
+                && let Some(id) = self.insert_resource(&depth.resource_id, &depth.resource_name)?
+            {
+                self.insert_output(record.event_id, "depth", None, id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_binding(&self, row: &BindingRow<'_>) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO bindings (event_id, stage, kind, slot, name, resource_id, size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                row.event_id,
+                row.stage,
+                row.kind,
+                row.slot,
+                row.name,
+                row.resource_id,
+                row.size
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn insert_output(
+        &self,
+        event_id: u32,
+        kind: &str,
+        slot: Option<u32>,
+        resource_id: i64,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO outputs (event_id, kind, slot, resource_id) VALUES (?1, ?2, ?3, ?4)",
+            params![event_id, kind, slot, resource_id],
+        )?;
+        Ok(())
+    }
+
+    fn insert_texture(&self, texture: &ReplayTextureInfo) -> Result<(), rusqlite::Error> {
+        let Ok(id) = i64::try_from(texture.resource_id) else {
+            return Ok(());
+        };
+        self.upsert_resource(id, &texture.name)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO textures
+                (resource_id, texture_index, width, height, depth, mips, array_size, ms_samp, byte_size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                id,
+                texture.index,
+                texture.width,
+                texture.height,
+                texture.depth,
+                texture.mips,
+                texture.array_size,
+                texture.ms_samp,
+                texture.byte_size,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn counts(&self) -> Result<SqliteTableCounts, rusqlite::Error> {
+        let count = |table: &str| {
+            self.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get::<_, i64>(0)
+                })
+                .map(|count| count as u64)
+        };
+        Ok(SqliteTableCounts {
+            actions: count("actions")?,
+            markers: count("markers")?,
+            resources: count("resources")?,
+            textures: count("textures")?,
+            shaders: count("shaders")?,
+            bindings: count("bindings")?,
+            outputs: count("outputs")?,
+        })
+    }
+}
+
+/// Runs one read-only statement against an exported database.
+///
+/// The database is opened read-only and statements that could write are rejected before they run.
+pub fn query_sqlite(
+    cwd: &Path,
+    req: &SqliteQueryRequest,
+) -> Result<SqliteQueryResponse, SqliteQueryError> {
+    let sqlite_path = resolve_path_from_cwd(cwd, &req.sqlite_path);
+    let conn = Connection::open_with_flags(
+        &sqlite_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.pragma_update(None, "query_only", true)?;
+
+    let mut stmt = conn.prepare(&req.sql)?;
+    if !stmt.readonly() {
+        return Err(SqliteQueryError::NotReadOnly);
+    }
+    let columns: Vec<String> = stmt
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut cursor = stmt.query([])?;
+    while let Some(row) = cursor.next()? {
+        if req.max_rows.is_some_and(|max| rows.len() >= max as usize) {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|index| row.get_ref(index).map(sql_value_to_json))
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(values);
+    }
+
+    Ok(SqliteQueryResponse {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+fn sql_value_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(value) => value.into(),
+        ValueRef::Real(value) => value.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+        ValueRef::Blob(blob) => blob
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::{
+        BuildSqliteRequest, SqliteExportError, SqliteQueryError, SqliteQueryRequest, build_sqlite,
+        parse_resource_id, query_sqlite,
+    };
+    use crate::ReplayTextureInfo;
+    use crate::test_support::{fixture_dir, frame_actions, write_jsonl};
+
+    fn draw_bindings(event_id: u32, marker: &str, texture: &str) -> serde_json::Value {
+        json!({
+            "event_id": event_id,
+            "depth": 1,
+            "name": format!("Draw {event_id}"),
+            "marker_path": [marker],
+            "stages": {
+                "Pixel": {
+                    "shader": {"resource_id": "ResourceId::900", "name": "ps_main", "entry_point": "main"},
+                    "srvs": [{"slot": 0, "name": "albedo", "resource_id": texture, "resource_name": ""}],
+                    "uavs": [],
+                    "cbuffers": [{"slot": 0, "name": "Globals", "size": 256, "resource_id": null, "resource_name": ""}],
+                },
+            },
+            "shader_names": ["ps_main"],
+            "resource_names": ["albedo"],
+            "outputs": {
+                "render_targets": [{"index": 0, "resource_id": "ResourceId::50", "resource_name": "GBuffer0"}],
+                "depth_target": {"resource_id": "ResourceId::51", "resource_name": "Depth"},
+            },
+        })
+    }
+
+    fn write_fixture(dir: &Path) {
+        write_jsonl(&dir.join("frame.actions.jsonl"), &frame_actions());
+        write_jsonl(
+            &dir.join("frame.bindings.jsonl"),
+            &[
+                draw_bindings(2, "Shadows", "ResourceId::40"),
+                draw_bindings(4, "Opaque", "ResourceId::40"),
+                draw_bindings(5, "Opaque", "ResourceId::41"),
+            ],
+        );
+    }
+
+    fn texture(resource_id: u64, name: &str) -> ReplayTextureInfo {
+        ReplayTextureInfo {
+            index: resource_id as u32,
+            resource_id,
+            name: name.into(),
+            width: 256,
+            height: 256,
+            depth: 1,
+            mips: 1,
+            array_size: 1,
+            ms_samp: 1,
+            byte_size: 262_144,
+        }
+    }
+
+    #[test]
+    fn build_sqlite_supports_joins_across_markers_bindings_and_textures() {
+        let dir = fixture_dir("sqlite");
+        write_fixture(&dir);
+
+        let artifacts = build_sqlite(
+            &dir,
+            &BuildSqliteRequest {
+                actions_jsonl_path: "frame.actions.jsonl".into(),
+                bindings_jsonl_path: Some("frame.bindings.jsonl".into()),
+                sqlite_path: None,
+                textures: vec![texture(40, "Albedo"), texture(41, "Noise")],
+            },
+        )
+        .expect("build sqlite");
+        assert!(artifacts.sqlite_path.ends_with("frame.sqlite"));
+        assert_eq!(artifacts.tables.actions, 7);
+        assert_eq!(artifacts.tables.markers, 3);
+        assert_eq!(artifacts.tables.bindings, 6);
+        assert_eq!(artifacts.tables.outputs, 6);
+        assert_eq!(artifacts.tables.shaders, 1);
+        assert_eq!(artifacts.tables.textures, 2);
+
+        let res = query_sqlite(
+            &dir,
+            &SqliteQueryRequest {
+                sqlite_path: artifacts.sqlite_path.clone(),
+                sql: "SELECT a.event_id, r.name AS texture
+                      FROM actions a
+                      JOIN markers m ON m.marker_id = a.marker_id
+                      JOIN bindings b ON b.event_id = a.event_id AND b.kind = 'srv'
+                      JOIN resources r ON r.resource_id = b.resource_id
+                      WHERE m.name = 'Opaque' AND a.is_drawcall AND r.name = 'Albedo'"
+                    .into(),
+                max_rows: None,
+            },
+        )
+        .expect("query sqlite");
+        assert_eq!(res.columns, ["event_id", "texture"]);
+        assert_eq!(res.rows, [vec![json!(4), json!("Albedo")]]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn query_sqlite_rejects_writes_and_truncates_rows() {
+        let dir = fixture_dir("sqlite");
+        write_fixture(&dir);
+        let artifacts = build_sqlite(
+            &dir,
+            &BuildSqliteRequest {
+                actions_jsonl_path: "frame.actions.jsonl".into(),
+                bindings_jsonl_path: None,
+                sqlite_path: Some("db/frame.sqlite".into()),
+                textures: Vec::new(),
+            },
+        )
+        .expect("build sqlite");
+
+        let err = query_sqlite(
+            &dir,
+            &SqliteQueryRequest {
+                sqlite_path: artifacts.sqlite_path.clone(),
+                sql: "DELETE FROM actions".into(),
+                max_rows: None,
+            },
+        )
+        .expect_err("delete must be rejected");
+        assert!(matches!(err, SqliteQueryError::NotReadOnly));
+
+        let res = query_sqlite(
+            &dir,
+            &SqliteQueryRequest {
+                sqlite_path: "db/frame.sqlite".into(),
+                sql: "SELECT event_id FROM actions ORDER BY event_id".into(),
+                max_rows: Some(2),
+            },
+        )
+        .expect("query sqlite");
+        assert_eq!(res.rows.len(), 2);
+        assert!(res.truncated);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn build_sqlite_refuses_to_overwrite_its_inputs() {
+        let dir = fixture_dir("sqlite-overwrite");
+        write_fixture(&dir);
+        let actions_before =
+            std::fs::read_to_string(dir.join("frame.actions.jsonl")).expect("read actions");
+
+        for sqlite_path in ["frame.actions.jsonl", "./frame.bindings.jsonl"] {
+            let err = build_sqlite(
+                &dir,
+                &BuildSqliteRequest {
+                    actions_jsonl_path: "frame.actions.jsonl".into(),
+                    bindings_jsonl_path: Some("frame.bindings.jsonl".into()),
+                    sqlite_path: Some(sqlite_path.into()),
+                    textures: Vec::new(),
+                },
+            )
+            .expect_err("output must not replace an input");
+            assert!(matches!(err, SqliteExportError::OutputIsInput(_)), "{err}");
+        }
+        assert_eq!(
+            std::fs::read_to_string(dir.join("frame.actions.jsonl")).expect("read actions"),
+            actions_before
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parse_resource_id_keeps_the_numeric_id() {
+        assert_eq!(parse_resource_id("ResourceId::123"), Some(123));
+        assert_eq!(parse_resource_id("77"), Some(77));
+        assert_eq!(parse_resource_id("ResourceId::0"), None);
+        assert_eq!(parse_resource_id("Null"), None);
+    }
+}
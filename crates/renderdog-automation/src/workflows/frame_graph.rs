@@ -0,0 +1,523 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write as _,
+    path::Path,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::FRAME_GRAPH_JOB;
+use crate::{
+    ARTIFACT_SCHEMA_VERSION, MarkerPath, QRenderDocJobError, RenderDocInstallation,
+    path_to_api_string,
+};
+
+use super::{CaptureInput, CaptureRef, ExportOutput, ResourceAccess};
+
+/// Builds a pass-level dependency graph from RenderDoc's per-event resource usage.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub output: ExportOutput,
+    /// Truncates marker paths to this many levels, merging nested markers into their ancestor's
+    /// pass. Unset uses the full marker path.
+    #[serde(default)]
+    pub pass_depth: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphResource {
+    pub resource_id: u64,
+    pub name: String,
+    pub resource_type: String,
+    /// Presented to the swapchain, so writes to it count as consumed.
+    pub swapchain: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphPass {
+    /// Index into `passes`, used by `edges`.
+    pub id: u32,
+    /// Marker stack of the pass; empty for work outside any marker.
+    pub marker_path: MarkerPath,
+    pub first_event_id: u32,
+    pub last_event_id: u32,
+    pub reads: Vec<u64>,
+    pub writes: Vec<u64>,
+    /// Written resources no later pass reads (swapchain images excluded).
+    pub unread_writes: Vec<u64>,
+    /// Writes resources, but nothing it writes is read by another pass or presented.
+    pub unconsumed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphEdge {
+    pub from: u32,
+    pub to: u32,
+    /// Resources written by `from` and then read by `to`.
+    pub resource_ids: Vec<u64>,
+}
+
+/// `<basename>.frame_graph.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraph {
+    /// Artifact schema version of the exporter; `0` when read from an artifact written before
+    /// versioning.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub capture_path: String,
+    pub api: String,
+    #[serde(default)]
+    pub pass_depth: Option<u32>,
+    /// Textures and buffers used by at least one pass.
+    pub resources: Vec<FrameGraphResource>,
+    /// Passes in order of their first resource access. A marker path that is interrupted by
+    /// another pass and resumed later is still one pass.
+    pub passes: Vec<FrameGraphPass>,
+    pub edges: Vec<FrameGraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphArtifacts {
+    pub frame_graph_json_path: String,
+    pub frame_graph_dot_path: String,
+    pub frame_graph_mermaid_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameGraphResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    #[serde(flatten)]
+    pub artifacts: FrameGraphArtifacts,
+    pub passes: u64,
+    pub edges: u64,
+    pub unconsumed_passes: Vec<MarkerPath>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FrameGraphJobRequest {
+    #[serde(flatten)]
+    capture: CaptureInput,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FrameGraphUsage {
+    resource_id: u64,
+    event_id: u32,
+    marker_path: MarkerPath,
+    usage: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FrameGraphJobResponse {
+    capture_path: String,
+    api: String,
+    resources: Vec<FrameGraphResource>,
+    usages: Vec<FrameGraphUsage>,
+}
+
+#[derive(Debug, Error)]
+pub enum FrameGraphError {
+    #[error("failed to create output dir: {0}")]
+    CreateOutputDir(std::io::Error),
+    #[error("frame graph job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+    #[error("failed to write frame graph: {0}")]
+    Write(std::io::Error),
+}
+
+impl RenderDocInstallation {
+    /// Writes `<basename>.frame_graph.json`, `.frame_graph.dot`, and `.frame_graph.mmd`.
+    pub fn frame_graph(
+        &self,
+        cwd: &Path,
+        req: &FrameGraphRequest,
+    ) -> Result<FrameGraphResponse, FrameGraphError> {
+        let (capture, output) = req
+            .output
+            .normalized_for_capture(cwd, &req.capture)
+            .map_err(FrameGraphError::CreateOutputDir)?;
+        let job: FrameGraphJobResponse =
+            self.run_qrenderdoc_job(cwd, FRAME_GRAPH_JOB, &FrameGraphJobRequest { capture })?;
+        let graph = FrameGraph::build(job, req.pass_depth);
+
+        let output_dir = Path::new(output.output_dir.as_deref().unwrap_or_default());
+        let basename = output.basename.as_deref().unwrap_or_default();
+        let json_path = output_dir.join(format!("{basename}.frame_graph.json"));
+        let dot_path = output_dir.join(format!("{basename}.frame_graph.dot"));
+        let mermaid_path = output_dir.join(format!("{basename}.frame_graph.mmd"));
+        let json = serde_json::to_string_pretty(&graph)
+            .map_err(|err| FrameGraphError::Write(err.into()))?;
+        std::fs::write(&json_path, json).map_err(FrameGraphError::Write)?;
+        std::fs::write(&dot_path, graph.to_dot()).map_err(FrameGraphError::Write)?;
+        std::fs::write(&mermaid_path, graph.to_mermaid()).map_err(FrameGraphError::Write)?;
+
+        Ok(FrameGraphResponse {
+            capture: CaptureRef::new(graph.capture_path.clone()),
+            artifacts: FrameGraphArtifacts {
+                frame_graph_json_path: path_to_api_string(&json_path),
+                frame_graph_dot_path: path_to_api_string(&dot_path),
+                frame_graph_mermaid_path: path_to_api_string(&mermaid_path),
+            },
+            passes: graph.passes.len() as u64,
+            edges: graph.edges.len() as u64,
+            unconsumed_passes: graph
+                .passes
+                .iter()
+                .filter(|pass| pass.unconsumed)
+                .map(|pass| pass.marker_path.clone())
+                .collect(),
+        })
+    }
+}
+
+/// Whether a usage depends on the resource's previous contents and whether it replaces them.
+///
+/// Render-target writes count as reads too: usage info can't tell a load from a full overwrite,
+/// so a pass drawing into a target another pass cleared depends on that pass.
+fn usage_dependency(usage: &str) -> (bool, bool) {
+    match ResourceAccess::from_usage(usage) {
+        ResourceAccess::Read | ResourceAccess::CopySource => (true, false),
+        ResourceAccess::ReadWrite => (true, true),
+        ResourceAccess::Write => (matches!(usage, "ColorTarget" | "DepthStencilTarget"), true),
+        ResourceAccess::Clear | ResourceAccess::CopyDestination => (false, true),
+        ResourceAccess::Barrier | ResourceAccess::Other => (false, false),
+    }
+}
+
+#[derive(Default)]
+struct PassBuilder {
+    marker_path: Vec<String>,
+    first_event_id: u32,
+    last_event_id: u32,
+    reads: BTreeSet<u64>,
+    writes: BTreeSet<u64>,
+    consumed_writes: BTreeSet<u64>,
+}
+
+impl FrameGraph {
+    fn build(job: FrameGraphJobResponse, pass_depth: Option<u32>) -> Self {
+        let mut usages = job.usages;
+        usages.sort_by_key(|usage| usage.event_id);
+
+        let mut passes: Vec<PassBuilder> = Vec::new();
+        let mut pass_by_path: HashMap<Vec<String>, usize> = HashMap::new();
+        let mut edges: Vec<(usize, usize, BTreeSet<u64>)> = Vec::new();
+        let mut edge_by_pair: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut last_writer: HashMap<u64, usize> = HashMap::new();
+
+        for usage in &usages {
+            let (reads, writes) = usage_dependency(&usage.usage);
+            if !reads && !writes {
+                continue;
+            }
+            let mut path = usage.marker_path.0.clone();
+            if let Some(depth) = pass_depth {
+                path.truncate(depth as usize);
+            }
+            let pass = *pass_by_path.entry(path.clone()).or_insert_with(|| {
+                passes.push(PassBuilder {
+                    marker_path: path,
+                    first_event_id: usage.event_id,
+                    ..Default::default()
+                });
+                passes.len() - 1
+            });
+            passes[pass].last_event_id = usage.event_id;
+
+            let resource = usage.resource_id;
+            if reads {
+                passes[pass].reads.insert(resource);
+                if let Some(&writer) = last_writer.get(&resource)
+                    && writer != pass
+                {
+                    passes[writer].consumed_writes.insert(resource);
+                    let edge = *edge_by_pair.entry((writer, pass)).or_insert_with(|| {
+                        edges.push((writer, pass, BTreeSet::new()));
+                        edges.len() - 1
+                    });
+                    edges[edge].2.insert(resource);
+                }
+            }
+            if writes {
+                passes[pass].writes.insert(resource);
+                last_writer.insert(resource, pass);
+            }
+        }
+
+        let swapchain: BTreeSet<u64> = job
+            .resources
+            .iter()
+            .filter(|resource| resource.swapchain)
+            .map(|resource| resource.resource_id)
+            .collect();
+        let used: BTreeSet<u64> = passes
+            .iter()
+            .flat_map(|pass| pass.reads.iter().chain(&pass.writes))
+            .copied()
+            .collect();
+
+        Self {
+            schema_version: ARTIFACT_SCHEMA_VERSION,
+            capture_path: job.capture_path,
+            api: job.api,
+            pass_depth,
+            resources: job
+                .resources
+                .into_iter()
+                .filter(|resource| used.contains(&resource.resource_id))
+                .collect(),
+            passes: passes
+                .into_iter()
+                .enumerate()
+                .map(|(id, pass)| {
+                    let unread_writes: Vec<u64> = pass
+                        .writes
+                        .iter()
+                        .filter(|id| !pass.consumed_writes.contains(id) && !swapchain.contains(id))
+                        .copied()
+                        .collect();
+                    FrameGraphPass {
+                        id: id as u32,
+                        marker_path: MarkerPath(pass.marker_path),
+                        first_event_id: pass.first_event_id,
+                        last_event_id: pass.last_event_id,
+                        unconsumed: !pass.writes.is_empty()
+                            && pass.consumed_writes.is_empty()
+                            && pass.writes.is_disjoint(&swapchain),
+                        reads: pass.reads.into_iter().collect(),
+                        writes: pass.writes.into_iter().collect(),
+                        unread_writes,
+                    }
+                })
+                .collect(),
+            edges: edges
+                .into_iter()
+                .map(|(from, to, resource_ids)| FrameGraphEdge {
+                    from: from as u32,
+                    to: to as u32,
+                    resource_ids: resource_ids.into_iter().collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Renders the graph as Graphviz DOT; unconsumed passes are drawn dashed and red.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph frame_graph {\n  rankdir=LR;\n  node [shape=box];\n");
+        for pass in &self.passes {
+            let style = if pass.unconsumed {
+                ", style=dashed, color=red"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "  p{} [label=\"{}\\n{}-{}\"{style}];",
+                pass.id,
+                escape_dot(&self.pass_label(pass)),
+                pass.first_event_id,
+                pass.last_event_id,
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  p{} -> p{} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                escape_dot(&self.edge_label(edge)),
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as a Mermaid flowchart; unconsumed passes get the `unconsumed` class.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for pass in &self.passes {
+            let _ = writeln!(
+                out,
+                "  p{}[\"{}<br/>{}-{}\"]",
+                pass.id,
+                escape_mermaid(&self.pass_label(pass)),
+                pass.first_event_id,
+                pass.last_event_id,
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  p{} -->|\"{}\"| p{}",
+                edge.from,
+                escape_mermaid(&self.edge_label(edge)),
+                edge.to,
+            );
+        }
+        let unconsumed: Vec<String> = self
+            .passes
+            .iter()
+            .filter(|pass| pass.unconsumed)
+            .map(|pass| format!("p{}", pass.id))
+            .collect();
+        if !unconsumed.is_empty() {
+            out.push_str("  classDef unconsumed stroke:#c00,stroke-dasharray:4 4\n");
+            let _ = writeln!(out, "  class {} unconsumed", unconsumed.join(","));
+        }
+        out
+    }
+
+    fn pass_label(&self, pass: &FrameGraphPass) -> String {
+        if pass.marker_path.0.is_empty() {
+            "(no marker)".to_string()
+        } else {
+            pass.marker_path.joined()
+        }
+    }
+
+    /// Up to three resource names, then a count of the rest.
+    fn edge_label(&self, edge: &FrameGraphEdge) -> String {
+        const MAX_NAMES: usize = 3;
+        let mut names: Vec<String> = edge
+            .resource_ids
+            .iter()
+            .take(MAX_NAMES)
+            .map(|id| {
+                self.resources
+                    .iter()
+                    .find(|resource| resource.resource_id == *id)
+                    .map(|resource| resource.name.clone())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("ResourceId::{id}"))
+            })
+            .collect();
+        if edge.resource_ids.len() > MAX_NAMES {
+            names.push(format!("+{} more", edge.resource_ids.len() - MAX_NAMES));
+        }
+        names.join(", ")
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{FrameGraph, FrameGraphJobResponse};
+    use crate::test_support::frame_events;
+
+    fn fixture() -> FrameGraphJobResponse {
+        let events = frame_events();
+        let usage = |resource_id: u64, event_id: u32, usage: &str| {
+            let event = events
+                .iter()
+                .find(|event| event.event_id == event_id)
+                .expect("frame event");
+            json!({
+                "resource_id": resource_id,
+                "event_id": event_id,
+                "marker_path": event.marker_path,
+                "usage": usage,
+            })
+        };
+        serde_json::from_value(json!({
+            "capture_path": "/captures/frame.rdc",
+            "api": "Vulkan",
+            "resources": [
+                {"resource_id": 10, "name": "Shadow Map", "resource_type": "Texture", "swapchain": false},
+                {"resource_id": 11, "name": "HDR \"Color\"", "resource_type": "Texture", "swapchain": false},
+                {"resource_id": 12, "name": "Debug", "resource_type": "Texture", "swapchain": false},
+                {"resource_id": 13, "name": "Backbuffer", "resource_type": "Texture", "swapchain": true},
+                {"resource_id": 14, "name": "Unused", "resource_type": "Buffer", "swapchain": false},
+            ],
+            "usages": [
+                usage(10, 2, "Clear"),
+                usage(10, 2, "DepthStencilTarget"),
+                usage(11, 4, "ColorTarget"),
+                usage(10, 5, "PS_Resource"),
+                usage(12, 6, "CS_RWResource"),
+                usage(11, 7, "PS_Resource"),
+                usage(13, 7, "ColorTarget"),
+                usage(14, 7, "Barrier"),
+            ],
+        }))
+        .expect("parse job response")
+    }
+
+    #[test]
+    fn frame_graph_links_writers_to_later_readers() {
+        let graph = FrameGraph::build(fixture(), None);
+
+        let passes: Vec<(String, bool)> = graph
+            .passes
+            .iter()
+            .map(|pass| (pass.marker_path.joined(), pass.unconsumed))
+            .collect();
+        assert_eq!(
+            passes,
+            [
+                ("Shadows".to_string(), false),
+                ("Opaque".to_string(), false),
+                ("Opaque/Cull".to_string(), true),
+                (String::new(), false),
+            ]
+        );
+        let edges: Vec<(u32, u32, Vec<u64>)> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from, edge.to, edge.resource_ids.clone()))
+            .collect();
+        assert_eq!(edges, [(0, 1, vec![10]), (1, 3, vec![11])]);
+        assert_eq!(graph.passes[2].unread_writes, [12]);
+        assert!(graph.passes[3].unread_writes.is_empty());
+        assert_eq!(graph.resources.len(), 4, "unused resources are dropped");
+
+        let merged = FrameGraph::build(fixture(), Some(1));
+        assert_eq!(merged.passes.len(), 3, "Opaque/Cull merges into Opaque");
+        let edges: Vec<(u32, u32, Vec<u64>)> = merged
+            .edges
+            .iter()
+            .map(|edge| (edge.from, edge.to, edge.resource_ids.clone()))
+            .collect();
+        assert_eq!(edges, [(0, 1, vec![10]), (1, 2, vec![11])]);
+        assert!(merged.passes.iter().all(|pass| !pass.unconsumed));
+
+        let flattened = FrameGraph::build(fixture(), Some(0));
+        assert_eq!(flattened.passes.len(), 1);
+        assert!(flattened.edges.is_empty());
+    }
+
+    #[test]
+    fn frame_graph_renders_dot_and_mermaid() {
+        let graph = FrameGraph::build(fixture(), None);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph frame_graph {"), "{dot}");
+        assert!(dot.contains("  p0 [label=\"Shadows\\n2-2\"];"), "{dot}");
+        assert!(dot.contains("  p2 [label=\"Opaque/Cull\\n6-6\", style=dashed, color=red];"));
+        assert!(dot.contains("  p3 [label=\"(no marker)\\n7-7\"];"), "{dot}");
+        assert!(
+            dot.contains("  p1 -> p3 [label=\"HDR \\\"Color\\\"\"];"),
+            "{dot}"
+        );
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"), "{mermaid}");
+        assert!(mermaid.contains("  p0 -->|\"Shadow Map\"| p1"), "{mermaid}");
+        assert!(mermaid.contains("  p1 -->|\"HDR #quot;Color#quot;\"| p3"));
+        assert!(mermaid.ends_with("  class p2 unconsumed\n"), "{mermaid}");
+    }
+}
@@ -0,0 +1,283 @@
+use std::{fmt::Write as _, path::Path};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::FRAME_STATS_JOB;
+use crate::{MarkerPath, QRenderDocJobError, RenderDocInstallation};
+
+use super::{CaptureInput, CaptureRef, EventFilter, ExportOutput};
+
+fn default_true() -> bool {
+    true
+}
+
+/// Aggregates draws, dispatches, clears, and copies per marker path.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameStatsRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub output: ExportOutput,
+    #[serde(flatten)]
+    pub filter: EventFilter,
+    /// Replays every draw and dispatch to count pipeline, shader, and render-target changes.
+    /// Disable for a faster pass that only reads the action tree.
+    #[serde(default = "default_true")]
+    pub include_state_changes: bool,
+}
+
+/// Counters summed over the draws, dispatches, clears, and copies of a pass (or the frame).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameStatsCounts {
+    /// `Drawcall` and `MeshDispatch` actions.
+    pub draws: u64,
+    /// `Dispatch` and `DispatchRay` actions.
+    pub dispatches: u64,
+    pub clears: u64,
+    pub copies: u64,
+    /// Indices of indexed draws.
+    pub indices: u64,
+    /// Vertices of non-indexed draws.
+    pub vertices: u64,
+    pub instances: u64,
+    /// Sum of `x * y * z` thread groups over dispatches.
+    pub dispatch_groups: u64,
+    pub indirect_draws: u64,
+    pub indirect_dispatches: u64,
+    /// Draws/dispatches whose pipeline differs from the previous one of the same kind. The first
+    /// draw and the first dispatch of the frame count as changes.
+    pub pipeline_changes: u64,
+    /// Draws/dispatches whose bound shaders differ from the previous draw or dispatch.
+    pub shader_changes: u64,
+    /// Draws whose color or depth targets differ from the previous draw.
+    pub render_target_changes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MarkerPassStats {
+    /// Marker stack of the counted actions; empty for work outside any marker.
+    pub marker_path: MarkerPath,
+    pub first_event_id: u32,
+    pub last_event_id: u32,
+    #[serde(flatten)]
+    pub counts: FrameStatsCounts,
+}
+
+/// `<basename>.frame_stats.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FrameStats {
+    /// Artifact schema version of the exporter; `0` when read from an artifact written before
+    /// versioning.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub capture_path: String,
+    pub api: String,
+    /// `false` when exported without `include_state_changes`; the change counters are then `0`.
+    pub state_changes_tracked: bool,
+    pub total: FrameStatsCounts,
+    /// Passes in order of their first counted event. A marker path that is interrupted by another
+    /// pass and resumed later is still reported once.
+    pub passes: Vec<MarkerPassStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameStatsArtifacts {
+    pub frame_stats_json_path: String,
+    pub frame_stats_markdown_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameStatsResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    #[serde(flatten)]
+    pub artifacts: FrameStatsArtifacts,
+    pub stats: FrameStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FrameStatsJobRequest {
+    #[serde(flatten)]
+    capture: CaptureInput,
+    #[serde(flatten)]
+    output: ExportOutput,
+    #[serde(flatten)]
+    filter: EventFilter,
+    include_state_changes: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FrameStatsJobResponse {
+    frame_stats_json_path: String,
+    stats: FrameStats,
+}
+
+#[derive(Debug, Error)]
+pub enum FrameStatsError {
+    #[error("failed to create output dir: {0}")]
+    CreateOutputDir(std::io::Error),
+    #[error("frame stats job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+    #[error("failed to write markdown report: {0}")]
+    WriteMarkdown(std::io::Error),
+}
+
+impl RenderDocInstallation {
+    /// Writes `<basename>.frame_stats.json` and a `<basename>.frame_stats.md` table.
+    pub fn frame_stats(
+        &self,
+        cwd: &Path,
+        req: &FrameStatsRequest,
+    ) -> Result<FrameStatsResponse, FrameStatsError> {
+        let (capture, output) = req
+            .output
+            .normalized_for_capture(cwd, &req.capture)
+            .map_err(FrameStatsError::CreateOutputDir)?;
+        req.filter.validate()?;
+        let job: FrameStatsJobResponse = self.run_qrenderdoc_job(
+            cwd,
+            FRAME_STATS_JOB,
+            &FrameStatsJobRequest {
+                capture,
+                output,
+                filter: req.filter.clone(),
+                include_state_changes: req.include_state_changes,
+            },
+        )?;
+
+        let markdown_path = job
+            .frame_stats_json_path
+            .strip_suffix(".json")
+            .unwrap_or(&job.frame_stats_json_path)
+            .to_string()
+            + ".md";
+        std::fs::write(&markdown_path, job.stats.to_markdown())
+            .map_err(FrameStatsError::WriteMarkdown)?;
+
+        Ok(FrameStatsResponse {
+            capture: CaptureRef::new(job.stats.capture_path.clone()),
+            artifacts: FrameStatsArtifacts {
+                frame_stats_json_path: job.frame_stats_json_path,
+                frame_stats_markdown_path: markdown_path,
+            },
+            stats: job.stats,
+        })
+    }
+}
+
+impl FrameStats {
+    /// Renders one row per pass plus a frame total as a GitHub-flavored Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Frame stats: {}\n\n", self.capture_path);
+        let _ = writeln!(out, "API: {}\n", self.api);
+        if !self.state_changes_tracked {
+            out.push_str("State changes were not tracked for this report.\n\n");
+        }
+        out.push_str(
+            "| Pass | Events | Draws | Dispatches | Clears | Copies | Indices | Vertices | Instances \
+             | Groups | Indirect draws | Indirect dispatches | Pipeline changes | Shader changes \
+             | RT changes |\n",
+        );
+        out.push_str(
+            "|---|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|\n",
+        );
+        for pass in &self.passes {
+            let name = if pass.marker_path.0.is_empty() {
+                "(no marker)".to_string()
+            } else {
+                pass.marker_path.joined()
+            };
+            let events = format!("{}-{}", pass.first_event_id, pass.last_event_id);
+            push_markdown_row(
+                &mut out,
+                &escape_markdown_cell(&name),
+                &events,
+                &pass.counts,
+            );
+        }
+        push_markdown_row(&mut out, "**Total**", "", &self.total);
+        out
+    }
+}
+
+fn push_markdown_row(out: &mut String, name: &str, events: &str, counts: &FrameStatsCounts) {
+    let _ = writeln!(
+        out,
+        "| {name} | {events} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+        counts.draws,
+        counts.dispatches,
+        counts.clears,
+        counts.copies,
+        counts.indices,
+        counts.vertices,
+        counts.instances,
+        counts.dispatch_groups,
+        counts.indirect_draws,
+        counts.indirect_dispatches,
+        counts.pipeline_changes,
+        counts.shader_changes,
+        counts.render_target_changes,
+    );
+}
+
+fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::FrameStats;
+
+    #[test]
+    fn frame_stats_markdown_has_a_row_per_pass_and_a_total() {
+        let stats: FrameStats = serde_json::from_value(json!({
+            "schema_version": 1,
+            "capture_path": "/captures/frame.rdc",
+            "api": "Vulkan",
+            "state_changes_tracked": true,
+            "total": {
+                "draws": 3, "dispatches": 1, "clears": 1, "copies": 0, "indices": 72,
+                "vertices": 3, "instances": 3, "dispatch_groups": 64, "indirect_draws": 1,
+                "indirect_dispatches": 0, "pipeline_changes": 3, "shader_changes": 3,
+                "render_target_changes": 2,
+            },
+            "passes": [
+                {
+                    "marker_path": [], "first_event_id": 1, "last_event_id": 1,
+                    "draws": 0, "dispatches": 0, "clears": 1, "copies": 0, "indices": 0,
+                    "vertices": 0, "instances": 0, "dispatch_groups": 0, "indirect_draws": 0,
+                    "indirect_dispatches": 0, "pipeline_changes": 0, "shader_changes": 0,
+                    "render_target_changes": 0,
+                },
+                {
+                    "marker_path": ["Frame", "Opaque|Alpha"], "first_event_id": 3,
+                    "last_event_id": 9,
+                    "draws": 3, "dispatches": 1, "clears": 0, "copies": 0, "indices": 72,
+                    "vertices": 3, "instances": 3, "dispatch_groups": 64, "indirect_draws": 1,
+                    "indirect_dispatches": 0, "pipeline_changes": 3, "shader_changes": 3,
+                    "render_target_changes": 2,
+                },
+            ],
+        }))
+        .expect("parse frame stats");
+
+        let markdown = stats.to_markdown();
+        let rows: Vec<&str> = markdown.lines().filter(|l| l.starts_with("| ")).collect();
+        assert_eq!(rows.len(), 4, "{markdown}");
+        assert!(rows[0].starts_with("| Pass | Events | Draws |"));
+        assert_eq!(
+            rows[1],
+            "| (no marker) | 1-1 | 0 | 0 | 1 | 0 | 0 | 0 | 0 | 0 | 0 | 0 | 0 | 0 | 0 |"
+        );
+        assert_eq!(
+            rows[2],
+            "| Frame/Opaque\\|Alpha | 3-9 | 3 | 1 | 0 | 0 | 72 | 3 | 3 | 64 | 1 | 0 | 3 | 3 | 2 |"
+        );
+        assert!(rows[3].starts_with("| **Total** |  | 3 | 1 | 1 |"));
+        assert!(!markdown.contains("not tracked"));
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, path::Path};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::REPLAY_GPU_TIMINGS_JOB;
+use crate::{
+    EventFlags, MarkerPath, QRenderDocJobError, RenderDocInstallation, path_to_api_string,
+};
+
+use super::{CaptureInput, CaptureRef, DrawcallScope, EventFilter, ExportOutput};
+
+fn default_top_n() -> u32 {
+    10
+}
+
+/// Times every action with RenderDoc's `EventGPUDuration` counter.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayGpuTimingsRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub output: ExportOutput,
+    #[serde(flatten)]
+    pub drawcall_scope: DrawcallScope,
+    #[serde(flatten)]
+    pub filter: EventFilter,
+    /// How many of the most expensive passes and events to return.
+    #[serde(default = "default_top_n")]
+    pub top_n: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EventGpuTiming {
+    pub event_id: u32,
+    pub name: String,
+    pub flags: EventFlags,
+    pub marker_path: MarkerPath,
+    pub duration_us: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MarkerGpuTiming {
+    pub marker_path: MarkerPath,
+    /// Time of every timed event under this marker, including nested markers.
+    pub duration_us: f64,
+    /// Time of events directly under this marker.
+    pub self_duration_us: f64,
+    pub events: u64,
+    pub first_event_id: u32,
+    pub last_event_id: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayGpuTimingsResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    pub api: String,
+    pub timed_events: u64,
+    pub total_duration_us: f64,
+    /// Marker paths sorted by inclusive GPU time, most expensive first.
+    pub top_passes: Vec<MarkerGpuTiming>,
+    /// Individual events sorted by GPU time, most expensive first.
+    pub top_events: Vec<EventGpuTiming>,
+    /// Chrome trace-format timeline (`chrome://tracing`, Perfetto). Not a versioned
+    /// [`ArtifactKind`](crate::ArtifactKind), since the trace format defines its layout.
+    pub chrome_trace_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplayGpuTimingsJobRequest {
+    #[serde(flatten)]
+    capture: CaptureInput,
+    #[serde(flatten)]
+    drawcall_scope: DrawcallScope,
+    #[serde(flatten)]
+    filter: EventFilter,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplayGpuTimingsJobResponse {
+    capture_path: String,
+    api: String,
+    events: Vec<EventGpuTiming>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayGpuTimingsError {
+    #[error("failed to create output dir: {0}")]
+    CreateOutputDir(std::io::Error),
+    #[error("gpu timings job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+    #[error("failed to write chrome trace: {0}")]
+    WriteTrace(std::io::Error),
+}
+
+impl RenderDocInstallation {
+    /// Fetches per-event GPU durations, aggregates them by marker path, and writes
+    /// `<basename>.gpu_trace.json`.
+    pub fn replay_gpu_timings(
+        &self,
+        cwd: &Path,
+        req: &ReplayGpuTimingsRequest,
+    ) -> Result<ReplayGpuTimingsResponse, ReplayGpuTimingsError> {
+        let (capture, output) = req
+            .output
+            .normalized_for_capture(cwd, &req.capture)
+            .map_err(ReplayGpuTimingsError::CreateOutputDir)?;
+        req.filter.validate()?;
+        let job: ReplayGpuTimingsJobResponse = self.run_qrenderdoc_job(
+            cwd,
+            REPLAY_GPU_TIMINGS_JOB,
+            &ReplayGpuTimingsJobRequest {
+                capture,
+                drawcall_scope: req.drawcall_scope,
+                filter: req.filter.clone(),
+            },
+        )?;
+
+        let output_dir = output.output_dir.as_deref().unwrap_or_default();
+        let basename = output.basename.as_deref().unwrap_or_default();
+        let trace_path = Path::new(output_dir).join(format!("{basename}.gpu_trace.json"));
+        std::fs::write(
+            &trace_path,
+            chrome_trace(&job.capture_path, &job.events).to_string(),
+        )
+        .map_err(ReplayGpuTimingsError::WriteTrace)?;
+
+        let top_n = req.top_n as usize;
+        let mut top_events = job.events.clone();
+        top_events.sort_by(|a, b| b.duration_us.total_cmp(&a.duration_us));
+        top_events.truncate(top_n);
+        let mut top_passes = marker_timings(&job.events);
+        top_passes.truncate(top_n);
+
+        Ok(ReplayGpuTimingsResponse {
+            capture: CaptureRef::new(job.capture_path),
+            api: job.api,
+            timed_events: job.events.len() as u64,
+            total_duration_us: job.events.iter().map(|event| event.duration_us).sum(),
+            top_passes,
+            top_events,
+            chrome_trace_path: path_to_api_string(&trace_path),
+        })
+    }
+}
+
+/// Sums event durations into every enclosing marker path, most expensive first.
+fn marker_timings(events: &[EventGpuTiming]) -> Vec<MarkerGpuTiming> {
+    let mut passes: Vec<MarkerGpuTiming> = Vec::new();
+    let mut index_by_path: HashMap<Vec<String>, usize> = HashMap::new();
+    for event in events {
+        let path = &event.marker_path.0;
+        for depth in 1..=path.len() {
+            let prefix = &path[..depth];
+            let index = *index_by_path.entry(prefix.to_vec()).or_insert_with(|| {
+                passes.push(MarkerGpuTiming {
+                    marker_path: MarkerPath(prefix.to_vec()),
+                    duration_us: 0.0,
+                    self_duration_us: 0.0,
+                    events: 0,
+                    first_event_id: event.event_id,
+                    last_event_id: event.event_id,
+                });
+                passes.len() - 1
+            });
+            let pass = &mut passes[index];
+            pass.duration_us += event.duration_us;
+            pass.events += 1;
+            pass.last_event_id = event.event_id;
+            if depth == path.len() {
+                pass.self_duration_us += event.duration_us;
+            }
+        }
+    }
+    passes.sort_by(|a, b| {
+        b.duration_us
+            .total_cmp(&a.duration_us)
+            .then(a.first_event_id.cmp(&b.first_event_id))
+    });
+    passes
+}
+
+/// Lays events out back to back in event order, with each marker as a slice spanning its events.
+///
+/// GPU idle time between events isn't measured, so the timeline's length is the sum of event
+/// durations rather than the frame's wall-clock time.
+fn chrome_trace(capture_path: &str, events: &[EventGpuTiming]) -> Value {
+    let mut trace_events = vec![json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": 1,
+        "args": {"name": format!("GPU: {capture_path}")},
+    })];
+    let mut open_markers: Vec<(String, f64)> = Vec::new();
+    let mut cursor_us = 0.0;
+
+    let close_marker = |trace_events: &mut Vec<Value>, name: String, start_us: f64, end_us| {
+        trace_events.push(json!({
+            "name": name,
+            "cat": "marker",
+            "ph": "X",
+            "ts": start_us,
+            "dur": end_us - start_us,
+            "pid": 1,
+            "tid": 1,
+        }));
+    };
+
+    for event in events {
+        let path = &event.marker_path.0;
+        let shared = open_markers
+            .iter()
+            .zip(path)
+            .take_while(|((open, _), name)| open == *name)
+            .count();
+        for (name, start_us) in open_markers.drain(shared..).rev() {
+            close_marker(&mut trace_events, name, start_us, cursor_us);
+        }
+        open_markers.extend(path[shared..].iter().map(|name| (name.clone(), cursor_us)));
+
+        trace_events.push(json!({
+            "name": event.name,
+            "cat": "event",
+            "ph": "X",
+            "ts": cursor_us,
+            "dur": event.duration_us,
+            "pid": 1,
+            "tid": 1,
+            "args": {"event_id": event.event_id, "flags": event.flags.names()},
+        }));
+        cursor_us += event.duration_us;
+    }
+    for (name, start_us) in open_markers.drain(..).rev() {
+        close_marker(&mut trace_events, name, start_us, cursor_us);
+    }
+
+    json!({"traceEvents": trace_events, "displayTimeUnit": "ms"})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventGpuTiming, chrome_trace, marker_timings};
+    use crate::test_support::frame_events;
+
+    /// Every drawcall, dispatch and present of the shared frame, with a made-up duration.
+    fn fixture() -> Vec<EventGpuTiming> {
+        let durations_us = [(2, 100.0), (4, 300.0), (5, 100.0), (6, 25.0), (7, 5.0)];
+        let events = frame_events();
+        durations_us
+            .iter()
+            .map(|&(event_id, duration_us)| {
+                let event = events
+                    .iter()
+                    .find(|event| event.event_id == event_id)
+                    .expect("frame event");
+                EventGpuTiming {
+                    event_id,
+                    name: event.name.clone(),
+                    flags: event.flags,
+                    marker_path: event.marker_path.clone(),
+                    duration_us,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn marker_timings_are_inclusive_and_sorted_by_cost() {
+        let passes = marker_timings(&fixture());
+        let summary: Vec<(String, f64, f64, u64)> = passes
+            .iter()
+            .map(|pass| {
+                (
+                    pass.marker_path.joined(),
+                    pass.duration_us,
+                    pass.self_duration_us,
+                    pass.events,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Opaque".to_string(), 425.0, 400.0, 3),
+                ("Shadows".to_string(), 100.0, 100.0, 1),
+                ("Opaque/Cull".to_string(), 25.0, 25.0, 1),
+            ]
+        );
+        assert_eq!(passes[0].first_event_id, 4);
+        assert_eq!(passes[0].last_event_id, 6);
+    }
+
+    #[test]
+    fn chrome_trace_nests_markers_around_back_to_back_events() {
+        let trace = chrome_trace("/captures/frame.rdc", &fixture());
+        let slices: Vec<(String, f64, f64)> = trace["traceEvents"]
+            .as_array()
+            .expect("traceEvents")
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                (
+                    event["name"].as_str().unwrap_or_default().to_string(),
+                    event["ts"].as_f64().unwrap_or_default(),
+                    event["dur"].as_f64().unwrap_or_default(),
+                )
+            })
+            .collect();
+        let slice = |name: &str| {
+            slices
+                .iter()
+                .find(|(slice, _, _)| slice == name)
+                .map(|(_, ts, dur)| (*ts, *dur))
+        };
+
+        assert_eq!(slice("Shadows"), Some((0.0, 100.0)));
+        assert_eq!(slice("Opaque"), Some((100.0, 425.0)));
+        assert_eq!(slice("Cull"), Some((500.0, 25.0)));
+        assert_eq!(slice("Dispatch(8, 8, 1)"), Some((500.0, 25.0)));
+        assert_eq!(slice("Present"), Some((525.0, 5.0)));
+        assert_eq!(slices.len(), 8);
+    }
+}
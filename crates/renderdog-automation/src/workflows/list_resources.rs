@@ -0,0 +1,271 @@
+use std::path::Path;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::REPLAY_LIST_RESOURCES_JOB;
+use crate::{QRenderDocJobError, RenderDocInstallation};
+
+use super::event_query::fold_case;
+use super::{CaptureInput, CaptureRef, FindEventsLimit};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceFilter {
+    #[serde(default)]
+    pub name_contains: Option<String>,
+    /// Regex matched against the resource name; `case_sensitive` doesn't apply (use `(?i)`).
+    #[serde(default)]
+    pub name_regex: Option<String>,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// RenderDoc resource types to keep, e.g. `Texture`, `Buffer`, `Shader`, `PipelineState`,
+    /// `Sampler`. Matched case-insensitively as a prefix, so `pipeline` matches `PipelineState`.
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+}
+
+/// Lists every resource of a capture: textures, buffers, shaders, pipelines, samplers, and the rest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayListResourcesRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub filter: ResourceFilter,
+    #[serde(flatten)]
+    pub limit: FindEventsLimit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceTextureInfo {
+    /// RenderDoc `TextureType`, e.g. `Texture2D`, `TextureCube`.
+    pub texture_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub mips: u32,
+    pub array_size: u32,
+    pub ms_samp: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayResourceInfo {
+    pub resource_id: u64,
+    pub name: String,
+    /// `true` when RenderDoc made the name up because the application never set one.
+    pub autogenerated_name: bool,
+    /// RenderDoc `ResourceType`, e.g. `Texture`, `Buffer`, `Shader`, `PipelineState`.
+    pub resource_type: String,
+    /// Texture format, e.g. `R8G8B8A8_UNORM`.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Texture/buffer creation flags (`ColorTarget`, `ShaderRead`, `Vertex`, `Constants`, ...).
+    #[serde(default)]
+    pub usage_flags: Vec<String>,
+    #[serde(default)]
+    pub byte_size: Option<u64>,
+    #[serde(default)]
+    pub texture: Option<ResourceTextureInfo>,
+    /// Resources this one was created from, e.g. the texture behind a view.
+    #[serde(default)]
+    pub parent_ids: Vec<u64>,
+    /// Resources created from this one.
+    #[serde(default)]
+    pub derived_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayListResourcesResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    pub total_resources: u64,
+    pub total_matches: u64,
+    pub truncated: bool,
+    pub resources: Vec<ReplayResourceInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ReplayListResourcesJobRequest {
+    #[serde(flatten)]
+    capture: CaptureInput,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplayListResourcesJobResponse {
+    capture_path: String,
+    resources: Vec<ReplayResourceInfo>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayListResourcesError {
+    #[error("invalid name_regex {pattern:?}: {source}")]
+    InvalidRegex {
+        pattern: String,
+        source: regex::Error,
+    },
+    #[error("list resources job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+}
+
+impl RenderDocInstallation {
+    pub fn replay_list_resources(
+        &self,
+        cwd: &Path,
+        req: &ReplayListResourcesRequest,
+    ) -> Result<ReplayListResourcesResponse, ReplayListResourcesError> {
+        let matcher = ResourceMatcher::new(&req.filter)?;
+        let job: ReplayListResourcesJobResponse = self.run_qrenderdoc_job(
+            cwd,
+            REPLAY_LIST_RESOURCES_JOB,
+            &ReplayListResourcesJobRequest {
+                capture: req.capture.normalized_in_cwd(cwd),
+            },
+        )?;
+
+        let total_resources = job.resources.len() as u64;
+        let mut resources: Vec<ReplayResourceInfo> = job
+            .resources
+            .into_iter()
+            .filter(|resource| matcher.matches(resource))
+            .collect();
+        let total_matches = resources.len() as u64;
+        if let Some(max_results) = req.limit.max_results {
+            resources.truncate(max_results as usize);
+        }
+
+        Ok(ReplayListResourcesResponse {
+            capture: CaptureRef::new(job.capture_path),
+            total_resources,
+            total_matches,
+            truncated: (resources.len() as u64) < total_matches,
+            resources,
+        })
+    }
+}
+
+struct ResourceMatcher {
+    name_contains: Option<String>,
+    name_regex: Option<Regex>,
+    case_sensitive: bool,
+    resource_types: Vec<String>,
+}
+
+impl ResourceMatcher {
+    fn new(filter: &ResourceFilter) -> Result<Self, ReplayListResourcesError> {
+        let name_regex = filter
+            .name_regex
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|source| ReplayListResourcesError::InvalidRegex {
+                    pattern: pattern.to_string(),
+                    source,
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            name_contains: filter
+                .name_contains
+                .as_deref()
+                .map(|value| fold_case(value, filter.case_sensitive)),
+            name_regex,
+            case_sensitive: filter.case_sensitive,
+            resource_types: filter
+                .resource_types
+                .iter()
+                .map(|ty| ty.to_lowercase())
+                .collect(),
+        })
+    }
+
+    fn matches(&self, resource: &ReplayResourceInfo) -> bool {
+        if let Some(value) = &self.name_contains
+            && !fold_case(&resource.name, self.case_sensitive).contains(value.as_str())
+        {
+            return false;
+        }
+        if let Some(regex) = &self.name_regex
+            && !regex.is_match(&resource.name)
+        {
+            return false;
+        }
+        let resource_type = resource.resource_type.to_lowercase();
+        self.resource_types.is_empty()
+            || self
+                .resource_types
+                .iter()
+                .any(|ty| resource_type.starts_with(ty.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplayResourceInfo, ResourceFilter, ResourceMatcher};
+
+    fn resource(name: &str, resource_type: &str) -> ReplayResourceInfo {
+        ReplayResourceInfo {
+            resource_id: 1,
+            name: name.to_string(),
+            autogenerated_name: false,
+            resource_type: resource_type.to_string(),
+            format: None,
+            usage_flags: Vec::new(),
+            byte_size: None,
+            texture: None,
+            parent_ids: Vec::new(),
+            derived_ids: Vec::new(),
+        }
+    }
+
+    fn matching(filter: ResourceFilter, resources: &[ReplayResourceInfo]) -> Vec<&str> {
+        let matcher = ResourceMatcher::new(&filter).expect("compile filter");
+        resources
+            .iter()
+            .filter(|resource| matcher.matches(resource))
+            .map(|resource| resource.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn resource_filter_combines_name_and_type() {
+        let resources = [
+            resource("GBuffer Albedo", "Texture"),
+            resource("GBuffer Normals", "Texture"),
+            resource("gbuffer_constants", "Buffer"),
+            resource("Opaque PSO", "PipelineState"),
+        ];
+
+        let by_name = ResourceFilter {
+            name_contains: Some("GBUFFER".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            matching(by_name, &resources),
+            ["GBuffer Albedo", "GBuffer Normals", "gbuffer_constants"]
+        );
+
+        let by_type_and_regex = ResourceFilter {
+            name_regex: Some("(?i)albedo|pso".to_string()),
+            resource_types: vec!["texture".to_string(), "pipeline".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            matching(by_type_and_regex, &resources),
+            ["GBuffer Albedo", "Opaque PSO"]
+        );
+
+        let case_sensitive = ResourceFilter {
+            name_contains: Some("GBuffer".to_string()),
+            case_sensitive: true,
+            resource_types: vec!["Buffer".to_string()],
+            ..Default::default()
+        };
+        assert!(matching(case_sensitive, &resources).is_empty());
+
+        let invalid = ResourceFilter {
+            name_regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(ResourceMatcher::new(&invalid).is_err());
+    }
+}
@@ -1,22 +1,66 @@
 //! High-level RenderDoc workflows built on `qrenderdoc --python`.
 
+mod event_query;
 mod export_actions;
+mod export_annotations;
 mod export_bindings_index;
 mod export_bundle;
+#[cfg(feature = "sqlite")]
+mod export_sqlite;
 mod find_and_save_outputs;
 mod find_events;
+mod frame_graph;
+mod frame_stats;
+mod gpu_timings;
+mod list_resources;
 mod one_shot;
+mod resource_usage;
+mod scan_outputs;
 mod trigger_capture;
 
+pub(crate) use event_query::fold_case;
+pub use event_query::{EventQuery, EventQueryError, EventQueryMatcher, query_actions_jsonl};
+pub use export_annotations::ExportAnnotationsError;
 pub use export_bundle::ExportBundleError;
+#[cfg(feature = "sqlite")]
+pub use export_sqlite::{
+    BuildSqliteRequest, ExportSqliteRequest, ExportSqliteResponse, SqliteExportArtifacts,
+    SqliteExportError, SqliteQueryError, SqliteQueryRequest, SqliteQueryResponse,
+    SqliteTableCounts, build_sqlite, query_sqlite,
+};
 pub use find_and_save_outputs::{
     FindEventSelection, FindEventsAndSaveOutputsPngError, FindEventsAndSaveOutputsPngRequest,
     FindEventsAndSaveOutputsPngResponse,
 };
 pub use find_events::FindEventsError;
+pub use frame_graph::{
+    FrameGraph, FrameGraphArtifacts, FrameGraphEdge, FrameGraphError, FrameGraphPass,
+    FrameGraphRequest, FrameGraphResource, FrameGraphResponse,
+};
+pub use frame_stats::{
+    FrameStats, FrameStatsArtifacts, FrameStatsCounts, FrameStatsError, FrameStatsRequest,
+    FrameStatsResponse, MarkerPassStats,
+};
+pub use gpu_timings::{
+    EventGpuTiming, MarkerGpuTiming, ReplayGpuTimingsError, ReplayGpuTimingsRequest,
+    ReplayGpuTimingsResponse,
+};
+pub use list_resources::{
+    ReplayListResourcesError, ReplayListResourcesRequest, ReplayListResourcesResponse,
+    ReplayResourceInfo, ResourceFilter, ResourceTextureInfo,
+};
 pub use one_shot::{
     CaptureAndExportBundleError, CaptureAndExportBundleRequest, CaptureAndExportBundleResponse,
-    OneShotCaptureError,
+    CapturedBundleExport, OneShotCaptureError,
+};
+pub use resource_usage::{
+    ReplayResourceUsageError, ReplayResourceUsageRequest, ReplayResourceUsageResponse,
+    ResourceAccess, ResourceSelector, ResourceUsage, ResourceUsageEvent,
+};
+pub use scan_outputs::{
+    FloatValue, NonFiniteFloat, OutputChannelStats, OutputSample, OutputTargetScan,
+    ScanOutputsForInvalidValuesError, ScanOutputsForInvalidValuesRequest,
+    ScanOutputsForInvalidValuesResponse,
 };
 pub use trigger_capture::TriggerCaptureError;
 
@@ -27,7 +71,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::scripting::PrepareQRenderDocJobRequest;
 use crate::{
-    QRenderDocJobError, normalize_capture_path, prepare_export_target, resolve_path_string_from_cwd,
+    CaptureProvenance, QRenderDocJobError, normalize_capture_path, prepare_export_target,
+    resolve_path_string_from_cwd,
 };
 
 fn default_max_results() -> Option<u32> {
@@ -166,6 +211,22 @@ pub struct EventFilter {
     pub marker_contains: Option<String>,
     #[serde(default)]
     pub case_sensitive: bool,
+    /// Further conditions, ANDed with the fields above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<EventQuery>,
+}
+
+impl EventFilter {
+    /// Compiles `query` so a bad regex or flag name fails before `qrenderdoc` starts.
+    pub(crate) fn validate(&self) -> Result<(), QRenderDocJobError> {
+        match &self.query {
+            Some(query) => query
+                .matcher()
+                .map(drop)
+                .map_err(QRenderDocJobError::InvalidEventQuery),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, JsonSchema)]
@@ -196,6 +257,12 @@ pub struct BindingsExportOptions {
     pub include_outputs: bool,
 }
 
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationsExportOptions {
+    #[serde(default)]
+    pub include_annotations: bool,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BundleExportOptions {
     #[serde(flatten)]
@@ -205,6 +272,8 @@ pub struct BundleExportOptions {
     #[serde(flatten)]
     pub bindings: BindingsExportOptions,
     #[serde(flatten)]
+    pub annotations: AnnotationsExportOptions,
+    #[serde(flatten)]
     pub post_actions: CapturePostActions,
 }
 
@@ -214,6 +283,9 @@ pub struct ActionsExportArtifacts {
     pub actions_summary_json_path: String,
     pub total_actions: u64,
     pub drawcall_actions: u64,
+    /// Build provenance embedded in the capture comments by the in-app helper, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<CaptureProvenance>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -223,6 +295,17 @@ pub struct BindingsExportArtifacts {
     pub total_drawcalls: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationsExportArtifacts {
+    pub annotations_jsonl_path: String,
+    pub annotations_summary_json_path: String,
+    /// `false` when the replay runtime predates annotation support, in which case
+    /// the JSONL file is empty regardless of what the capture contains.
+    pub annotations_api_available: bool,
+    pub object_annotations: u64,
+    pub command_annotations: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BundleExportArtifacts {
     #[serde(flatten)]
@@ -230,6 +313,8 @@ pub struct BundleExportArtifacts {
     #[serde(flatten)]
     pub bindings: BindingsExportArtifacts,
     #[serde(flatten)]
+    pub annotations: Option<AnnotationsExportArtifacts>,
+    #[serde(flatten)]
     pub post_actions: CapturePostActionOutputs,
 }
 
@@ -237,6 +322,7 @@ impl BundleExportArtifacts {
     pub(crate) fn from_parts(
         actions: ExportActionsResponse,
         bindings: ExportBindingsIndexResponse,
+        annotations: Option<ExportAnnotationsResponse>,
         post_actions: CapturePostActionOutputs,
     ) -> Self {
         let ExportActionsResponse { artifacts: actions } = actions;
@@ -247,11 +333,18 @@ impl BundleExportArtifacts {
         Self {
             actions,
             bindings,
+            annotations: annotations.map(|response| response.artifacts),
             post_actions,
         }
     }
 }
 
+/// How and when to capture from a target.
+///
+/// By default `num_frames` consecutive frames are captured immediately. `wait_for_frame` queues
+/// those frames to start at a future frame number instead, and `capture_at_frames` queues one
+/// capture at each listed frame (taking precedence over `num_frames`/`wait_for_frame`). The
+/// `timeout_s` budget must cover the time the target needs to reach the last requested frame.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TriggerCaptureOptions {
     #[serde(default = "default_host")]
@@ -260,6 +353,19 @@ pub struct TriggerCaptureOptions {
     pub num_frames: u32,
     #[serde(default = "default_timeout_s")]
     pub timeout_s: u32,
+    /// Frame numbers to capture, one capture each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capture_at_frames: Vec<u32>,
+    /// First frame of the `num_frames` captures, instead of the next presented frame.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for_frame: Option<u32>,
+    /// Time to wait after connecting before triggering or queueing, e.g. to skip loading screens.
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Follow child processes announced over target control and capture the first process in the
+    /// tree that registers a graphics API, for apps started through a launcher.
+    #[serde(default)]
+    pub follow_children: bool,
 }
 
 impl Default for TriggerCaptureOptions {
@@ -268,11 +374,40 @@ impl Default for TriggerCaptureOptions {
             host: default_host(),
             num_frames: default_frames(),
             timeout_s: default_timeout_s(),
+            capture_at_frames: Vec::new(),
+            wait_for_frame: None,
+            delay_ms: 0,
+            follow_children: false,
         }
     }
 }
 
 impl TriggerCaptureOptions {
+    /// `(frame_number, num_frames)` pairs to queue, or empty to trigger `num_frames` right away.
+    pub(crate) fn queued_captures(&self) -> Vec<(u32, u32)> {
+        if !self.capture_at_frames.is_empty() {
+            let mut frames = self.capture_at_frames.clone();
+            frames.sort_unstable();
+            frames.dedup();
+            return frames.into_iter().map(|frame| (frame, 1)).collect();
+        }
+
+        self.wait_for_frame
+            .map(|frame| vec![(frame, self.num_frames.max(1))])
+            .unwrap_or_default()
+    }
+
+    /// Number of `NewCapture` messages the trigger should produce.
+    pub(crate) fn expected_captures(&self) -> usize {
+        match self.queued_captures().as_slice() {
+            [] => self.num_frames.max(1) as usize,
+            queued => queued
+                .iter()
+                .map(|&(_, num_frames)| num_frames as usize)
+                .sum(),
+        }
+    }
+
     pub(crate) fn for_target(&self, target: TargetControlRef) -> TriggerCaptureRequest {
         TriggerCaptureRequest {
             target,
@@ -289,12 +424,51 @@ pub struct TriggerCaptureRequest {
     pub trigger: TriggerCaptureOptions,
 }
 
+/// One capture written by the target in response to a trigger.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct TriggerCaptureResponse {
+pub struct TriggeredCapture {
     #[serde(flatten)]
     pub capture: CaptureRef,
     pub frame_number: u32,
     pub api: String,
+    #[serde(default)]
+    pub byte_size: u64,
+    /// JPEG thumbnail sent by the target, written next to local captures as `<name>.thumb.jpg`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_path: Option<String>,
+}
+
+/// A process seen while following children of a launched target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TargetProcessNode {
+    pub pid: u32,
+    pub target_ident: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_pid: Option<u32>,
+    /// Empty for children that were announced but never accepted a connection.
+    #[serde(default)]
+    pub executable: String,
+    /// Graphics API the process registered, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<String>,
+    /// Whether captures were taken from this process.
+    #[serde(default)]
+    pub captured: bool,
+}
+
+/// Captures received for a trigger, in the order the target wrote them.
+///
+/// A trigger for `num_frames` frames produces one capture per frame. If the timeout expires after
+/// at least one capture arrived, the captures received so far are returned.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TriggerCaptureResponse {
+    /// PID of the target process, as reported in the target-control handshake.
+    #[serde(default)]
+    pub pid: u32,
+    pub captures: Vec<TriggeredCapture>,
+    /// Processes seen with `follow_children`, launcher first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub process_tree: Vec<TargetProcessNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -340,11 +514,12 @@ impl PrepareQRenderDocJobRequest for FindEventsRequest {
     type Error = QRenderDocJobError;
 
     fn prepare_in_cwd(&self, cwd: &Path) -> Result<Self, Self::Error> {
+        self.filter.validate()?;
         Ok(self.normalized_in_cwd(cwd))
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(transparent)]
 pub struct MarkerPath(pub Vec<String>);
 
@@ -401,6 +576,19 @@ impl EventFlags {
         Self { bits }
     }
 
+    pub fn from_name(name: &str) -> Option<Self> {
+        EVENT_FLAG_NAMES
+            .iter()
+            .find(|(_, flag_name)| *flag_name == name)
+            .map(|(bit, _)| Self::new(*bit))
+    }
+
+    /// Draws and dispatches of any kind, matching the `only_drawcalls` scope.
+    pub fn is_drawcall_like(&self) -> bool {
+        const DRAWCALL_LIKE: u32 = 0x0002 | 0x0004 | 0x0008 | 0x4000;
+        self.bits & DRAWCALL_LIKE != 0
+    }
+
     pub fn names(&self) -> Vec<&'static str> {
         EVENT_FLAG_NAMES
             .iter()
@@ -469,6 +657,46 @@ pub(crate) struct ExportBindingsIndexResponse {
     pub artifacts: BindingsExportArtifacts,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAnnotationsRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub output: ExportOutput,
+    #[serde(flatten)]
+    pub filter: EventFilter,
+}
+
+impl ExportAnnotationsRequest {
+    pub(crate) fn normalized_in_cwd(&self, cwd: &Path) -> Result<Self, std::io::Error> {
+        let (capture, output) = self.output.normalized_for_capture(cwd, &self.capture)?;
+
+        Ok(Self {
+            capture,
+            output,
+            filter: self.filter.clone(),
+        })
+    }
+}
+
+impl PrepareQRenderDocJobRequest for ExportAnnotationsRequest {
+    type Error = ExportAnnotationsError;
+
+    fn prepare_in_cwd(&self, cwd: &Path) -> Result<Self, Self::Error> {
+        self.filter.validate()?;
+        self.normalized_in_cwd(cwd)
+            .map_err(ExportAnnotationsError::CreateOutputDir)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAnnotationsResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    #[serde(flatten)]
+    pub artifacts: AnnotationsExportArtifacts,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExportBundleRequest {
     #[serde(flatten)]
@@ -503,14 +731,51 @@ mod tests {
     use serde_json::Value;
 
     use super::{
-        ActionsExportArtifacts, BindingsExportArtifacts, BindingsExportOptions,
-        BundleExportArtifacts, BundleExportOptions, CaptureInput, CapturePostActionOutputs,
-        CapturePostActions, CaptureRef, DrawcallScope, EventFilter, EventFlags,
-        ExportActionsResponse, ExportBindingsIndexResponse, ExportBundleRequest,
-        ExportBundleResponse, ExportOutput, FindEventsResponse, FindEventsSummary, FoundEvent,
-        MarkerPath, OutputFile, TargetControlRef, TriggerCaptureOptions, TriggerCaptureRequest,
-        TriggerCaptureResponse,
+        ActionsExportArtifacts, AnnotationsExportArtifacts, AnnotationsExportOptions,
+        BindingsExportArtifacts, BindingsExportOptions, BundleExportArtifacts, BundleExportOptions,
+        CaptureInput, CapturePostActionOutputs, CapturePostActions, CaptureRef, DrawcallScope,
+        EventFilter, EventFlags, ExportActionsResponse, ExportAnnotationsResponse,
+        ExportBindingsIndexResponse, ExportBundleRequest, ExportBundleResponse, ExportOutput,
+        FindEventsResponse, FindEventsSummary, FoundEvent, MarkerPath, OutputFile,
+        TargetControlRef, TriggerCaptureOptions, TriggerCaptureRequest, TriggerCaptureResponse,
+        TriggeredCapture,
     };
+    use crate::{
+        CaptureProvenance, EventQuery, FindEventsLimit, FindEventsRequest, QRenderDocJobError,
+        RenderDocInstallation,
+    };
+
+    #[test]
+    fn find_events_rejects_invalid_query_before_launching_qrenderdoc() {
+        let install = RenderDocInstallation {
+            root_dir: "missing".into(),
+            qrenderdoc_exe: "missing/qrenderdoc".into(),
+            renderdoccmd_exe: "missing/renderdoccmd".into(),
+        };
+        let req = FindEventsRequest {
+            capture: CaptureInput {
+                capture_path: "frame.rdc".to_string(),
+            },
+            drawcall_scope: DrawcallScope::default(),
+            filter: EventFilter {
+                // Look-behind is valid Python `re` but not `regex` syntax.
+                query: Some(EventQuery::NameRegex {
+                    pattern: "(?<=Draw)Indexed".to_string(),
+                }),
+                ..EventFilter::default()
+            },
+            limit: FindEventsLimit::default(),
+        };
+
+        let err = install
+            .find_events(Path::new("/nonexistent/project"), &req)
+            .expect_err("invalid query");
+
+        assert!(
+            matches!(err, QRenderDocJobError::InvalidEventQuery(_)),
+            "{err}"
+        );
+    }
 
     #[test]
     fn capture_input_normalizes_relative_path_in_cwd() {
@@ -559,6 +824,7 @@ mod tests {
             host: "renderdoc-host".to_string(),
             num_frames: 3,
             timeout_s: 90,
+            ..TriggerCaptureOptions::default()
         }
         .for_target(TargetControlRef::new(17));
 
@@ -576,6 +842,7 @@ mod tests {
                 host: "renderdoc-host".to_string(),
                 num_frames: 3,
                 timeout_s: 90,
+                ..TriggerCaptureOptions::default()
             },
         };
 
@@ -595,6 +862,54 @@ mod tests {
         assert!(!object.contains_key("trigger"));
     }
 
+    #[test]
+    fn trigger_capture_options_queue_listed_frames_once_each() {
+        let options = TriggerCaptureOptions {
+            num_frames: 5,
+            capture_at_frames: vec![1200, 120, 600, 120],
+            wait_for_frame: Some(10),
+            ..TriggerCaptureOptions::default()
+        };
+
+        assert_eq!(
+            options.queued_captures(),
+            vec![(120, 1), (600, 1), (1200, 1)]
+        );
+        assert_eq!(options.expected_captures(), 3);
+    }
+
+    #[test]
+    fn trigger_capture_options_queue_consecutive_frames_from_wait_for_frame() {
+        let options = TriggerCaptureOptions {
+            num_frames: 2,
+            wait_for_frame: Some(300),
+            ..TriggerCaptureOptions::default()
+        };
+        assert_eq!(options.queued_captures(), vec![(300, 2)]);
+        assert_eq!(options.expected_captures(), 2);
+
+        let immediate = TriggerCaptureOptions {
+            num_frames: 4,
+            ..TriggerCaptureOptions::default()
+        };
+        assert!(immediate.queued_captures().is_empty());
+        assert_eq!(immediate.expected_captures(), 4);
+    }
+
+    #[test]
+    fn trigger_capture_options_deserialize_schedule_fields() {
+        let options: TriggerCaptureOptions = serde_json::from_value(serde_json::json!({
+            "capture_at_frames": [120, 600, 1200],
+            "delay_ms": 1500
+        }))
+        .expect("deserialize options");
+
+        assert_eq!(options.capture_at_frames, vec![120, 600, 1200]);
+        assert_eq!(options.delay_ms, 1500);
+        assert_eq!(options.wait_for_frame, None);
+        assert_eq!(options.num_frames, 1);
+    }
+
     #[test]
     fn target_control_ref_serializes_flat_field() {
         let target = TargetControlRef::new(17);
@@ -609,29 +924,63 @@ mod tests {
     }
 
     #[test]
-    fn trigger_capture_response_serializes_capture_flattened() {
+    fn trigger_capture_response_serializes_captures_flattened() {
         let response = TriggerCaptureResponse {
-            capture: CaptureRef::new("/tmp/frame.rdc"),
-            frame_number: 2,
-            api: "Vulkan".to_string(),
+            pid: 4242,
+            process_tree: Vec::new(),
+            captures: vec![
+                TriggeredCapture {
+                    capture: CaptureRef::new("/tmp/frame_2.rdc"),
+                    frame_number: 2,
+                    api: "Vulkan".to_string(),
+                    byte_size: 1024,
+                    thumbnail_path: Some("/tmp/frame_2.thumb.jpg".to_string()),
+                },
+                TriggeredCapture {
+                    capture: CaptureRef::new("/tmp/frame_3.rdc"),
+                    frame_number: 3,
+                    api: "Vulkan".to_string(),
+                    byte_size: 2048,
+                    thumbnail_path: None,
+                },
+            ],
         };
 
         let json = serde_json::to_value(response).expect("serialize response");
-        let object = json.as_object().expect("response object");
+        let captures = json["captures"].as_array().expect("captures array");
 
+        assert_eq!(captures.len(), 2);
+        let first = captures[0].as_object().expect("capture object");
         assert_eq!(
-            object.get("capture_path"),
-            Some(&Value::String("/tmp/frame.rdc".to_string()))
+            first.get("capture_path"),
+            Some(&Value::String("/tmp/frame_2.rdc".to_string()))
         );
         assert_eq!(
-            object.get("frame_number"),
+            first.get("frame_number"),
             Some(&Value::Number(2_u32.into()))
         );
+        assert_eq!(first.get("api"), Some(&Value::String("Vulkan".to_string())));
         assert_eq!(
-            object.get("api"),
-            Some(&Value::String("Vulkan".to_string()))
+            first.get("byte_size"),
+            Some(&Value::Number(1024_u64.into()))
+        );
+        assert_eq!(
+            first.get("thumbnail_path"),
+            Some(&Value::String("/tmp/frame_2.thumb.jpg".to_string()))
+        );
+        assert!(!first.contains_key("capture"));
+        assert!(
+            !json
+                .as_object()
+                .expect("response")
+                .contains_key("process_tree")
+        );
+        assert!(
+            !captures[1]
+                .as_object()
+                .expect("capture object")
+                .contains_key("thumbnail_path")
         );
-        assert!(!object.contains_key("capture"));
     }
 
     #[test]
@@ -656,6 +1005,7 @@ mod tests {
                     include_cbuffers: true,
                     include_outputs: false,
                 },
+                annotations: AnnotationsExportOptions::default(),
                 post_actions: CapturePostActions {
                     save_thumbnail: true,
                     thumbnail_output_path: Some("thumb.png".to_string()),
@@ -766,6 +1116,10 @@ mod tests {
                         actions_summary_json_path: "/tmp/out/frame.summary.json".to_string(),
                         total_actions: 10,
                         drawcall_actions: 4,
+                        provenance: Some(CaptureProvenance {
+                            git_sha: Some("abc123".to_string()),
+                            ..CaptureProvenance::default()
+                        }),
                     },
                 },
                 ExportBindingsIndexResponse {
@@ -776,6 +1130,7 @@ mod tests {
                         total_drawcalls: 4,
                     },
                 },
+                None,
                 CapturePostActionOutputs {
                     thumbnail_output_path: Some("/tmp/out/frame.thumb.png".to_string()),
                     ui_pid: Some(123),
@@ -807,5 +1162,89 @@ mod tests {
         assert!(!object.contains_key("artifacts"));
         assert!(!object.contains_key("actions"));
         assert!(!object.contains_key("bindings"));
+        assert!(!object.contains_key("annotations_jsonl_path"));
+        assert_eq!(
+            object
+                .get("provenance")
+                .and_then(|provenance| provenance.get("git_sha")),
+            Some(&Value::String("abc123".to_string()))
+        );
+    }
+
+    #[test]
+    fn export_annotations_response_round_trips_flattened_artifacts() {
+        let response = ExportAnnotationsResponse {
+            capture: CaptureRef::new("/tmp/frame.rdc"),
+            artifacts: AnnotationsExportArtifacts {
+                annotations_jsonl_path: "/tmp/out/frame.annotations.jsonl".to_string(),
+                annotations_summary_json_path: "/tmp/out/frame.annotations_summary.json"
+                    .to_string(),
+                annotations_api_available: true,
+                object_annotations: 2,
+                command_annotations: 5,
+            },
+        };
+
+        let json = serde_json::to_value(&response).expect("serialize response");
+        let object = json.as_object().expect("response object");
+
+        assert_eq!(
+            object.get("annotations_jsonl_path"),
+            Some(&Value::String(
+                "/tmp/out/frame.annotations.jsonl".to_string()
+            ))
+        );
+        assert_eq!(
+            object.get("command_annotations"),
+            Some(&Value::Number(5_u32.into()))
+        );
+        assert!(!object.contains_key("artifacts"));
+
+        let parsed: ExportAnnotationsResponse =
+            serde_json::from_value(json).expect("deserialize response");
+        assert_eq!(parsed.capture.capture_path, "/tmp/frame.rdc");
+        assert_eq!(parsed.artifacts.object_annotations, 2);
+    }
+
+    #[test]
+    fn bundle_artifacts_merge_annotations_when_exported() {
+        let json = serde_json::json!({
+            "actions_jsonl_path": "/tmp/out/frame.actions.jsonl",
+            "actions_summary_json_path": "/tmp/out/frame.summary.json",
+            "total_actions": 10,
+            "drawcall_actions": 4,
+            "bindings_jsonl_path": "/tmp/out/frame.bindings.jsonl",
+            "bindings_summary_json_path": "/tmp/out/frame.bindings_summary.json",
+            "total_drawcalls": 4,
+            "annotations_jsonl_path": "/tmp/out/frame.annotations.jsonl",
+            "annotations_summary_json_path": "/tmp/out/frame.annotations_summary.json",
+            "annotations_api_available": true,
+            "object_annotations": 1,
+            "command_annotations": 3,
+        });
+
+        let artifacts: BundleExportArtifacts =
+            serde_json::from_value(json).expect("deserialize artifacts");
+        let annotations = artifacts.annotations.expect("annotations artifacts");
+
+        assert_eq!(
+            annotations.annotations_jsonl_path,
+            "/tmp/out/frame.annotations.jsonl"
+        );
+        assert_eq!(annotations.command_annotations, 3);
+    }
+
+    #[test]
+    fn bundle_options_deserialize_include_annotations_flag() {
+        let options: BundleExportOptions =
+            serde_json::from_value(serde_json::json!({ "include_annotations": true }))
+                .expect("deserialize options");
+
+        assert!(options.annotations.include_annotations);
+        assert!(
+            !BundleExportOptions::default()
+                .annotations
+                .include_annotations
+        );
     }
 }
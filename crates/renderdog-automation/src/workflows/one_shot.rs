@@ -1,16 +1,22 @@
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::capture::LaunchedCaptureTarget;
+use crate::process::{OutputCollector, TerminateOnDrop, finish_target_process};
 use crate::{
-    BundleExportArtifacts, BundleExportOptions, CaptureLaunchReport, CaptureRef,
+    BundleExportArtifacts, BundleExportOptions, CaptureLaunchReport, CaptureTargetConnection,
     CaptureTargetError, CaptureTargetRequest, ExportBundleError, ExportBundleRequest,
-    ExportBundleResponse, ExportOutput, RenderDocInstallation, TriggerCaptureError,
-    TriggerCaptureOptions,
+    ExportBundleResponse, ExportOutput, RenderDocInstallation, TargetControlClient,
+    TargetControlConnectOptions, TargetExitReport, TargetProcessOptions, TriggerCaptureError,
+    TriggerCaptureOptions, TriggeredCapture, path_to_api_string,
 };
 
+/// How long to wait for a freshly launched target's handshake when looking up its PID.
+const LAUNCH_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CaptureAndExportBundleRequest {
     #[serde(flatten)]
@@ -21,24 +27,40 @@ pub struct CaptureAndExportBundleRequest {
     pub output: ExportOutput,
     #[serde(flatten)]
     pub bundle: BundleExportOptions,
+    #[serde(flatten)]
+    pub process: TargetProcessOptions,
 }
 
+/// Export artifacts for one of the triggered captures.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct CaptureAndExportBundleResponse {
+pub struct CapturedBundleExport {
     #[serde(flatten)]
-    pub launch: CaptureLaunchReport,
-    #[serde(flatten)]
-    pub capture: CaptureRef,
+    pub capture: TriggeredCapture,
     #[serde(flatten)]
     pub artifacts: BundleExportArtifacts,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaptureAndExportBundleResponse {
+    #[serde(flatten)]
+    pub launch: CaptureLaunchReport,
+    /// One entry per triggered capture, in capture order.
+    pub captures: Vec<CapturedBundleExport>,
+    /// Present when `terminate_after_capture` or `wait_for_exit_s` was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_exit: Option<TargetExitReport>,
+}
+
 #[derive(Debug, Error)]
 pub enum OneShotCaptureError {
     #[error("failed to create output dir: {0}")]
     CreateOutputDir(std::io::Error),
     #[error("capture target failed: {0}")]
     CaptureTarget(#[from] CaptureTargetError),
+    #[error(
+        "wait_for_exit blocks until the target exits, so it cannot be combined with a triggered capture; use wait_for_exit_s instead"
+    )]
+    WaitForExitUnsupported,
     #[error("trigger capture failed: {0}")]
     Trigger(#[from] TriggerCaptureError),
 }
@@ -49,210 +71,336 @@ pub enum CaptureAndExportBundleError {
     Capture(#[from] OneShotCaptureError),
     #[error("export bundle failed: {0}")]
     Export(#[from] ExportBundleError),
+    #[error("failed to stop target process: {0}")]
+    TargetProcess(std::io::Error),
 }
 
 struct CompletedOneShotCapture {
     launch: CaptureLaunchReport,
-    export: ExportBundleRequest,
+    captures: Vec<(TriggeredCapture, ExportBundleRequest)>,
 }
 
 impl CompletedOneShotCapture {
-    fn into_response(self, export: ExportBundleResponse) -> CaptureAndExportBundleResponse {
-        let ExportBundleResponse { capture, artifacts } = export;
+    fn into_response(
+        self,
+        exports: Vec<ExportBundleResponse>,
+        target_exit: Option<TargetExitReport>,
+    ) -> CaptureAndExportBundleResponse {
+        let captures = self
+            .captures
+            .into_iter()
+            .zip(exports)
+            .map(|((mut capture, _), export)| {
+                capture.capture = export.capture;
+                CapturedBundleExport {
+                    capture,
+                    artifacts: export.artifacts,
+                }
+            })
+            .collect();
 
         CaptureAndExportBundleResponse {
             launch: self.launch,
-            capture,
-            artifacts,
+            captures,
+            target_exit,
         }
     }
 }
 
+/// Appends `_frame<N>` to a caller-chosen basename or thumbnail path so that multi-frame triggers
+/// don't overwrite each other's exports. Default basenames already differ per capture file.
+fn per_frame_export_request(
+    output: &ExportOutput,
+    bundle: &BundleExportOptions,
+    frame_number: u32,
+) -> (ExportOutput, BundleExportOptions) {
+    let suffix = format!("_frame{frame_number}");
+    let mut output = output.clone();
+    let mut bundle = bundle.clone();
+
+    if let Some(basename) = output.basename.as_mut() {
+        basename.push_str(&suffix);
+    }
+    if let Some(thumbnail) = bundle.post_actions.thumbnail_output_path.as_mut() {
+        let path = Path::new(thumbnail.as_str());
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("thumb");
+        let file_name = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => format!("{stem}{suffix}.{ext}"),
+            None => format!("{stem}{suffix}"),
+        };
+        *thumbnail = path_to_api_string(&path.with_file_name(file_name));
+    }
+
+    (output, bundle)
+}
+
 impl RenderDocInstallation {
     pub fn capture_and_export_bundle(
         &self,
         cwd: &Path,
         req: &CaptureAndExportBundleRequest,
     ) -> Result<CaptureAndExportBundleResponse, CaptureAndExportBundleError> {
-        let capture = self.capture_one_shot(cwd, req)?;
-        let export = self.export_bundle(cwd, &capture.export)?;
+        let (capture, target_output) = self.capture_one_shot(cwd, req)?;
+        let exports = capture
+            .captures
+            .iter()
+            .map(|(_, export)| self.export_bundle(cwd, export))
+            .collect::<Result<Vec<_>, _>>();
+
+        // Stop the target even when an export failed, so CI runs don't leak processes.
+        let target_exit = match capture.launch.pid {
+            Some(pid) if req.process.is_enabled() => Some(
+                finish_target_process(pid, &req.process, &target_output)
+                    .map_err(CaptureAndExportBundleError::TargetProcess),
+            ),
+            _ => None,
+        };
+        let exports = exports?;
+        let target_exit = target_exit.transpose()?;
 
-        Ok(capture.into_response(export))
+        Ok(capture.into_response(exports, target_exit))
     }
 
     fn capture_one_shot(
         &self,
         cwd: &Path,
         req: &CaptureAndExportBundleRequest,
-    ) -> Result<CompletedOneShotCapture, OneShotCaptureError> {
-        let resolved_target = req.target.resolved_in_cwd(cwd)?;
-        let launched_target = self.launch_capture_target(&resolved_target)?;
-
-        let triggered_capture = self.trigger_capture_via_target_control(
-            cwd,
-            &req.trigger.for_target(launched_target.target),
-        )?;
-
-        let (capture, output) = req
-            .output
-            .normalized_for_capture(cwd, &triggered_capture.capture)
-            .map_err(OneShotCaptureError::CreateOutputDir)?;
-
-        Ok(CompletedOneShotCapture {
-            launch: launched_target,
-            export: ExportBundleRequest {
-                capture,
-                output,
-                bundle: req.bundle.clone(),
+    ) -> Result<(CompletedOneShotCapture, OutputCollector), OneShotCaptureError> {
+        let resolved_target = if req.trigger.follow_children {
+            // Children only show up over target control when RenderDoc hooks into them.
+            let mut target = req.target.clone();
+            target.capture_options.hook_into_children = true;
+            target.resolved_in_cwd(cwd)?
+        } else {
+            req.target.resolved_in_cwd(cwd)?
+        };
+        if resolved_target.waits_for_exit() {
+            return Err(OneShotCaptureError::WaitForExitUnsupported);
+        }
+        let LaunchedCaptureTarget {
+            report: mut launch,
+            output: target_output,
+        } = self.launch_capture_target(&resolved_target)?;
+
+        // renderdoccmd only reports the target ident; the handshake carries the PID. When the
+        // target is to be terminated anyway, any error from here on terminates it instead of
+        // leaving it running.
+        let launched_pid = TargetControlClient::connect(
+            &req.trigger.host,
+            launch.target.target_ident,
+            &TargetControlConnectOptions {
+                timeout: LAUNCH_HANDSHAKE_TIMEOUT,
+                ..TargetControlConnectOptions::default()
             },
-        })
+        )
+        .ok()
+        .map(|client| client.pid());
+        let terminate_on_error = |pid: Option<u32>| {
+            TerminateOnDrop::new(
+                pid.filter(|_| req.process.terminate_after_capture),
+                Duration::from_millis(req.process.terminate_grace_ms),
+            )
+        };
+        let launched_guard = terminate_on_error(launched_pid);
+
+        // With `follow_children` the captured process is a child of the launched one; guard it
+        // too, and drop it before the launcher.
+        let connection = CaptureTargetConnection::connect(&req.trigger.for_target(launch.target))
+            .map_err(TriggerCaptureError::from)?;
+        let captured_guard =
+            terminate_on_error(Some(connection.pid()).filter(|&pid| Some(pid) != launched_pid));
+
+        let triggered = connection
+            .trigger(&req.trigger)
+            .map_err(TriggerCaptureError::from)?;
+        launch.pid = (triggered.pid != 0)
+            .then_some(triggered.pid)
+            .or(launched_pid);
+        launch.process_tree = triggered.process_tree;
+
+        let multi_frame = triggered.captures.len() > 1;
+        let captures = triggered
+            .captures
+            .into_iter()
+            .map(|triggered_capture| {
+                let (output, bundle) = if multi_frame {
+                    per_frame_export_request(
+                        &req.output,
+                        &req.bundle,
+                        triggered_capture.frame_number,
+                    )
+                } else {
+                    (req.output.clone(), req.bundle.clone())
+                };
+                let (capture, output) = output
+                    .normalized_for_capture(cwd, &triggered_capture.capture)
+                    .map_err(OneShotCaptureError::CreateOutputDir)?;
+
+                Ok((
+                    triggered_capture,
+                    ExportBundleRequest {
+                        capture,
+                        output,
+                        bundle,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>, OneShotCaptureError>>()?;
+
+        captured_guard.disarm();
+        launched_guard.disarm();
+        Ok((CompletedOneShotCapture { launch, captures }, target_output))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use serde_json::Value;
 
     use super::{
         CaptureAndExportBundleError, CaptureAndExportBundleRequest, CaptureAndExportBundleResponse,
-        CompletedOneShotCapture, OneShotCaptureError,
+        CapturedBundleExport, CompletedOneShotCapture, OneShotCaptureError,
+        per_frame_export_request,
     };
     use crate::{
-        ActionsExportArtifacts, BindingsExportArtifacts, BindingsExportOptions,
-        BundleExportArtifacts, BundleExportOptions, CaptureInput, CaptureLaunchReport,
-        CapturePostActionOutputs, CapturePostActions, CaptureRef, CaptureTargetError,
-        CaptureTargetRequest, DrawcallScope, EventFilter, ExportBundleRequest,
-        ExportBundleResponse, ExportOutput, TargetControlRef, TriggerCaptureOptions,
+        ActionsExportArtifacts, AnnotationsExportOptions, BindingsExportArtifacts,
+        BindingsExportOptions, BundleExportArtifacts, BundleExportOptions, CaptureInput,
+        CaptureLaunchOptions, CaptureLaunchReport, CapturePostActionOutputs, CapturePostActions,
+        CaptureRef, CaptureTargetError, CaptureTargetRequest, DrawcallScope, EventFilter,
+        ExportBundleRequest, ExportBundleResponse, ExportOutput, TargetControlRef,
+        TargetProcessOptions, TriggerCaptureOptions, TriggeredCapture,
     };
 
-    #[test]
-    fn completed_one_shot_capture_builds_export_bundle_request() {
-        let capture = CompletedOneShotCapture {
-            launch: CaptureLaunchReport {
-                target: TargetControlRef::new(7),
-                capture_file_template: Some("/tmp/frame".to_string()),
-                stdout: "stdout".to_string(),
-                stderr: "stderr".to_string(),
+    fn launch_report(target_ident: u32) -> CaptureLaunchReport {
+        CaptureLaunchReport {
+            target: TargetControlRef::new(target_ident),
+            pid: Some(4242),
+            capture_file_template: Some("/tmp/frame".to_string()),
+            stdout: "stdout".to_string(),
+            stderr: "stderr".to_string(),
+            process_tree: Vec::new(),
+        }
+    }
+
+    fn triggered_capture(frame_number: u32) -> TriggeredCapture {
+        TriggeredCapture {
+            capture: CaptureRef::new(format!("/tmp/frame{frame_number}.rdc")),
+            frame_number,
+            api: "Vulkan".to_string(),
+            byte_size: 4096,
+            thumbnail_path: None,
+        }
+    }
+
+    fn bundle_artifacts(basename: &str) -> BundleExportArtifacts {
+        BundleExportArtifacts {
+            actions: ActionsExportArtifacts {
+                actions_jsonl_path: format!("/tmp/out/{basename}.actions.jsonl"),
+                actions_summary_json_path: format!("/tmp/out/{basename}.summary.json"),
+                total_actions: 10,
+                drawcall_actions: 4,
+                provenance: None,
             },
-            export: ExportBundleRequest {
-                capture: CaptureInput {
-                    capture_path: "/tmp/frame.rdc".to_string(),
-                },
-                output: ExportOutput {
-                    output_dir: Some("/tmp/out".to_string()),
-                    basename: Some("frame".to_string()),
-                },
-                bundle: BundleExportOptions {
-                    drawcall_scope: DrawcallScope {
-                        only_drawcalls: true,
-                    },
-                    filter: EventFilter {
-                        marker_contains: Some("fret".to_string()),
-                        ..EventFilter::default()
-                    },
-                    bindings: BindingsExportOptions {
-                        include_cbuffers: true,
-                        include_outputs: false,
-                    },
-                    post_actions: CapturePostActions {
-                        save_thumbnail: true,
-                        thumbnail_output_path: Some("thumb.png".to_string()),
-                        open_capture_ui: false,
-                    },
-                },
+            bindings: BindingsExportArtifacts {
+                bindings_jsonl_path: format!("/tmp/out/{basename}.bindings.jsonl"),
+                bindings_summary_json_path: format!("/tmp/out/{basename}.bindings_summary.json"),
+                total_drawcalls: 4,
+            },
+            annotations: None,
+            post_actions: CapturePostActionOutputs {
+                thumbnail_output_path: Some(format!("/tmp/out/{basename}.thumb.png")),
+                ui_pid: Some(123),
+            },
+        }
+    }
+
+    #[test]
+    fn per_frame_export_request_suffixes_caller_chosen_names() {
+        let output = ExportOutput {
+            output_dir: Some("/tmp/out".to_string()),
+            basename: Some("frame".to_string()),
+        };
+        let bundle = BundleExportOptions {
+            post_actions: CapturePostActions {
+                save_thumbnail: true,
+                thumbnail_output_path: Some("/tmp/out/thumb.png".to_string()),
+                open_capture_ui: false,
             },
+            ..BundleExportOptions::default()
         };
 
-        assert_eq!(capture.export.capture.capture_path, "/tmp/frame.rdc");
-        assert_eq!(
-            capture.export.output.output_dir.as_deref(),
-            Some("/tmp/out")
-        );
-        assert_eq!(capture.export.output.basename.as_deref(), Some("frame"));
-        assert!(capture.export.bundle.drawcall_scope.only_drawcalls);
+        let (output, bundle) = per_frame_export_request(&output, &bundle, 12);
+
+        assert_eq!(output.output_dir.as_deref(), Some("/tmp/out"));
+        assert_eq!(output.basename.as_deref(), Some("frame_frame12"));
         assert_eq!(
-            capture.export.bundle.filter.marker_contains.as_deref(),
-            Some("fret")
+            bundle.post_actions.thumbnail_output_path.as_deref(),
+            Some("/tmp/out/thumb_frame12.png")
         );
-        assert!(capture.export.bundle.bindings.include_cbuffers);
-        assert!(capture.export.bundle.post_actions.save_thumbnail);
     }
 
     #[test]
-    fn completed_one_shot_capture_merges_export_response() {
-        let capture = CompletedOneShotCapture {
-            launch: CaptureLaunchReport {
-                target: TargetControlRef::new(9),
-                capture_file_template: Some("/tmp/frame".to_string()),
-                stdout: "stdout".to_string(),
-                stderr: "stderr".to_string(),
-            },
-            export: ExportBundleRequest {
-                capture: CaptureInput {
-                    capture_path: "/tmp/frame.rdc".to_string(),
-                },
-                output: ExportOutput {
-                    output_dir: Some("/tmp/out".to_string()),
-                    basename: Some("frame".to_string()),
-                },
-                bundle: BundleExportOptions {
-                    drawcall_scope: DrawcallScope {
-                        only_drawcalls: false,
-                    },
-                    filter: EventFilter::default(),
-                    bindings: BindingsExportOptions {
-                        include_cbuffers: false,
-                        include_outputs: false,
-                    },
-                    post_actions: CapturePostActions::default(),
-                },
+    fn per_frame_export_request_keeps_default_names() {
+        let (output, bundle) =
+            per_frame_export_request(&ExportOutput::default(), &BundleExportOptions::default(), 3);
+
+        assert_eq!(output.basename, None);
+        assert_eq!(bundle.post_actions.thumbnail_output_path, None);
+    }
+
+    #[test]
+    fn completed_one_shot_capture_merges_export_responses() {
+        let export_request = |frame_number: u32| ExportBundleRequest {
+            capture: CaptureInput {
+                capture_path: format!("/tmp/frame{frame_number}.rdc"),
             },
+            output: ExportOutput::default(),
+            bundle: BundleExportOptions::default(),
         };
-        let export = ExportBundleResponse {
-            capture: CaptureRef::new("/tmp/frame.rdc"),
-            artifacts: BundleExportArtifacts {
-                actions: ActionsExportArtifacts {
-                    actions_jsonl_path: "/tmp/out/frame.actions.jsonl".to_string(),
-                    actions_summary_json_path: "/tmp/out/frame.summary.json".to_string(),
-                    total_actions: 10,
-                    drawcall_actions: 4,
-                },
-                bindings: BindingsExportArtifacts {
-                    bindings_jsonl_path: "/tmp/out/frame.bindings.jsonl".to_string(),
-                    bindings_summary_json_path: "/tmp/out/frame.bindings_summary.json".to_string(),
-                    total_drawcalls: 4,
-                },
-                post_actions: CapturePostActionOutputs {
-                    thumbnail_output_path: Some("/tmp/out/frame.thumb.png".to_string()),
-                    ui_pid: Some(123),
-                },
-            },
+        let capture = CompletedOneShotCapture {
+            launch: launch_report(9),
+            captures: vec![
+                (triggered_capture(10), export_request(10)),
+                (triggered_capture(11), export_request(11)),
+            ],
         };
+        let exports = vec![
+            ExportBundleResponse {
+                capture: CaptureRef::new("/tmp/frame10.rdc"),
+                artifacts: bundle_artifacts("frame10"),
+            },
+            ExportBundleResponse {
+                capture: CaptureRef::new("/tmp/frame11.rdc"),
+                artifacts: bundle_artifacts("frame11"),
+            },
+        ];
 
-        let response: CaptureAndExportBundleResponse = capture.into_response(export);
+        let response: CaptureAndExportBundleResponse = capture.into_response(exports, None);
 
         assert_eq!(response.launch.target.target_ident, 9);
-        assert_eq!(response.capture.capture_path, "/tmp/frame.rdc");
         assert_eq!(
             response.launch.capture_file_template.as_deref(),
             Some("/tmp/frame")
         );
-        assert_eq!(response.launch.stdout, "stdout");
-        assert_eq!(response.launch.stderr, "stderr");
+        assert_eq!(response.captures.len(), 2);
+        assert_eq!(response.captures[1].capture.frame_number, 11);
         assert_eq!(
-            response.artifacts.actions.actions_jsonl_path,
-            "/tmp/out/frame.actions.jsonl"
+            response.captures[1].capture.capture.capture_path,
+            "/tmp/frame11.rdc"
         );
-        assert_eq!(response.artifacts.bindings.total_drawcalls, 4);
         assert_eq!(
-            response
-                .artifacts
-                .post_actions
-                .thumbnail_output_path
-                .as_deref(),
-            Some("/tmp/out/frame.thumb.png")
+            response.captures[1].artifacts.actions.actions_jsonl_path,
+            "/tmp/out/frame11.actions.jsonl"
+        );
+        assert_eq!(
+            response.captures[0].artifacts.post_actions.ui_pid,
+            Some(123)
         );
-        assert_eq!(response.artifacts.post_actions.ui_pid, Some(123));
     }
 
     #[test]
@@ -264,6 +412,12 @@ mod tests {
                 working_dir: None,
                 artifacts_dir: None,
                 capture_template_name: Some("capture".to_string()),
+                capture_options: CaptureLaunchOptions {
+                    api_validation: true,
+                    ..CaptureLaunchOptions::default()
+                },
+                env: BTreeMap::new(),
+                wait_for_exit: false,
             },
             trigger: TriggerCaptureOptions::default(),
             output: ExportOutput::default(),
@@ -279,12 +433,17 @@ mod tests {
                     include_cbuffers: true,
                     include_outputs: false,
                 },
+                annotations: AnnotationsExportOptions::default(),
                 post_actions: CapturePostActions {
                     save_thumbnail: true,
                     thumbnail_output_path: Some("thumb.png".to_string()),
                     open_capture_ui: false,
                 },
             },
+            process: TargetProcessOptions {
+                terminate_after_capture: true,
+                ..TargetProcessOptions::default()
+            },
         };
 
         let json = serde_json::to_value(req).expect("serialize request");
@@ -311,36 +470,25 @@ mod tests {
         );
         assert_eq!(object.get("include_cbuffers"), Some(&Value::Bool(true)));
         assert_eq!(object.get("save_thumbnail"), Some(&Value::Bool(true)));
+        assert_eq!(object.get("api_validation"), Some(&Value::Bool(true)));
+        assert_eq!(object.get("allow_vsync"), Some(&Value::Bool(true)));
+        assert_eq!(
+            object.get("terminate_after_capture"),
+            Some(&Value::Bool(true))
+        );
         assert!(!object.contains_key("bundle"));
+        assert!(!object.contains_key("process"));
     }
 
     #[test]
     fn capture_and_export_bundle_response_serializes_artifacts_flattened() {
         let response = CaptureAndExportBundleResponse {
-            launch: CaptureLaunchReport {
-                target: TargetControlRef::new(9),
-                capture_file_template: Some("/tmp/frame".to_string()),
-                stdout: "stdout".to_string(),
-                stderr: "stderr".to_string(),
-            },
-            capture: CaptureRef::new("/tmp/frame.rdc"),
-            artifacts: BundleExportArtifacts {
-                actions: ActionsExportArtifacts {
-                    actions_jsonl_path: "/tmp/out/frame.actions.jsonl".to_string(),
-                    actions_summary_json_path: "/tmp/out/frame.summary.json".to_string(),
-                    total_actions: 10,
-                    drawcall_actions: 4,
-                },
-                bindings: BindingsExportArtifacts {
-                    bindings_jsonl_path: "/tmp/out/frame.bindings.jsonl".to_string(),
-                    bindings_summary_json_path: "/tmp/out/frame.bindings_summary.json".to_string(),
-                    total_drawcalls: 4,
-                },
-                post_actions: CapturePostActionOutputs {
-                    thumbnail_output_path: Some("/tmp/out/frame.thumb.png".to_string()),
-                    ui_pid: Some(123),
-                },
-            },
+            launch: launch_report(9),
+            captures: vec![CapturedBundleExport {
+                capture: triggered_capture(2),
+                artifacts: bundle_artifacts("frame2"),
+            }],
+            target_exit: None,
         };
 
         let json = serde_json::to_value(response).expect("serialize response");
@@ -350,10 +498,6 @@ mod tests {
             object.get("target_ident"),
             Some(&Value::Number(9_u32.into()))
         );
-        assert_eq!(
-            object.get("capture_path"),
-            Some(&Value::String("/tmp/frame.rdc".to_string()))
-        );
         assert_eq!(
             object.get("capture_file_template"),
             Some(&Value::String("/tmp/frame".to_string()))
@@ -362,27 +506,44 @@ mod tests {
             object.get("stdout"),
             Some(&Value::String("stdout".to_string()))
         );
+        assert_eq!(object.get("pid"), Some(&Value::Number(4242_u32.into())));
+        assert!(!object.contains_key("launch"));
+        assert!(!object.contains_key("target_exit"));
+
+        let captures = object
+            .get("captures")
+            .and_then(Value::as_array)
+            .expect("captures array");
+        assert_eq!(captures.len(), 1);
+        let capture = captures[0].as_object().expect("capture object");
         assert_eq!(
-            object.get("stderr"),
-            Some(&Value::String("stderr".to_string()))
+            capture.get("capture_path"),
+            Some(&Value::String("/tmp/frame2.rdc".to_string()))
         );
         assert_eq!(
-            object.get("actions_jsonl_path"),
-            Some(&Value::String("/tmp/out/frame.actions.jsonl".to_string()))
+            capture.get("frame_number"),
+            Some(&Value::Number(2_u32.into()))
         );
         assert_eq!(
-            object.get("bindings_jsonl_path"),
-            Some(&Value::String("/tmp/out/frame.bindings.jsonl".to_string()))
+            capture.get("byte_size"),
+            Some(&Value::Number(4096_u64.into()))
         );
         assert_eq!(
-            object.get("thumbnail_output_path"),
-            Some(&Value::String("/tmp/out/frame.thumb.png".to_string()))
+            capture.get("actions_jsonl_path"),
+            Some(&Value::String("/tmp/out/frame2.actions.jsonl".to_string()))
         );
-        assert!(!object.contains_key("launch"));
-        assert!(!object.contains_key("capture"));
-        assert!(!object.contains_key("artifacts"));
-        assert!(!object.contains_key("actions"));
-        assert!(!object.contains_key("bindings"));
+        assert_eq!(
+            capture.get("bindings_jsonl_path"),
+            Some(&Value::String("/tmp/out/frame2.bindings.jsonl".to_string()))
+        );
+        assert_eq!(
+            capture.get("thumbnail_output_path"),
+            Some(&Value::String("/tmp/out/frame2.thumb.png".to_string()))
+        );
+        assert!(!capture.contains_key("capture"));
+        assert!(!capture.contains_key("artifacts"));
+        assert!(!capture.contains_key("actions"));
+        assert!(!capture.contains_key("bindings"));
     }
 
     #[test]
@@ -0,0 +1,287 @@
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::REPLAY_RESOURCE_USAGE_JOB;
+use crate::scripting::PrepareQRenderDocJobRequest;
+use crate::{MarkerPath, QRenderDocJobError, RenderDocInstallation};
+
+use super::{CaptureInput, CaptureRef};
+
+/// Picks the resource(s) to report, by id or by exact name.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ResourceSelector {
+    Id {
+        /// `ResourceId` as shown in RenderDoc and returned by `replay_list_resources`.
+        resource_id: u64,
+    },
+    Name {
+        /// Every resource with exactly this debug name is reported.
+        resource_name: String,
+    },
+}
+
+/// Lists every event that reads, writes, clears, copies, or binds a resource.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayResourceUsageRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub resource: ResourceSelector,
+}
+
+impl ReplayResourceUsageRequest {
+    pub(crate) fn normalized_in_cwd(&self, cwd: &Path) -> Self {
+        Self {
+            capture: self.capture.normalized_in_cwd(cwd),
+            ..self.clone()
+        }
+    }
+}
+
+impl PrepareQRenderDocJobRequest for ReplayResourceUsageRequest {
+    type Error = QRenderDocJobError;
+
+    fn prepare_in_cwd(&self, cwd: &Path) -> Result<Self, Self::Error> {
+        Ok(self.normalized_in_cwd(cwd))
+    }
+}
+
+/// Coarse classification of RenderDoc's `ResourceUsage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceAccess {
+    Read,
+    Write,
+    ReadWrite,
+    Clear,
+    CopySource,
+    CopyDestination,
+    Barrier,
+    Other,
+}
+
+impl ResourceAccess {
+    /// Classifies a `ResourceUsage` name such as `PS_Resource` or `CopyDst`.
+    pub fn from_usage(usage: &str) -> Self {
+        let (_, usage) = split_usage_stage(usage);
+        match usage {
+            "Clear" | "Discard" => Self::Clear,
+            "CopySrc" | "ResolveSrc" => Self::CopySource,
+            "CopyDst" | "ResolveDst" | "Copy" | "Resolve" => Self::CopyDestination,
+            "RWResource" => Self::ReadWrite,
+            "ColorTarget" | "DepthStencilTarget" | "StreamOut" | "CPUWrite" | "GenMips" => {
+                Self::Write
+            }
+            "Resource" | "Constants" | "VertexBuffer" | "IndexBuffer" | "InputTarget"
+            | "Indirect" => Self::Read,
+            "Barrier" => Self::Barrier,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Splits a stage-prefixed usage (`VS_Constants`) into its shader stage and usage.
+fn split_usage_stage(usage: &str) -> (Option<&'static str>, &str) {
+    const STAGES: &[(&str, &str)] = &[
+        ("VS_", "vertex"),
+        ("HS_", "hull"),
+        ("DS_", "domain"),
+        ("GS_", "geometry"),
+        ("PS_", "pixel"),
+        ("CS_", "compute"),
+        ("TS_", "task"),
+        ("MS_", "mesh"),
+        ("All_", "all"),
+    ];
+    STAGES
+        .iter()
+        .find_map(|(prefix, stage)| usage.strip_prefix(prefix).map(|rest| (Some(*stage), rest)))
+        .unwrap_or((None, usage))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceUsageEvent {
+    /// API event that used the resource.
+    pub event_id: u32,
+    /// Action containing `event_id`; differs from it for state setup and barriers recorded before
+    /// the action.
+    pub action_event_id: Option<u32>,
+    pub name: String,
+    pub marker_path: MarkerPath,
+    /// Raw `ResourceUsage` name, e.g. `PS_Resource`, `ColorTarget`, `CopyDst`.
+    pub usage: String,
+    pub access: ResourceAccess,
+    /// Shader stage for stage-specific bindings (`vertex`, `pixel`, ..., or `all`).
+    pub stage: Option<String>,
+    /// View the resource was accessed through, if any.
+    pub view_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceUsage {
+    pub resource_id: u64,
+    pub resource_name: String,
+    pub resource_type: String,
+    pub usages: Vec<ResourceUsageEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayResourceUsageResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    pub resources: Vec<ResourceUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawResourceUsageEvent {
+    event_id: u32,
+    action_event_id: Option<u32>,
+    name: String,
+    marker_path: MarkerPath,
+    usage: String,
+    view_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawResourceUsage {
+    resource_id: u64,
+    resource_name: String,
+    resource_type: String,
+    usages: Vec<RawResourceUsageEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplayResourceUsageJobResponse {
+    capture_path: String,
+    resources: Vec<RawResourceUsage>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayResourceUsageError {
+    #[error("resource usage job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+}
+
+impl RenderDocInstallation {
+    /// Reports RenderDoc's per-event usage of the selected resource(s), in event order.
+    pub fn replay_resource_usage(
+        &self,
+        cwd: &Path,
+        req: &ReplayResourceUsageRequest,
+    ) -> Result<ReplayResourceUsageResponse, ReplayResourceUsageError> {
+        let job: ReplayResourceUsageJobResponse =
+            self.run_qrenderdoc_job_in_cwd(cwd, REPLAY_RESOURCE_USAGE_JOB, req)?;
+
+        Ok(ReplayResourceUsageResponse {
+            capture: CaptureRef::new(job.capture_path),
+            resources: job.resources.into_iter().map(ResourceUsage::from).collect(),
+        })
+    }
+}
+
+impl From<RawResourceUsage> for ResourceUsage {
+    fn from(raw: RawResourceUsage) -> Self {
+        let mut usages: Vec<ResourceUsageEvent> = raw
+            .usages
+            .into_iter()
+            .map(|usage| ResourceUsageEvent {
+                access: ResourceAccess::from_usage(&usage.usage),
+                stage: split_usage_stage(&usage.usage).0.map(str::to_string),
+                event_id: usage.event_id,
+                action_event_id: usage.action_event_id,
+                name: usage.name,
+                marker_path: usage.marker_path,
+                usage: usage.usage,
+                view_id: usage.view_id,
+            })
+            .collect();
+        usages.sort_by_key(|usage| usage.event_id);
+        Self {
+            resource_id: raw.resource_id,
+            resource_name: raw.resource_name,
+            resource_type: raw.resource_type,
+            usages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{RawResourceUsage, ReplayResourceUsageRequest, ResourceAccess, ResourceUsage};
+
+    #[test]
+    fn usage_names_are_classified_by_access_and_stage() {
+        let usage: RawResourceUsage = serde_json::from_value(json!({
+            "resource_id": 42,
+            "resource_name": "GBuffer Albedo",
+            "resource_type": "Texture",
+            "usages": [
+                {"event_id": 30, "action_event_id": 30, "name": "Draw", "marker_path": ["Lighting"],
+                 "usage": "PS_Resource", "view_id": 77},
+                {"event_id": 4, "action_event_id": 4, "name": "ClearColor", "marker_path": [],
+                 "usage": "Clear", "view_id": null},
+                {"event_id": 12, "action_event_id": 12, "name": "Draw", "marker_path": ["GBuffer"],
+                 "usage": "ColorTarget", "view_id": 78},
+                {"event_id": 40, "action_event_id": 40, "name": "Copy", "marker_path": [],
+                 "usage": "CopySrc", "view_id": null},
+                {"event_id": 41, "action_event_id": null, "name": "", "marker_path": [],
+                 "usage": "CS_RWResource", "view_id": null},
+                {"event_id": 42, "action_event_id": 43, "name": "Draw", "marker_path": [],
+                 "usage": "Barrier", "view_id": null},
+            ],
+        }))
+        .expect("parse usage");
+
+        let usage = ResourceUsage::from(usage);
+        let summary: Vec<(u32, ResourceAccess, Option<&str>)> = usage
+            .usages
+            .iter()
+            .map(|u| (u.event_id, u.access, u.stage.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (4, ResourceAccess::Clear, None),
+                (12, ResourceAccess::Write, None),
+                (30, ResourceAccess::Read, Some("pixel")),
+                (40, ResourceAccess::CopySource, None),
+                (41, ResourceAccess::ReadWrite, Some("compute")),
+                (42, ResourceAccess::Barrier, None),
+            ]
+        );
+        assert_eq!(
+            ResourceAccess::from_usage("CopyDst"),
+            ResourceAccess::CopyDestination
+        );
+        assert_eq!(ResourceAccess::from_usage("Unused"), ResourceAccess::Other);
+    }
+
+    #[test]
+    fn request_selects_resource_by_id_or_name() {
+        let by_id: ReplayResourceUsageRequest =
+            serde_json::from_value(json!({"capture_path": "a.rdc", "resource_id": 42}))
+                .expect("by id");
+        let by_name: ReplayResourceUsageRequest =
+            serde_json::from_value(json!({"capture_path": "a.rdc", "resource_name": "Albedo"}))
+                .expect("by name");
+
+        assert_eq!(
+            serde_json::to_value(&by_id).expect("serialize by id")["resource_id"],
+            42
+        );
+        assert_eq!(
+            serde_json::to_value(&by_name).expect("serialize by name")["resource_name"],
+            "Albedo"
+        );
+        assert!(
+            serde_json::from_value::<ReplayResourceUsageRequest>(json!({"capture_path": "a.rdc"}))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::HashMap, path::Path};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::qrenderdoc_jobs::SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB;
+use crate::scripting::PrepareQRenderDocJobRequest;
+use crate::{MarkerPath, QRenderDocJobError, RenderDocInstallation, ReplaySavedImageKind};
+
+use super::{CaptureInput, CaptureRef, EventFilter};
+
+fn default_true() -> bool {
+    true
+}
+
+/// Checks every bound color/depth output after each draw for NaN, Inf, and out-of-range values.
+///
+/// Only draws and mesh dispatches are scanned: compute dispatches don't write the bound targets.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanOutputsForInvalidValuesRequest {
+    #[serde(flatten)]
+    pub capture: CaptureInput,
+    #[serde(flatten)]
+    pub filter: EventFilter,
+    #[serde(default = "default_true")]
+    pub include_depth: bool,
+    /// Values below this count as invalid. Unset flags NaN, -Inf, and values below -FLT_MAX/4
+    /// (about -8.5e37), the widest range RenderDoc's float32 histogram can span.
+    #[serde(default)]
+    pub valid_min: Option<f64>,
+    /// Values above this count as invalid. Unset flags NaN, Inf, and values above FLT_MAX/4
+    /// (about 8.5e37).
+    #[serde(default)]
+    pub valid_max: Option<f64>,
+}
+
+impl ScanOutputsForInvalidValuesRequest {
+    pub(crate) fn normalized_in_cwd(&self, cwd: &Path) -> Self {
+        Self {
+            capture: self.capture.normalized_in_cwd(cwd),
+            ..self.clone()
+        }
+    }
+
+    fn validate(&self) -> Result<(), ScanOutputsForInvalidValuesError> {
+        match (self.valid_min, self.valid_max) {
+            (Some(min), Some(max)) if min > max => {
+                Err(ScanOutputsForInvalidValuesError::InvalidRange { min, max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn in_range(&self, value: f64) -> bool {
+        self.valid_min.is_none_or(|min| value >= min)
+            && self.valid_max.is_none_or(|max| value <= max)
+    }
+}
+
+impl PrepareQRenderDocJobRequest for ScanOutputsForInvalidValuesRequest {
+    type Error = ScanOutputsForInvalidValuesError;
+
+    fn prepare_in_cwd(&self, cwd: &Path) -> Result<Self, Self::Error> {
+        self.validate()?;
+        self.filter.validate()?;
+        Ok(self.normalized_in_cwd(cwd))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NonFiniteFloat {
+    Nan,
+    Inf,
+    #[serde(rename = "-inf")]
+    NegInf,
+}
+
+/// A float that survives JSON: non-finite values are written as `"nan"`, `"inf"`, or `"-inf"`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum FloatValue {
+    Finite(f64),
+    NonFinite(NonFiniteFloat),
+}
+
+impl FloatValue {
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Self::Finite(value) => value,
+            Self::NonFinite(NonFiniteFloat::Nan) => f64::NAN,
+            Self::NonFinite(NonFiniteFloat::Inf) => f64::INFINITY,
+            Self::NonFinite(NonFiniteFloat::NegInf) => f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn is_finite(self) -> bool {
+        matches!(self, Self::Finite(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OutputChannelStats {
+    pub min: FloatValue,
+    pub max: FloatValue,
+    /// Pixels whose value in this channel is NaN, Inf, or outside `valid_min`..`valid_max`.
+    ///
+    /// Counted from RenderDoc's histogram, which has no bucket for NaN. Drivers that drop NaN or
+    /// bin it at index 0 are both handled; on a driver that bins it anywhere else NaN pixels are
+    /// undercounted, though a NaN `min` or `max` still marks the target bad.
+    pub invalid_pixels: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OutputSample {
+    pub event_id: u32,
+    pub name: String,
+    pub marker_path: MarkerPath,
+    /// Pixels in the bound mip/slice.
+    pub pixels: u64,
+    /// One entry per color channel, or just depth for depth targets.
+    pub channels: Vec<OutputChannelStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OutputTargetScan {
+    pub resource_id: u64,
+    pub name: String,
+    pub format: String,
+    pub kind: ReplaySavedImageKind,
+    /// Color output slot the target was first seen in; `None` for depth.
+    pub index: Option<u32>,
+    pub events_scanned: u64,
+    pub bad_events: u64,
+    /// First scanned event after which the target held invalid values.
+    pub first_bad: Option<OutputSample>,
+    /// State after the last scanned event that had the target bound.
+    pub last: OutputSample,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanOutputsForInvalidValuesResponse {
+    #[serde(flatten)]
+    pub capture: CaptureRef,
+    pub events_scanned: u64,
+    pub bad_targets: u64,
+    /// Targets in order of first use.
+    pub targets: Vec<OutputTargetScan>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScannedTarget {
+    kind: ReplaySavedImageKind,
+    index: Option<u32>,
+    resource_id: u64,
+    pixels: u64,
+    channels: Vec<OutputChannelStats>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScannedEvent {
+    event_id: u32,
+    name: String,
+    marker_path: MarkerPath,
+    targets: Vec<ScannedTarget>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScannedResource {
+    resource_id: u64,
+    name: String,
+    format: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScanOutputsJobResponse {
+    capture_path: String,
+    resources: Vec<ScannedResource>,
+    events: Vec<ScannedEvent>,
+}
+
+#[derive(Debug, Error)]
+pub enum ScanOutputsForInvalidValuesError {
+    #[error("valid_min {min} is greater than valid_max {max}")]
+    InvalidRange { min: f64, max: f64 },
+    #[error("scan outputs job failed: {0}")]
+    Job(#[from] QRenderDocJobError),
+}
+
+impl RenderDocInstallation {
+    /// Replays each matching draw and reports, per output target, the first event after which
+    /// it holds NaN, Inf, or out-of-range values.
+    pub fn scan_outputs_for_invalid_values(
+        &self,
+        cwd: &Path,
+        req: &ScanOutputsForInvalidValuesRequest,
+    ) -> Result<ScanOutputsForInvalidValuesResponse, ScanOutputsForInvalidValuesError> {
+        let job: ScanOutputsJobResponse =
+            self.run_qrenderdoc_job_in_cwd(cwd, SCAN_OUTPUTS_FOR_INVALID_VALUES_JOB, req)?;
+        Ok(summarize_scan(req, job))
+    }
+}
+
+fn summarize_scan(
+    req: &ScanOutputsForInvalidValuesRequest,
+    job: ScanOutputsJobResponse,
+) -> ScanOutputsForInvalidValuesResponse {
+    let resources: HashMap<u64, ScannedResource> = job
+        .resources
+        .into_iter()
+        .map(|resource| (resource.resource_id, resource))
+        .collect();
+    let is_bad = |sample: &OutputSample| {
+        sample.channels.iter().any(|channel| {
+            channel.invalid_pixels > 0
+                || !channel.min.is_finite()
+                || !channel.max.is_finite()
+                || !req.in_range(channel.min.to_f64())
+                || !req.in_range(channel.max.to_f64())
+        })
+    };
+
+    let events_scanned = job.events.len() as u64;
+    let mut targets: Vec<OutputTargetScan> = Vec::new();
+    let mut index_by_target: HashMap<(u64, ReplaySavedImageKind), usize> = HashMap::new();
+    for event in job.events {
+        for target in event.targets {
+            let sample = OutputSample {
+                event_id: event.event_id,
+                name: event.name.clone(),
+                marker_path: event.marker_path.clone(),
+                pixels: target.pixels,
+                channels: target.channels,
+            };
+            let bad = is_bad(&sample);
+            let key = (target.resource_id, target.kind);
+            let scan = match index_by_target.get(&key) {
+                Some(&index) => &mut targets[index],
+                None => {
+                    let resource = resources.get(&target.resource_id);
+                    index_by_target.insert(key, targets.len());
+                    targets.push(OutputTargetScan {
+                        resource_id: target.resource_id,
+                        name: resource.map(|r| r.name.clone()).unwrap_or_default(),
+                        format: resource.map(|r| r.format.clone()).unwrap_or_default(),
+                        kind: target.kind,
+                        index: target.index,
+                        events_scanned: 0,
+                        bad_events: 0,
+                        first_bad: None,
+                        last: sample.clone(),
+                    });
+                    targets.last_mut().expect("just pushed")
+                }
+            };
+            scan.events_scanned += 1;
+            if bad {
+                scan.bad_events += 1;
+                scan.first_bad.get_or_insert_with(|| sample.clone());
+            }
+            scan.last = sample;
+        }
+    }
+
+    ScanOutputsForInvalidValuesResponse {
+        capture: CaptureRef::new(job.capture_path),
+        events_scanned,
+        bad_targets: targets.iter().filter(|t| t.first_bad.is_some()).count() as u64,
+        targets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        FloatValue, NonFiniteFloat, ScanOutputsForInvalidValuesRequest, ScanOutputsJobResponse,
+        summarize_scan,
+    };
+
+    fn channel(min: serde_json::Value, max: serde_json::Value, invalid: u64) -> serde_json::Value {
+        json!({"min": min, "max": max, "invalid_pixels": invalid})
+    }
+
+    fn event(event_id: u32, targets: serde_json::Value) -> serde_json::Value {
+        json!({
+            "event_id": event_id,
+            "name": format!("Draw {event_id}"),
+            "marker_path": ["Frame", "Lighting"],
+            "targets": targets,
+        })
+    }
+
+    fn color(resource_id: u64, channels: serde_json::Value) -> serde_json::Value {
+        json!({
+            "kind": "color", "index": 0, "resource_id": resource_id, "pixels": 64,
+            "channels": channels,
+        })
+    }
+
+    #[test]
+    fn scan_reports_first_event_each_target_goes_bad() {
+        let req: ScanOutputsForInvalidValuesRequest = serde_json::from_value(json!({
+            "capture_path": "/captures/frame.rdc",
+            "valid_max": 65504.0,
+        }))
+        .expect("parse request");
+        let job: ScanOutputsJobResponse = serde_json::from_value(json!({
+            "capture_path": "/captures/frame.rdc",
+            "resources": [
+                {"resource_id": 5, "name": "HDR", "format": "R16G16B16A16_FLOAT"},
+                {"resource_id": 6, "name": "Depth", "format": "D32_FLOAT"},
+            ],
+            "events": [
+                event(10, json!([
+                    color(5, json!([channel(json!(0.0), json!(1.0), 0)])),
+                    {"kind": "depth", "index": null, "resource_id": 6, "pixels": 64,
+                     "channels": [channel(json!(0.0), json!(1.0), 0)]},
+                ])),
+                event(11, json!([color(5, json!([channel(json!(0.0), json!(1.0), 3)]))])),
+                event(12, json!([color(5, json!([channel(json!("-inf"), json!("inf"), 7)]))])),
+                event(13, json!([color(5, json!([channel(json!(0.0), json!(70000.0), 0)]))])),
+            ],
+        }))
+        .expect("parse job response");
+
+        let res = summarize_scan(&req, job);
+        assert_eq!(res.events_scanned, 4);
+        assert_eq!(res.bad_targets, 1);
+
+        let hdr = &res.targets[0];
+        assert_eq!(hdr.name, "HDR");
+        assert_eq!(hdr.events_scanned, 4);
+        assert_eq!(hdr.bad_events, 3);
+        assert_eq!(hdr.first_bad.as_ref().map(|s| s.event_id), Some(11));
+        assert_eq!(hdr.last.event_id, 13);
+        assert_eq!(res.targets[1].name, "Depth");
+        assert_eq!(res.targets[1].first_bad, None);
+
+        let response = serde_json::to_value(&res).expect("serialize response");
+        assert_eq!(
+            response["targets"][0]["last"]["channels"][0]["max"],
+            70000.0
+        );
+    }
+
+    #[test]
+    fn float_values_round_trip_non_finite_values() {
+        let values: Vec<FloatValue> =
+            serde_json::from_value(json!([1.5, "nan", "inf", "-inf"])).expect("parse floats");
+        assert_eq!(values[0], FloatValue::Finite(1.5));
+        assert_eq!(values[1], FloatValue::NonFinite(NonFiniteFloat::Nan));
+        assert!(values[1].to_f64().is_nan());
+        assert_eq!(values[3].to_f64(), f64::NEG_INFINITY);
+        assert_eq!(
+            serde_json::to_value(&values).expect("serialize floats"),
+            json!([1.5, "nan", "inf", "-inf"])
+        );
+    }
+}
@@ -1,18 +1,28 @@
 use std::path::Path;
 
-use crate::qrenderdoc_jobs::TRIGGER_CAPTURE_JOB;
-use crate::{QRenderDocJobError, RenderDocInstallation};
+use thiserror::Error;
+
+use crate::{RenderDocInstallation, TargetControlError, trigger_capture_native};
 
 use super::{TriggerCaptureRequest, TriggerCaptureResponse};
 
-pub type TriggerCaptureError = QRenderDocJobError;
+#[derive(Debug, Error)]
+pub enum TriggerCaptureError {
+    #[error(transparent)]
+    TargetControl(#[from] TargetControlError),
+}
 
 impl RenderDocInstallation {
+    /// Triggers a capture over target control.
+    ///
+    /// Runs [`trigger_capture_native`]; no `qrenderdoc` process is launched, so neither the
+    /// installation nor `cwd` is used. Kept for callers that drive every workflow through
+    /// [`RenderDocInstallation`].
     pub fn trigger_capture_via_target_control(
         &self,
-        cwd: &Path,
+        _cwd: &Path,
         req: &TriggerCaptureRequest,
     ) -> Result<TriggerCaptureResponse, TriggerCaptureError> {
-        self.run_qrenderdoc_job(cwd, TRIGGER_CAPTURE_JOB, req)
+        Ok(trigger_capture_native(req)?)
     }
 }
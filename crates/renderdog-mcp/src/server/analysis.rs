@@ -0,0 +1,169 @@
+use rmcp::{
+    Json, Peer, RoleServer, handler::server::wrapper::Parameters, model::Meta, tool, tool_router,
+};
+
+use renderdog_automation as renderdog;
+
+use super::{CwdRequest, RenderdogMcpServer, ToolRun};
+
+#[tool_router(router = analysis_tool_router, vis = "pub(super)")]
+impl RenderdogMcpServer {
+    #[tool(
+        name = "renderdoc_frame_stats",
+        description = "Aggregate a .rdc capture's action tree per marker pass: draws, dispatches, clears, copies, indices/vertices/instances, dispatch groups, indirect calls, and pipeline/shader/render-target changes. Writes <basename>.frame_stats.json and a Markdown table (.frame_stats.md). Set include_state_changes=false to skip replaying every draw."
+    )]
+    async fn frame_stats_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::FrameStatsRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::FrameStatsResponse>, String> {
+        let tool = "renderdoc_frame_stats";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("frame stats", req, |install, cwd, req| {
+                install.frame_stats(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            passes = res.stats.passes.len(),
+            draws = res.stats.total.draws,
+            dispatches = res.stats.total.dispatches,
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_frame_graph",
+        description = "Build a pass-level dependency graph (frame graph) of a .rdc capture from RenderDoc's per-event resource usage: nodes are marker passes, edges are textures/buffers written by one pass and read by a later one. Writes <basename>.frame_graph.json, Graphviz .frame_graph.dot, and Mermaid .frame_graph.mmd, and returns passes whose outputs are never consumed. pass_depth truncates marker paths to merge nested markers."
+    )]
+    async fn frame_graph_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::FrameGraphRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::FrameGraphResponse>, String> {
+        let tool = "renderdoc_frame_graph";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("frame graph", req, |install, cwd, req| {
+                install.frame_graph(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            passes = res.passes,
+            edges = res.edges,
+            unconsumed_passes = res.unconsumed_passes.len(),
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_replay_gpu_timings",
+        description = "Time every action in a .rdc capture with RenderDoc's EventGPUDuration counter. Returns the top_n (default 10) most expensive marker passes (inclusive) and events, and writes a Chrome trace-format timeline (<basename>.gpu_trace.json) for chrome://tracing or Perfetto. Needs a replay that exposes GPU counters."
+    )]
+    async fn replay_gpu_timings_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ReplayGpuTimingsRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::ReplayGpuTimingsResponse>, String> {
+        let tool = "renderdoc_replay_gpu_timings";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("replay gpu timings", req, |install, cwd, req| {
+                install.replay_gpu_timings(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            timed_events = res.timed_events,
+            total_duration_us = res.total_duration_us,
+            chrome_trace_path = %res.chrome_trace_path,
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_replay_resource_usage",
+        description = "List every event in a .rdc capture that reads, writes, clears, copies, resolves, or binds a resource, selected by resource_id or exact resource_name. Uses RenderDoc's resource usage info, so copies, clears, and render-target writes are included. Each usage has the raw RenderDoc usage, an access kind (read/write/read_write/clear/copy_source/copy_destination/barrier/other), shader stage, and marker path."
+    )]
+    async fn replay_resource_usage_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ReplayResourceUsageRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::ReplayResourceUsageResponse>, String> {
+        let tool = "renderdoc_replay_resource_usage";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("replay resource usage", req, |install, cwd, req| {
+                install.replay_resource_usage(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            resources = res.resources.len(),
+            usages = res.resources.iter().map(|r| r.usages.len()).sum::<usize>(),
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_scan_outputs_for_invalid_values",
+        description = "Replay every draw and mesh dispatch of a .rdc capture (optionally narrowed with the find_events filters; compute dispatches are skipped) and check each bound color/depth output with RenderDoc's min/max and histogram queries for NaN, Inf, and values outside valid_min/valid_max. Returns per-channel min/max and invalid pixel counts, and the first event at which each target goes bad."
+    )]
+    async fn scan_outputs_for_invalid_values_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ScanOutputsForInvalidValuesRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::ScanOutputsForInvalidValuesResponse>, String> {
+        let tool = "renderdoc_scan_outputs_for_invalid_values";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("scan outputs", req, |install, cwd, req| {
+                install.scan_outputs_for_invalid_values(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            events_scanned = res.events_scanned,
+            targets = res.targets.len(),
+            bad_targets = res.bad_targets,
+            "ok"
+        );
+        Ok(Json(res))
+    }
+}
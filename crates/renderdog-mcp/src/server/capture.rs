@@ -2,7 +2,7 @@ use rmcp::{Json, handler::server::wrapper::Parameters, tool, tool_router};
 
 use renderdog_automation as renderdog;
 
-use super::{CwdRequest, RenderdogMcpServer, ToolRun};
+use super::{CwdRequest, RenderdogMcpServer, ToolRun, tool_result};
 
 #[tool_router(router = capture_tool_router, vis = "pub(super)")]
 impl RenderdogMcpServer {
@@ -18,9 +18,11 @@ impl RenderdogMcpServer {
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
         });
-        let res = run.with_install_and_cwd("save thumbnail", req, |install, cwd, req| {
-            install.save_thumbnail_in_cwd(&cwd, &req)
-        })?;
+        let res = run
+            .with_install_and_cwd("save thumbnail", req, |install, cwd, req| {
+                install.save_thumbnail_in_cwd(&cwd, &req)
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -43,9 +45,11 @@ impl RenderdogMcpServer {
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, capture_path = %req.inner.capture_path, "start");
         });
-        let res = run.with_install_and_cwd("open capture UI", req, |install, cwd, req| {
-            install.open_capture_ui_in_cwd(&cwd, &req)
-        })?;
+        let res = run
+            .with_install_and_cwd("open capture UI", req, |install, cwd, req| {
+                install.open_capture_ui_in_cwd(&cwd, &req)
+            })
+            .await?;
         tracing::info!(
             tool = tool,
             elapsed_ms = run.elapsed_ms(),
@@ -54,4 +58,27 @@ impl RenderdogMcpServer {
         );
         Ok(Json(res))
     }
+
+    #[tool(
+        name = "renderdoc_list_targets",
+        description = "List running RenderDoc-enabled applications on a host (executable, API, PID, busy state) and their target_ident for attaching. Ports that answered but failed the probe (e.g. protocol version mismatch) are listed under failed."
+    )]
+    async fn list_targets(
+        &self,
+        Parameters(req): Parameters<renderdog::ListTargetsRequest>,
+    ) -> Result<Json<renderdog::ListTargetsResponse>, String> {
+        let tool = "renderdoc_list_targets";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, host = %req.host, "start");
+        });
+        let res = tool_result(tool, "list targets", renderdog::list_targets(&req.host))?;
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            targets = res.targets.len(),
+            failed = res.failed.len(),
+            "ok"
+        );
+        Ok(Json(res))
+    }
 }
@@ -15,9 +15,11 @@ impl RenderdogMcpServer {
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, "start");
         });
-        let res = run.with_install("detect installation", |install| {
-            Ok::<_, std::convert::Infallible>(install.describe_installation())
-        })?;
+        let res = run
+            .with_install("detect installation", |install| {
+                Ok::<_, std::convert::Infallible>(install.describe_installation())
+            })
+            .await?;
 
         tracing::info!(tool = tool, elapsed_ms = run.elapsed_ms(), "ok");
         Ok(Json(res))
@@ -32,9 +34,11 @@ impl RenderdogMcpServer {
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, "start");
         });
-        let diag = run.with_install("diagnose vulkan layer", |install| {
-            install.diagnose_vulkan_layer()
-        })?;
+        let diag = run
+            .with_install("diagnose vulkan layer", |install| {
+                install.diagnose_vulkan_layer()
+            })
+            .await?;
         tracing::info!(tool = tool, elapsed_ms = run.elapsed_ms(), "ok");
         Ok(Json(diag))
     }
@@ -48,9 +52,11 @@ impl RenderdogMcpServer {
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, "start");
         });
-        let diag = run.with_install("diagnose environment", |install| {
-            Ok::<_, std::convert::Infallible>(install.diagnose_environment())
-        })?;
+        let diag = run
+            .with_install("diagnose environment", |install| {
+                Ok::<_, std::convert::Infallible>(install.diagnose_environment())
+            })
+            .await?;
         tracing::info!(tool = tool, elapsed_ms = run.elapsed_ms(), "ok");
         Ok(Json(diag))
     }
@@ -1,8 +1,10 @@
-use rmcp::{Json, handler::server::wrapper::Parameters, tool, tool_router};
+use rmcp::{
+    Json, Peer, RoleServer, handler::server::wrapper::Parameters, model::Meta, tool, tool_router,
+};
 
 use renderdog_automation as renderdog;
 
-use super::{CwdRequest, RenderdogMcpServer, ToolRun};
+use super::{CwdRequest, RenderdogMcpServer, ToolRun, tool_result};
 
 #[tool_router(router = export_tool_router, vis = "pub(super)")]
 impl RenderdogMcpServer {
@@ -13,14 +15,19 @@ impl RenderdogMcpServer {
     async fn export_bundle_tool(
         &self,
         Parameters(req): Parameters<CwdRequest<renderdog::ExportBundleRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
     ) -> Result<Json<renderdog::ExportBundleResponse>, String> {
         let tool = "renderdoc_export_bundle_jsonl";
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
-        });
-        let res = run.with_install_and_cwd("export bundle", req, |install, cwd, req| {
-            install.export_bundle(&cwd, &req)
-        })?;
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("export bundle", req, |install, cwd, req| {
+                install.export_bundle(&cwd, &req)
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -35,4 +42,92 @@ impl RenderdogMcpServer {
 
         Ok(Json(res))
     }
+
+    #[tool(
+        name = "renderdoc_export_sqlite",
+        description = "Export actions, markers, bindings, resources, textures and shaders from an existing .rdc capture into a normalized SQLite database (<basename>.sqlite). Query it with renderdoc_query_sqlite."
+    )]
+    async fn export_sqlite_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ExportSqliteRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<renderdog::ExportSqliteResponse>, String> {
+        let tool = "renderdoc_export_sqlite";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("export sqlite", req, |install, cwd, req| {
+                install.export_sqlite(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            capture_path = %res.capture.capture_path,
+            sqlite_path = %res.sqlite.sqlite_path,
+            actions = res.sqlite.tables.actions,
+            bindings = res.sqlite.tables.bindings,
+            "ok"
+        );
+
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_query_sqlite",
+        description = "Run one read-only SQL statement against a database written by renderdoc_export_sqlite. Tables: markers, actions, resources, textures, shaders, draw_shaders, bindings, outputs. Rows are capped by max_rows (default 200)."
+    )]
+    async fn query_sqlite_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::SqliteQueryRequest>>,
+    ) -> Result<Json<renderdog::SqliteQueryResponse>, String> {
+        let tool = "renderdoc_query_sqlite";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, sqlite_path = %req.inner.sqlite_path, "start");
+        });
+        let (cwd, req) = req.into_parts()?;
+        let res = tool_result(tool, "query sqlite", renderdog::query_sqlite(&cwd, &req))?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            rows = res.rows.len(),
+            truncated = res.truncated,
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
+    #[tool(
+        name = "renderdoc_validate_artifacts",
+        description = "Validate exported artifacts (*.actions.jsonl, *.bindings.jsonl, *.annotations.jsonl and their summaries) in a directory against the JSON Schemas for the current schema_version. Reports per-file record counts, schema versions found, and issues with line numbers."
+    )]
+    async fn validate_artifacts_tool(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ValidateArtifactsRequest>>,
+    ) -> Result<Json<renderdog::ValidateArtifactsResponse>, String> {
+        let tool = "renderdoc_validate_artifacts";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, artifacts_dir = %req.inner.artifacts_dir, "start");
+        });
+        let (cwd, req) = req.into_parts()?;
+        let res = tool_result(
+            tool,
+            "validate artifacts",
+            renderdog::validate_artifacts(&cwd, &req),
+        )?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            files = res.files.len(),
+            valid = res.valid,
+            "ok"
+        );
+        Ok(Json(res))
+    }
 }
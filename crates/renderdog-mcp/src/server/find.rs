@@ -1,8 +1,10 @@
-use rmcp::{Json, handler::server::wrapper::Parameters, tool, tool_router};
+use rmcp::{
+    Json, Peer, RoleServer, handler::server::wrapper::Parameters, model::Meta, tool, tool_router,
+};
 
 use renderdog_automation as renderdog;
 
-use super::{CwdRequest, RenderdogMcpServer, ToolRun};
+use super::{CwdRequest, RenderdogMcpServer, ToolRun, tool_result};
 
 #[tool_router(router = find_tool_router, vis = "pub(super)")]
 impl RenderdogMcpServer {
@@ -13,14 +15,19 @@ impl RenderdogMcpServer {
     async fn find_events(
         &self,
         Parameters(req): Parameters<CwdRequest<renderdog::FindEventsRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
     ) -> Result<Json<renderdog::FindEventsResponse>, String> {
         let tool = "renderdoc_find_events";
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
-        });
-        let res = run.with_install_and_cwd("find events", req, |install, cwd, req| {
-            install.find_events(&cwd, &req)
-        })?;
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd("find events", req, |install, cwd, req| {
+                install.find_events(&cwd, &req)
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -39,16 +46,21 @@ impl RenderdogMcpServer {
     async fn find_events_and_save_outputs_png(
         &self,
         Parameters(req): Parameters<CwdRequest<renderdog::FindEventsAndSaveOutputsPngRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
     ) -> Result<Json<renderdog::FindEventsAndSaveOutputsPngResponse>, String> {
         let tool = "renderdoc_find_events_and_save_outputs_png";
         let run = ToolRun::start(tool, || {
             tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
-        });
-        let res = run.with_install_and_cwd(
-            "find events and save outputs PNG",
-            req,
-            |install, cwd, req| install.find_events_and_save_outputs_png(&cwd, &req),
-        )?;
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd(
+                "find events and save outputs PNG",
+                req,
+                |install, cwd, req| install.find_events_and_save_outputs_png(&cwd, &req),
+            )
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -59,4 +71,33 @@ impl RenderdogMcpServer {
         );
         Ok(Json(res))
     }
+
+    #[tool(
+        name = "renderdoc_find_events_offline",
+        description = "Find matching action events in an exported <basename>.actions.jsonl without RenderDoc. Takes the same filters as renderdoc_find_events; use it to answer follow-up questions from an exported bundle."
+    )]
+    async fn find_events_offline(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::FindEventsOfflineRequest>>,
+    ) -> Result<Json<renderdog::FindEventsResponse>, String> {
+        let tool = "renderdoc_find_events_offline";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, actions_jsonl_path = %req.inner.actions_jsonl_path, "start");
+        });
+        let (cwd, req) = req.into_parts()?;
+        let res = tool_result(
+            tool,
+            "find events offline",
+            renderdog::find_events_offline(&cwd, &req),
+        )?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            total_matches = res.summary.total_matches,
+            truncated = res.summary.truncated,
+            "ok"
+        );
+        Ok(Json(res))
+    }
 }
@@ -1,13 +1,17 @@
+mod analysis;
 mod capture;
 mod diagnostics;
 mod export;
 mod find;
+mod progress;
 mod replay;
 mod workflows;
 
-use std::{fmt::Display, path::PathBuf, time::Instant};
+use std::{error::Error, path::PathBuf, time::Instant};
 
-use rmcp::{handler::server::router::tool::ToolRouter, tool_handler};
+use rmcp::{
+    Peer, RoleServer, handler::server::router::tool::ToolRouter, model::Meta, tool_handler,
+};
 use schemars::JsonSchema;
 
 use renderdog_automation as renderdog;
@@ -29,6 +33,7 @@ impl RenderdogMcpServer {
                 + Self::export_tool_router()
                 + Self::find_tool_router()
                 + Self::replay_tool_router()
+                + Self::analysis_tool_router()
                 + Self::workflows_tool_router(),
         }
     }
@@ -51,6 +56,7 @@ impl<T> CwdRequest<T> {
 pub(super) struct ToolRun {
     tool: &'static str,
     start: Instant,
+    progress: Option<renderdog::QRenderDocProgressHandler>,
 }
 
 impl ToolRun {
@@ -62,31 +68,71 @@ impl ToolRun {
         Self {
             tool,
             start: Instant::now(),
+            progress: None,
         }
     }
 
-    pub(super) fn with_install<T, E, F>(&self, action: &'static str, op: F) -> Result<T, String>
+    /// Reports qrenderdoc job progress to the client, if it asked for progress notifications.
+    pub(super) fn with_progress(mut self, meta: &Meta, peer: &Peer<RoleServer>) -> Self {
+        self.progress = progress::forward_progress(meta, peer);
+        self
+    }
+
+    pub(super) async fn with_install<T, E, F>(
+        &self,
+        action: &'static str,
+        op: F,
+    ) -> Result<T, String>
     where
-        E: Display,
-        F: FnOnce(&renderdog::RenderDocInstallation) -> Result<T, E>,
+        T: Send + 'static,
+        E: Error + Send + 'static,
+        F: FnOnce(&renderdog::RenderDocInstallation) -> Result<T, E> + Send + 'static,
     {
         let install = require_installation(self.tool)?;
-        self.result(action, op(&install))
+        let result = self.run_blocking(install, op).await?;
+        self.result(action, result)
     }
 
-    pub(super) fn with_install_and_cwd<Req, T, E, F>(
+    pub(super) async fn with_install_and_cwd<Req, T, E, F>(
         &self,
         action: &'static str,
         req: CwdRequest<Req>,
         op: F,
     ) -> Result<T, String>
     where
-        E: Display,
-        F: FnOnce(&renderdog::RenderDocInstallation, PathBuf, Req) -> Result<T, E>,
+        Req: Send + 'static,
+        T: Send + 'static,
+        E: Error + Send + 'static,
+        F: FnOnce(&renderdog::RenderDocInstallation, PathBuf, Req) -> Result<T, E> + Send + 'static,
     {
         let install = require_installation(self.tool)?;
         let (cwd, req) = req.into_parts()?;
-        self.result(action, op(&install, cwd, req))
+        let result = self
+            .run_blocking(install, move |install| op(install, cwd, req))
+            .await?;
+        self.result(action, result)
+    }
+
+    /// Runs blocking RenderDoc work off the async runtime, so progress notifications and other
+    /// requests keep flowing while a job runs.
+    async fn run_blocking<T, F>(
+        &self,
+        install: renderdog::RenderDocInstallation,
+        op: F,
+    ) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&renderdog::RenderDocInstallation) -> T + Send + 'static,
+    {
+        let progress = self.progress.clone();
+        let task = tokio::task::spawn_blocking(move || match progress {
+            Some(handler) => install.with_job_progress(&handler, op),
+            None => op(&install),
+        });
+        task.await.map_err(|err| {
+            tracing::error!(tool = self.tool, err = %err, "job task failed");
+            format!("{} did not finish: {err}", self.tool)
+        })
     }
 
     pub(super) fn elapsed_ms(&self) -> u128 {
@@ -95,7 +141,7 @@ impl ToolRun {
 
     fn result<T, E>(&self, action: &'static str, result: Result<T, E>) -> Result<T, String>
     where
-        E: Display,
+        E: Error + 'static,
     {
         tool_result(self.tool, action, result)
     }
@@ -107,15 +153,60 @@ pub(super) fn tool_result<T, E>(
     result: Result<T, E>,
 ) -> Result<T, String>
 where
-    E: Display,
+    E: Error + 'static,
 {
     result.map_err(|err| {
         tracing::error!(tool = tool, action = action, "failed");
         tracing::debug!(tool = tool, action = action, err = %err, "details");
-        format!("{action} failed: {err}")
+        let mut message = format!("{action} failed: {err}");
+        if let Some(script_error) = find_script_error(&err) {
+            if let Some(hint) = script_error_hint(script_error) {
+                message.push('\n');
+                message.push_str(hint);
+            }
+            if let Some(traceback) = script_error.traceback() {
+                message.push_str("\n\nPython traceback:\n");
+                message.push_str(traceback);
+            }
+        }
+        message
     })
 }
 
+fn find_script_error<'a>(
+    err: &'a (dyn Error + 'static),
+) -> Option<&'a renderdog::QRenderDocScriptError> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(script_error) = err.downcast_ref() {
+            return Some(script_error);
+        }
+        current = err.source();
+    }
+    None
+}
+
+/// Which tool gets the caller unstuck, for script errors whose message can't name one.
+fn script_error_hint(err: &renderdog::QRenderDocScriptError) -> Option<&'static str> {
+    use renderdog::QRenderDocScriptError as E;
+
+    match err {
+        E::EventOutOfRange { .. } => Some(
+            "Pick an event_id from renderdoc_find_events or renderdoc_export_bundle_jsonl output.",
+        ),
+        E::TextureIndexOutOfRange { .. } => {
+            Some("Call renderdoc_replay_list_textures to see the valid texture indices.")
+        }
+        E::ResourceNotFound { .. } => Some(
+            "Call renderdoc_replay_list_resources to see the capture's resource ids and names.",
+        ),
+        E::PythonException { .. } => Some(
+            "The qrenderdoc script failed unexpectedly; the traceback below shows where. Check renderdoc_diagnose_environment if the RenderDoc installation looks broken.",
+        ),
+        _ => None,
+    }
+}
+
 pub(super) fn require_installation(
     tool: &'static str,
 ) -> Result<renderdog::RenderDocInstallation, String> {
@@ -0,0 +1,117 @@
+use rmcp::{
+    Peer, RoleServer,
+    model::{Meta, ProgressNotificationParam},
+};
+
+use renderdog_automation as renderdog;
+
+/// Maps per-stage job progress onto one increasing MCP progress value.
+#[derive(Default)]
+struct ProgressTrack {
+    stage: String,
+    offset: u64,
+    stage_extent: u64,
+}
+
+impl ProgressTrack {
+    fn notification(
+        &mut self,
+        update: &renderdog::QRenderDocJobProgress,
+    ) -> (f64, Option<f64>, String) {
+        if update.stage != self.stage {
+            self.offset += self.stage_extent;
+            self.stage = update.stage.clone();
+            self.stage_extent = 0;
+        }
+        let extent = update.total.unwrap_or(update.current).max(update.current);
+        self.stage_extent = self.stage_extent.max(extent);
+
+        let message = match &update.message {
+            Some(message) => format!("{}: {message}", update.stage),
+            None => match update.total {
+                Some(total) => format!("{} {}/{total}", update.stage, update.current),
+                None => format!("{} {}", update.stage, update.current),
+            },
+        };
+        (
+            (self.offset + update.current) as f64,
+            update.total.map(|total| (self.offset + total) as f64),
+            message,
+        )
+    }
+}
+
+/// Forwards qrenderdoc job progress as `notifications/progress` when the client sent a progress
+/// token with the tool call.
+pub(super) fn forward_progress(
+    meta: &Meta,
+    peer: &Peer<RoleServer>,
+) -> Option<renderdog::QRenderDocProgressHandler> {
+    let progress_token = meta.get_progress_token()?;
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let peer = peer.clone();
+
+    // Jobs run under spawn_blocking; a separate task keeps notifications ordered and non-blocking.
+    tokio::spawn(async move {
+        let mut track = ProgressTrack::default();
+        while let Some(update) = receiver.recv().await {
+            let (progress, total, message) = track.notification(&update);
+            let param = ProgressNotificationParam {
+                progress_token: progress_token.clone(),
+                progress,
+                total,
+                message: Some(message),
+            };
+            if let Err(err) = peer.notify_progress(param).await {
+                tracing::debug!(err = %err, "failed to send progress notification");
+                break;
+            }
+        }
+    });
+
+    Some(renderdog::QRenderDocProgressHandler::new(
+        move |update: &renderdog::QRenderDocJobProgress| {
+            let _ = sender.send(update.clone());
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProgressTrack;
+    use renderdog_automation::QRenderDocJobProgress;
+
+    fn update(stage: &str, current: u64, total: Option<u64>) -> QRenderDocJobProgress {
+        QRenderDocJobProgress {
+            stage: stage.to_string(),
+            current,
+            total,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn progress_track_keeps_progress_increasing_across_stages() {
+        let mut track = ProgressTrack::default();
+
+        assert_eq!(
+            track.notification(&update("open_capture", 1, Some(1))),
+            (1.0, Some(1.0), "open_capture 1/1".to_string())
+        );
+        assert_eq!(
+            track.notification(&update("actions", 40, Some(100))),
+            (41.0, Some(101.0), "actions 40/100".to_string())
+        );
+        assert_eq!(
+            track.notification(&update("actions", 100, Some(100))),
+            (101.0, Some(101.0), "actions 100/100".to_string())
+        );
+
+        let mut with_message = update("save_outputs", 2, None);
+        with_message.message = Some("event 42".to_string());
+        assert_eq!(
+            track.notification(&with_message),
+            (103.0, None, "save_outputs: event 42".to_string())
+        );
+    }
+}
@@ -22,9 +22,11 @@ impl RenderdogMcpServer {
                 "start"
             );
         });
-        let res = run.with_install_and_cwd("replay list textures", req, |install, cwd, req| {
-            install.replay_list_textures(&cwd, &req)
-        })?;
+        let res = run
+            .with_install_and_cwd("replay list textures", req, |install, cwd, req| {
+                install.replay_list_textures(&cwd, &req)
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -35,6 +37,35 @@ impl RenderdogMcpServer {
         Ok(Json(res))
     }
 
+    #[tool(
+        name = "renderdoc_replay_list_resources",
+        description = "List every resource in a .rdc capture (textures, buffers, shaders, pipelines, samplers, ...) with name, resource_type, format, usage flags, byte size, texture dimensions, and parent/derived resource ids. Filter with name_contains, name_regex, and resource_types (case-insensitive prefixes); max_results defaults to 200."
+    )]
+    async fn replay_list_resources(
+        &self,
+        Parameters(req): Parameters<CwdRequest<renderdog::ReplayListResourcesRequest>>,
+    ) -> Result<Json<renderdog::ReplayListResourcesResponse>, String> {
+        let tool = "renderdoc_replay_list_resources";
+        let run = ToolRun::start(tool, || {
+            tracing::info!(tool = tool, capture_path = %req.inner.capture.capture_path, "start");
+        });
+        let res = run
+            .with_install_and_cwd("replay list resources", req, |install, cwd, req| {
+                install.replay_list_resources(&cwd, &req)
+            })
+            .await?;
+
+        tracing::info!(
+            tool = tool,
+            elapsed_ms = run.elapsed_ms(),
+            total_resources = res.total_resources,
+            total_matches = res.total_matches,
+            truncated = res.truncated,
+            "ok"
+        );
+        Ok(Json(res))
+    }
+
     #[tool(
         name = "renderdoc_replay_pick_pixel",
         description = "Pick a pixel from a texture in a .rdc capture via `qrenderdoc --python` replay."
@@ -54,9 +85,11 @@ impl RenderdogMcpServer {
                 "start"
             );
         });
-        let res = run.with_install_and_cwd("replay pick pixel", req, |install, cwd, req| {
-            install.replay_pick_pixel(&cwd, &req)
-        })?;
+        let res = run
+            .with_install_and_cwd("replay pick pixel", req, |install, cwd, req| {
+                install.replay_pick_pixel(&cwd, &req)
+            })
+            .await?;
 
         tracing::info!(tool = tool, elapsed_ms = run.elapsed_ms(), "ok");
         Ok(Json(res))
@@ -79,10 +112,11 @@ impl RenderdogMcpServer {
                 "start"
             );
         });
-        let res =
-            run.with_install_and_cwd("replay save texture PNG", req, |install, cwd, req| {
+        let res = run
+            .with_install_and_cwd("replay save texture PNG", req, |install, cwd, req| {
                 install.replay_save_texture_png(&cwd, &req)
-            })?;
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
@@ -111,10 +145,11 @@ impl RenderdogMcpServer {
                 "start"
             );
         });
-        let res =
-            run.with_install_and_cwd("replay save outputs PNG", req, |install, cwd, req| {
+        let res = run
+            .with_install_and_cwd("replay save outputs PNG", req, |install, cwd, req| {
                 install.replay_save_outputs_png(&cwd, &req)
-            })?;
+            })
+            .await?;
 
         tracing::info!(
             tool = tool,
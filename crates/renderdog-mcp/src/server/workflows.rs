@@ -1,4 +1,6 @@
-use rmcp::{Json, handler::server::wrapper::Parameters, tool, tool_router};
+use rmcp::{
+    Json, Peer, RoleServer, handler::server::wrapper::Parameters, model::Meta, tool, tool_router,
+};
 
 use renderdog_automation as renderdog;
 
@@ -8,11 +10,13 @@ use super::{CwdRequest, RenderdogMcpServer, ToolRun};
 impl RenderdogMcpServer {
     #[tool(
         name = "renderdoc_capture_and_export_bundle_jsonl",
-        description = "One-shot workflow: start target under renderdoccmd capture, trigger capture via target control, then export <basename>.actions.jsonl (+ summary) and <basename>.bindings.jsonl (+ bindings_summary)."
+        description = "One-shot workflow: start target under renderdoccmd capture, trigger capture via target control, then export every captured frame to <basename>.actions.jsonl (+ summary) and <basename>.bindings.jsonl (+ bindings_summary)."
     )]
     async fn capture_and_export_bundle_tool(
         &self,
         Parameters(req): Parameters<CwdRequest<renderdog::CaptureAndExportBundleRequest>>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
     ) -> Result<Json<renderdog::CaptureAndExportBundleResponse>, String> {
         let tool = "renderdoc_capture_and_export_bundle_jsonl";
         let run = ToolRun::start(tool, || {
@@ -22,24 +26,36 @@ impl RenderdogMcpServer {
                 args_len = req.inner.target.args.len(),
                 "start"
             );
-        });
-        let res = run.with_install_and_cwd(
-            "one-shot capture/export bundle",
-            req,
-            |install, cwd, req| install.capture_and_export_bundle(&cwd, &req),
-        )?;
+        })
+        .with_progress(&meta, &peer);
+        let res = run
+            .with_install_and_cwd(
+                "one-shot capture/export bundle",
+                req,
+                |install, cwd, req| install.capture_and_export_bundle(&cwd, &req),
+            )
+            .await?;
 
         tracing::info!(
             tool = tool,
             elapsed_ms = run.elapsed_ms(),
             target_ident = res.launch.target.target_ident,
-            capture_path = %res.capture.capture_path,
-            actions_jsonl_path = %res.artifacts.actions.actions_jsonl_path,
-            bindings_jsonl_path = %res.artifacts.bindings.bindings_jsonl_path,
-            total_actions = res.artifacts.actions.total_actions,
-            total_drawcalls = res.artifacts.bindings.total_drawcalls,
+            captures = res.captures.len(),
+            target_exited = res.target_exit.as_ref().map(|exit| exit.exited),
             "ok"
         );
+        for capture in &res.captures {
+            tracing::debug!(
+                tool = tool,
+                capture_path = %capture.capture.capture.capture_path,
+                frame_number = capture.capture.frame_number,
+                actions_jsonl_path = %capture.artifacts.actions.actions_jsonl_path,
+                bindings_jsonl_path = %capture.artifacts.bindings.bindings_jsonl_path,
+                total_actions = capture.artifacts.actions.total_actions,
+                total_drawcalls = capture.artifacts.bindings.total_drawcalls,
+                "exported capture"
+            );
+        }
 
         Ok(Json(res))
     }
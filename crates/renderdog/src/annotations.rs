@@ -57,6 +57,17 @@ impl<T> AnnotationVector<T>
 where
     T: Copy + Default,
 {
+    /// Builds a vector from one to four elements; returns `None` for any other length.
+    pub fn from_slice(values: &[T]) -> Option<Self> {
+        match *values {
+            [a] => Some(Self::D1([a])),
+            [a, b] => Some(Self::D2([a, b])),
+            [a, b, c] => Some(Self::D3([a, b, c])),
+            [a, b, c, d] => Some(Self::D4([a, b, c, d])),
+            _ => None,
+        }
+    }
+
     fn width(self) -> u32 {
         match self {
             Self::D1(_) => 1,
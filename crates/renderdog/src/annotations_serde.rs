@@ -0,0 +1,813 @@
+use serde::ser::{self, Serialize};
+use thiserror::Error;
+
+use crate::{AnnotationValue, AnnotationVector};
+
+/// Annotation value that owns its string payload, produced by [`annotation_entries`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OwnedAnnotationValue {
+    Value(AnnotationValue<'static>),
+    String(String),
+}
+
+impl OwnedAnnotationValue {
+    pub fn as_value(&self) -> AnnotationValue<'_> {
+        match self {
+            Self::Value(value) => *value,
+            Self::String(value) => AnnotationValue::String(value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationEntry {
+    pub key: String,
+    pub value: OwnedAnnotationValue,
+}
+
+#[derive(Debug, Error)]
+pub enum AnnotationSerializeError {
+    #[error("annotation key is empty; pass a prefix when serializing a scalar value")]
+    EmptyKey,
+
+    #[error("unsupported map key type for annotations: {0}")]
+    UnsupportedMapKey(&'static str),
+
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl ser::Error for AnnotationSerializeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Flattens any `Serialize` value into dotted annotation keys.
+///
+/// Struct fields and map entries become `prefix.field`, sequence elements become `prefix[i]`.
+/// Sequences of one to four numbers or bools of the same type are emitted as a single vector
+/// annotation instead (e.g. `[f32; 3]` becomes a `Float32Vector`). `None` and unit values map
+/// to [`AnnotationValue::Empty`], which removes an existing annotation in RenderDoc.
+pub fn annotation_entries<T>(
+    prefix: &str,
+    value: &T,
+) -> Result<Vec<AnnotationEntry>, AnnotationSerializeError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = Vec::new();
+    value.serialize(EntrySerializer {
+        key: prefix.to_string(),
+        out: &mut out,
+    })?;
+    Ok(out)
+}
+
+fn child_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+fn index_key(prefix: &str, index: usize) -> String {
+    format!("{prefix}[{index}]")
+}
+
+struct EntrySerializer<'a> {
+    key: String,
+    out: &'a mut Vec<AnnotationEntry>,
+}
+
+impl EntrySerializer<'_> {
+    fn push(self, value: OwnedAnnotationValue) -> Result<(), AnnotationSerializeError> {
+        if self.key.is_empty() {
+            return Err(AnnotationSerializeError::EmptyKey);
+        }
+        self.out.push(AnnotationEntry {
+            key: self.key,
+            value,
+        });
+        Ok(())
+    }
+
+    fn push_value(self, value: AnnotationValue<'static>) -> Result<(), AnnotationSerializeError> {
+        self.push(OwnedAnnotationValue::Value(value))
+    }
+}
+
+impl<'a> ser::Serializer for EntrySerializer<'a> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = SeqSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Self::Error> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Self::Error> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Int32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Int64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Self::Error> {
+        self.serialize_u32(u32::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Self::Error> {
+        self.serialize_u32(u32::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::UInt32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::UInt64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Float32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Float64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Self::Error> {
+        self.push(OwnedAnnotationValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Self::Error> {
+        self.push(OwnedAnnotationValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
+        let mut seq = SeqSerializer::new(self.key, self.out);
+        for byte in v {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Empty)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        self.push_value(AnnotationValue::Empty)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(EntrySerializer {
+            key: child_key(&self.key, variant),
+            out: self.out,
+        })
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqSerializer::new(self.key, self.out))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SeqSerializer::new(self.key, self.out))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(SeqSerializer::new(self.key, self.out))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SeqSerializer::new(child_key(&self.key, variant), self.out))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapSerializer::new(self.key, self.out))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(MapSerializer::new(self.key, self.out))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(MapSerializer::new(child_key(&self.key, variant), self.out))
+    }
+}
+
+struct SeqSerializer<'a> {
+    key: String,
+    out: &'a mut Vec<AnnotationEntry>,
+    entries: Vec<AnnotationEntry>,
+    len: usize,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn new(key: String, out: &'a mut Vec<AnnotationEntry>) -> Self {
+        Self {
+            key,
+            out,
+            entries: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn push_element<T>(&mut self, value: &T) -> Result<(), AnnotationSerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(EntrySerializer {
+            key: index_key(&self.key, self.len),
+            out: &mut self.entries,
+        })?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), AnnotationSerializeError> {
+        let is_flat = self.entries.len() == self.len
+            && self
+                .entries
+                .iter()
+                .enumerate()
+                .all(|(i, entry)| entry.key == index_key(&self.key, i));
+        if is_flat && !self.key.is_empty() {
+            let scalars: Option<Vec<_>> = self
+                .entries
+                .iter()
+                .map(|entry| match entry.value {
+                    OwnedAnnotationValue::Value(value) => Some(value),
+                    OwnedAnnotationValue::String(_) => None,
+                })
+                .collect();
+            if let Some(vector) = scalars.as_deref().and_then(vector_value) {
+                self.out.push(AnnotationEntry {
+                    key: self.key,
+                    value: OwnedAnnotationValue::Value(vector),
+                });
+                return Ok(());
+            }
+        }
+
+        self.out.extend(self.entries);
+        Ok(())
+    }
+}
+
+macro_rules! vector_of {
+    ($values:expr, $scalar:ident, $vector:ident) => {{
+        let items: Option<Vec<_>> = $values
+            .iter()
+            .map(|value| match value {
+                AnnotationValue::$scalar(v) => Some(*v),
+                _ => None,
+            })
+            .collect();
+        items
+            .and_then(|items| AnnotationVector::from_slice(&items))
+            .map(AnnotationValue::$vector)
+    }};
+}
+
+fn vector_value(values: &[AnnotationValue<'static>]) -> Option<AnnotationValue<'static>> {
+    match values.first()? {
+        AnnotationValue::Bool(_) => vector_of!(values, Bool, BoolVector),
+        AnnotationValue::Int32(_) => vector_of!(values, Int32, Int32Vector),
+        AnnotationValue::UInt32(_) => vector_of!(values, UInt32, UInt32Vector),
+        AnnotationValue::Int64(_) => vector_of!(values, Int64, Int64Vector),
+        AnnotationValue::UInt64(_) => vector_of!(values, UInt64, UInt64Vector),
+        AnnotationValue::Float32(_) => vector_of!(values, Float32, Float32Vector),
+        AnnotationValue::Float64(_) => vector_of!(values, Float64, Float64Vector),
+        _ => None,
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+struct MapSerializer<'a> {
+    key: String,
+    out: &'a mut Vec<AnnotationEntry>,
+    pending_key: Option<String>,
+}
+
+impl<'a> MapSerializer<'a> {
+    fn new(key: String, out: &'a mut Vec<AnnotationEntry>) -> Self {
+        Self {
+            key,
+            out,
+            pending_key: None,
+        }
+    }
+
+    fn push_field<T>(&mut self, name: &str, value: &T) -> Result<(), AnnotationSerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(EntrySerializer {
+            key: child_key(&self.key, name),
+            out: self.out,
+        })
+    }
+}
+
+impl ser::SerializeMap for MapSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.pending_key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| ser::Error::custom("serialize_value called before serialize_key"))?;
+        self.push_field(&key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer<'_> {
+    type Ok = ();
+    type Error = AnnotationSerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+struct MapKeySerializer;
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = AnnotationSerializeError;
+    type SerializeSeq = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeTuple = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeTupleStruct = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeTupleVariant = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeMap = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeStruct = ser::Impossible<String, AnnotationSerializeError>;
+    type SerializeStructVariant = ser::Impossible<String, AnnotationSerializeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Self::Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("none"))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<String, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Self::Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(AnnotationSerializeError::UnsupportedMapKey(
+            "newtype variant",
+        ))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey("struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(AnnotationSerializeError::UnsupportedMapKey(
+            "struct variant",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Albedo {
+        r: f32,
+        g: f32,
+        b: f32,
+    }
+
+    #[derive(Serialize)]
+    enum BlendMode {
+        Opaque,
+    }
+
+    #[derive(Serialize)]
+    struct Material {
+        name: String,
+        albedo: Albedo,
+        uv_scale: [f32; 2],
+        two_sided: bool,
+        blend: BlendMode,
+        layers: Vec<&'static str>,
+        lod_bias: Option<i32>,
+        texture_ids: Vec<u64>,
+    }
+
+    fn entry_map(entries: Vec<AnnotationEntry>) -> BTreeMap<String, OwnedAnnotationValue> {
+        entries.into_iter().map(|e| (e.key, e.value)).collect()
+    }
+
+    #[test]
+    fn struct_fields_flatten_to_dotted_keys_with_typed_values() {
+        let material = Material {
+            name: "brick".to_string(),
+            albedo: Albedo {
+                r: 0.5,
+                g: 0.25,
+                b: 1.0,
+            },
+            uv_scale: [2.0, 4.0],
+            two_sided: true,
+            blend: BlendMode::Opaque,
+            layers: vec!["base", "detail"],
+            lod_bias: None,
+            texture_ids: vec![1, 2, 3, 4, 5],
+        };
+
+        let entries = entry_map(annotation_entries("material", &material).expect("serialize"));
+
+        assert_eq!(
+            entries["material.name"],
+            OwnedAnnotationValue::String("brick".to_string())
+        );
+        assert_eq!(
+            entries["material.albedo.r"].as_value(),
+            AnnotationValue::Float32(0.5)
+        );
+        assert_eq!(
+            entries["material.uv_scale"].as_value(),
+            AnnotationValue::Float32Vector(AnnotationVector::D2([2.0, 4.0]))
+        );
+        assert_eq!(
+            entries["material.two_sided"].as_value(),
+            AnnotationValue::Bool(true)
+        );
+        assert_eq!(
+            entries["material.blend"].as_value(),
+            AnnotationValue::String("Opaque")
+        );
+        assert_eq!(
+            entries["material.layers[1]"].as_value(),
+            AnnotationValue::String("detail")
+        );
+        assert_eq!(
+            entries["material.lod_bias"].as_value(),
+            AnnotationValue::Empty
+        );
+        assert_eq!(
+            entries["material.texture_ids[4]"].as_value(),
+            AnnotationValue::UInt64(5)
+        );
+        assert!(!entries.contains_key("material.texture_ids"));
+    }
+
+    #[test]
+    fn nested_sequences_use_index_keys_and_maps_use_string_keys() {
+        let mut value = BTreeMap::new();
+        value.insert(3_u32, vec![(1_u8, "a")]);
+
+        let entries = entry_map(annotation_entries("mesh", &value).expect("serialize"));
+
+        assert_eq!(
+            entries["mesh.3[0][0]"].as_value(),
+            AnnotationValue::UInt32(1)
+        );
+        assert_eq!(
+            entries["mesh.3[0][1]"].as_value(),
+            AnnotationValue::String("a")
+        );
+    }
+
+    #[test]
+    fn top_level_struct_without_prefix_uses_field_names() {
+        let entries = annotation_entries(
+            "",
+            &Albedo {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+            },
+        )
+        .expect("serialize");
+
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["r", "g", "b"]);
+    }
+
+    #[test]
+    fn scalar_without_prefix_is_rejected() {
+        assert!(matches!(
+            annotation_entries("", &1_u32),
+            Err(AnnotationSerializeError::EmptyKey)
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    cell::Cell,
+    cell::{Cell, RefCell},
     ffi::{CStr, CString, c_void},
     path::{Path, PathBuf},
     ptr::NonNull,
@@ -10,7 +10,10 @@ use thiserror::Error;
 
 use renderdog_sys as sys;
 
-use crate::{AnnotationTarget, AnnotationValue, OverlayBits};
+use crate::{
+    AnnotationSerializeError, AnnotationTarget, AnnotationValue, AutoProvenance, OverlayBits,
+    annotation_entries,
+};
 
 #[cfg(unix)]
 use libloading::Library;
@@ -107,12 +110,16 @@ pub enum InAppError {
 
     #[error("RenderDoc returned an unknown annotation status code: {0}")]
     UnknownAnnotationStatus(u32),
+
+    #[error("failed to flatten value into annotations: {0}")]
+    AnnotationSerialize(#[from] AnnotationSerializeError),
 }
 
 pub struct RenderDocInApp {
     api: NonNull<sys::RENDERDOC_API_1_7_0>,
     _guard: LibraryGuard,
     requested_version: sys::RENDERDOC_Version,
+    pub(crate) auto_provenance: RefCell<Option<AutoProvenance>>,
     _not_sync: Cell<()>,
 }
 
@@ -302,6 +309,7 @@ impl RenderDocInApp {
             api,
             _guard: guard,
             requested_version,
+            auto_provenance: RefCell::new(None),
             _not_sync: Cell::new(()),
         })
     }
@@ -317,6 +325,7 @@ impl RenderDocInApp {
             api,
             _guard: LibraryGuard::Unix { _lib: lib },
             requested_version,
+            auto_provenance: RefCell::new(None),
             _not_sync: Cell::new(()),
         })
     }
@@ -337,7 +346,7 @@ impl RenderDocInApp {
         self.supports_api_version(sys::RENDERDOC_Version::eRENDERDOC_API_Version_1_7_0)
     }
 
-    fn require_api_version(
+    pub(crate) fn require_api_version(
         &self,
         feature: &'static str,
         min: sys::RENDERDOC_Version,
@@ -695,6 +704,54 @@ impl RenderDocInApp {
         Self::map_annotation_status(status)
     }
 
+    /// Sets one object annotation per leaf of `value`, keyed below `prefix`.
+    ///
+    /// See [`annotation_entries`] for how keys and value types are derived.
+    pub fn set_object_annotations_from_serde<T>(
+        &self,
+        device: Option<sys::RENDERDOC_DevicePointer>,
+        object: impl Into<AnnotationTarget>,
+        prefix: &str,
+        value: &T,
+    ) -> Result<(), InAppError>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let object = object.into();
+        for entry in annotation_entries(prefix, value)? {
+            self.set_object_annotation(device, object, &entry.key, entry.value.as_value())?;
+        }
+        Ok(())
+    }
+
+    /// Sets one command annotation per leaf of `value`, keyed below `prefix`.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Self::set_command_annotation`].
+    pub unsafe fn set_command_annotations_from_serde<T>(
+        &self,
+        device: Option<sys::RENDERDOC_DevicePointer>,
+        queue_or_command_buffer: *mut c_void,
+        prefix: &str,
+        value: &T,
+    ) -> Result<(), InAppError>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        for entry in annotation_entries(prefix, value)? {
+            unsafe {
+                self.set_command_annotation(
+                    device,
+                    queue_or_command_buffer,
+                    &entry.key,
+                    entry.value.as_value(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn unload_crash_handler(&self) -> Result<(), InAppError> {
         let f = self
             .api()
@@ -734,6 +791,7 @@ impl RenderDocInApp {
             .api()
             .TriggerCapture
             .ok_or(InAppError::MissingFunction("TriggerCapture"))?;
+        self.stamp_finished_captures();
         unsafe { f() };
         Ok(())
     }
@@ -747,6 +805,7 @@ impl RenderDocInApp {
             .api()
             .TriggerMultiFrameCapture
             .ok_or(InAppError::MissingFunction("TriggerMultiFrameCapture"))?;
+        self.stamp_finished_captures();
         unsafe { f(frames) };
         Ok(())
     }
@@ -784,6 +843,9 @@ impl RenderDocInApp {
                 window.unwrap_or(std::ptr::null_mut()),
             )
         };
+        if ok == 1 {
+            self.stamp_finished_captures();
+        }
         Ok(ok == 1)
     }
 
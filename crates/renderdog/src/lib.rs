@@ -5,17 +5,29 @@
 //! - connecting to an injected RenderDoc (Windows),
 //! - dynamically loading the RenderDoc library (Windows/Linux),
 //! - runtime API version negotiation (tries 1.7.0 down to 1.0.0),
-//! - RenderDoc 1.7 object/command annotations when the runtime supports them.
+//! - RenderDoc 1.7 object/command annotations when the runtime supports them, including
+//!   flattening any `serde::Serialize` value into dotted annotation keys,
+//! - retention policies that prune old captures written by long-running sessions,
+//! - build provenance (app version, git SHA, command line) embedded in capture comments,
+//! - a process-wide `Send + Sync` handle ([`SharedRenderDoc`]) for multi-threaded renderers.
 //!
 //! For automation workflows (renderdoccmd/qrenderdoc), see the `renderdog-automation` crate.
 
 mod annotations;
+mod annotations_serde;
 mod in_app;
+mod provenance;
+mod retention;
 mod settings;
+mod shared;
 
 pub use annotations::*;
+pub use annotations_serde::*;
 pub use in_app::*;
+pub use provenance::*;
+pub use retention::*;
 pub use settings::*;
+pub use shared::*;
 
 pub type SysCaptureOption = RENDERDOC_CaptureOption;
 pub type SysInputButton = RENDERDOC_InputButton;
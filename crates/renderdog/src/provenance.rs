@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{InAppError, RenderDocInApp};
+
+/// Top-level key of the JSON object written into capture comments.
+///
+/// `renderdog-automation` looks for this key when reading provenance back out of a capture.
+pub const CAPTURE_PROVENANCE_KEY: &str = "renderdog_provenance";
+
+/// Format version stored next to the provenance fields.
+pub const CAPTURE_PROVENANCE_VERSION: u32 = 1;
+
+/// Build and runtime details identifying which binary produced a capture.
+///
+/// Written as JSON into the capture file comments with
+/// [`RenderDocInApp::set_capture_provenance`] or [`RenderDocInApp::set_auto_capture_provenance`],
+/// so a `.rdc` attached to a bug report can be traced back to the build, command line, and frame
+/// that produced it. `renderdog-automation` reads the same type back out of exported summaries;
+/// every field is optional so captures written by older or newer helpers still parse.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct CaptureProvenance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command_line: Vec<String>,
+    /// Selected environment variables; only the names passed to [`Self::with_env_vars`].
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Copied as-is into every capture by [`RenderDocInApp::set_auto_capture_provenance`]; use
+    /// `capture_index` / `capture_timestamp` to tell auto-stamped captures apart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_number: Option<u64>,
+    /// Index of the capture in RenderDoc's capture list for this process; set by auto provenance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_index: Option<u32>,
+    /// When RenderDoc finished the capture, in seconds since the Unix epoch; set by auto
+    /// provenance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, String>,
+}
+
+impl CaptureProvenance {
+    pub fn new(app_name: impl Into<String>) -> Self {
+        Self {
+            app_name: Some(app_name.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Starts from an app name/version plus the current process command line.
+    ///
+    /// Typically called with `env!("CARGO_PKG_NAME")` and `env!("CARGO_PKG_VERSION")` from the
+    /// application crate.
+    pub fn for_current_process(app_name: &str, app_version: &str) -> Self {
+        Self::new(app_name)
+            .with_app_version(app_version)
+            .with_process_command_line()
+    }
+
+    pub fn with_app_version(mut self, version: impl Into<String>) -> Self {
+        self.app_version = Some(version.into());
+        self
+    }
+
+    pub fn with_git_sha(mut self, sha: impl Into<String>) -> Self {
+        self.git_sha = Some(sha.into());
+        self
+    }
+
+    pub fn with_command_line<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.command_line = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_process_command_line(self) -> Self {
+        self.with_command_line(std::env::args_os().map(|arg| arg.to_string_lossy().into_owned()))
+    }
+
+    /// Records the current values of `names`; variables that are unset are skipped.
+    pub fn with_env_vars<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for name in names {
+            let name = name.as_ref();
+            if let Some(value) = std::env::var_os(name) {
+                self.env
+                    .insert(name.to_string(), value.to_string_lossy().into_owned());
+            }
+        }
+        self
+    }
+
+    pub fn with_frame_number(mut self, frame_number: u64) -> Self {
+        self.frame_number = Some(frame_number);
+        self
+    }
+
+    pub fn with_custom(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.custom.insert(key.into(), value.into());
+        self
+    }
+
+    /// Renders the capture comment text: `{"renderdog_provenance": {"version": 1, ...}}`.
+    ///
+    /// Written by hand rather than through `serde_json` so the in-app crate stays free of a JSON
+    /// dependency; only strings, integers, string lists and string maps are involved.
+    pub fn to_comments_json(&self) -> String {
+        let mut fields = vec![("version", CAPTURE_PROVENANCE_VERSION.to_string())];
+        let strings = [
+            ("app_name", &self.app_name),
+            ("app_version", &self.app_version),
+            ("git_sha", &self.git_sha),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                fields.push((key, json_string(value)));
+            }
+        }
+        if !self.command_line.is_empty() {
+            let args: Vec<String> = self
+                .command_line
+                .iter()
+                .map(|arg| json_string(arg))
+                .collect();
+            fields.push(("command_line", format!("[{}]", args.join(", "))));
+        }
+        if !self.env.is_empty() {
+            fields.push(("env", json_string_map(&self.env)));
+        }
+        if let Some(frame_number) = self.frame_number {
+            fields.push(("frame_number", frame_number.to_string()));
+        }
+        if let Some(capture_index) = self.capture_index {
+            fields.push(("capture_index", capture_index.to_string()));
+        }
+        if let Some(capture_timestamp) = self.capture_timestamp {
+            fields.push(("capture_timestamp", capture_timestamp.to_string()));
+        }
+        if !self.custom.is_empty() {
+            fields.push(("custom", json_string_map(&self.custom)));
+        }
+
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("    {}: {value}", json_string(key)))
+            .collect();
+        format!(
+            "{{\n  {}: {{\n{}\n  }}\n}}",
+            json_string(CAPTURE_PROVENANCE_KEY),
+            body.join(",\n")
+        )
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_string_map(map: &BTreeMap<String, String>) -> String {
+    let entries: Vec<String> = map
+        .iter()
+        .map(|(key, value)| format!("{}: {}", json_string(key), json_string(value)))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// State behind [`RenderDocInApp::set_auto_capture_provenance`].
+pub(crate) struct AutoProvenance {
+    provenance: CaptureProvenance,
+    /// Captures below this index have been stamped (or predate the hook).
+    next_capture: u32,
+}
+
+impl RenderDocInApp {
+    /// Writes `provenance` into the comments of `capture_file_path`, or of the most recent
+    /// capture when `None`.
+    ///
+    /// Call this after a capture has finished (after [`Self::end_frame_capture`] returns, or once
+    /// [`Self::get_num_captures`] has grown for triggered captures). Existing comments are
+    /// replaced. [`Self::set_auto_capture_provenance`] does this for every capture.
+    pub fn set_capture_provenance(
+        &self,
+        capture_file_path: Option<&str>,
+        provenance: &CaptureProvenance,
+    ) -> Result<(), InAppError> {
+        self.set_capture_file_comments(capture_file_path, &provenance.to_comments_json())
+    }
+
+    /// Writes `provenance` into every capture made from now on; `None` turns this off.
+    ///
+    /// Each capture gets its own `capture_index` and `capture_timestamp`; the other fields,
+    /// including `frame_number`, are written as given. Captures from [`Self::end_frame_capture`]
+    /// are stamped before it returns. Triggered captures finish on a later present, so they are
+    /// stamped by the next `trigger_capture`, `trigger_multi_frame_capture` or
+    /// `end_frame_capture` call, or explicitly with [`Self::record_capture_provenance`]. Stamping
+    /// from those capture calls is best-effort: a failure never stops or fails the capture call,
+    /// and the capture is retried (and the error returned) by the next
+    /// [`Self::record_capture_provenance`]. Captures that already exist are left alone.
+    pub fn set_auto_capture_provenance(
+        &self,
+        provenance: Option<&CaptureProvenance>,
+    ) -> Result<(), InAppError> {
+        let auto = match provenance {
+            Some(provenance) => {
+                self.require_api_version(
+                    "SetCaptureFileComments",
+                    renderdog_sys::RENDERDOC_Version::eRENDERDOC_API_Version_1_2_0,
+                )?;
+                Some(AutoProvenance {
+                    provenance: provenance.clone(),
+                    next_capture: self.get_num_captures()?,
+                })
+            }
+            None => None,
+        };
+        *self.auto_provenance.borrow_mut() = auto;
+        Ok(())
+    }
+
+    /// Stamps captures finished since the last call with the provenance from
+    /// [`Self::set_auto_capture_provenance`], returning how many were written.
+    pub fn record_capture_provenance(&self) -> Result<u32, InAppError> {
+        let mut auto = self.auto_provenance.borrow_mut();
+        let Some(auto) = auto.as_mut() else {
+            return Ok(0);
+        };
+
+        let num_captures = self.get_num_captures()?;
+        let mut stamped = 0;
+        while auto.next_capture < num_captures {
+            let (path, timestamp) = self.get_capture(auto.next_capture)?;
+            let provenance = CaptureProvenance {
+                capture_index: Some(auto.next_capture),
+                capture_timestamp: Some(timestamp),
+                ..auto.provenance.clone()
+            };
+            self.set_capture_file_comments(Some(&path), &provenance.to_comments_json())?;
+            auto.next_capture += 1;
+            stamped += 1;
+        }
+        Ok(stamped)
+    }
+
+    /// Auto-stamping done on behalf of a capture call, which must not gate or mask the capture.
+    pub(crate) fn stamp_finished_captures(&self) {
+        let _ = self.record_capture_provenance();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{CAPTURE_PROVENANCE_KEY, CaptureProvenance};
+
+    #[test]
+    fn comments_json_wraps_fields_under_versioned_key() {
+        let provenance = CaptureProvenance::new("viewer")
+            .with_app_version("1.2.3")
+            .with_git_sha("abc123")
+            .with_command_line(["viewer", "--scene", "demo"])
+            .with_frame_number(42)
+            .with_custom("gpu_preset", "high");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&provenance.to_comments_json()).expect("valid JSON");
+        let inner = &json[CAPTURE_PROVENANCE_KEY];
+
+        assert_eq!(inner["version"], 1);
+        assert_eq!(inner["app_name"], "viewer");
+        assert_eq!(inner["app_version"], "1.2.3");
+        assert_eq!(inner["git_sha"], "abc123");
+        assert_eq!(inner["command_line"][1], "--scene");
+        assert_eq!(inner["frame_number"], 42);
+        assert_eq!(inner["custom"]["gpu_preset"], "high");
+    }
+
+    #[test]
+    fn comments_json_escapes_strings_and_round_trips() {
+        // No `..Default::default()`: a new field must be added here, and then round-trips only
+        // if `to_comments_json` writes it too.
+        let provenance = CaptureProvenance {
+            app_name: Some("viewer \"dev\"".into()),
+            app_version: Some("1.2.3-beta+\\local".into()),
+            git_sha: Some("abc123".into()),
+            command_line: vec!["C:\\game\\viewer.exe".into(), "--title=a\tb\n".into()],
+            env: BTreeMap::from([("RUST_LOG".into(), "info,wgpu=\"warn\"".into())]),
+            frame_number: Some(u64::MAX),
+            capture_index: Some(3),
+            capture_timestamp: Some(1_700_000_000),
+            custom: BTreeMap::from([("note".into(), "\u{1}".into())]),
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&provenance.to_comments_json()).expect("valid JSON");
+        let parsed: CaptureProvenance =
+            serde_json::from_value(json[CAPTURE_PROVENANCE_KEY].clone()).expect("parse back");
+
+        assert_eq!(parsed, provenance);
+    }
+
+    #[test]
+    fn empty_fields_are_omitted_and_unset_env_vars_skipped() {
+        let provenance = CaptureProvenance::new("viewer")
+            .with_env_vars(["RENDERDOG_PROVENANCE_TEST_UNSET_VARIABLE"]);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&provenance.to_comments_json()).expect("valid JSON");
+        let inner = json[CAPTURE_PROVENANCE_KEY]
+            .as_object()
+            .expect("provenance object");
+
+        assert!(provenance.env.is_empty());
+        assert_eq!(
+            inner.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["app_name", "version"]
+        );
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use thiserror::Error;
+
+use crate::{InAppError, RenderDocInApp};
+
+/// Source of in-app capture metadata used by [`CaptureRetentionPolicy`].
+///
+/// Implemented by [`RenderDocInApp`]; tests and tools can provide their own backend.
+pub trait CaptureInventory {
+    fn get_num_captures(&self) -> Result<u32, InAppError>;
+
+    fn get_capture_info_opt(&self, idx: u32) -> Result<Option<(PathBuf, SystemTime)>, InAppError>;
+
+    fn get_capture_file_path_template_path(&self) -> Result<PathBuf, InAppError>;
+}
+
+impl CaptureInventory for RenderDocInApp {
+    fn get_num_captures(&self) -> Result<u32, InAppError> {
+        RenderDocInApp::get_num_captures(self)
+    }
+
+    fn get_capture_info_opt(&self, idx: u32) -> Result<Option<(PathBuf, SystemTime)>, InAppError> {
+        RenderDocInApp::get_capture_info_opt(self, idx)
+    }
+
+    fn get_capture_file_path_template_path(&self) -> Result<PathBuf, InAppError> {
+        RenderDocInApp::get_capture_file_path_template_path(self)
+    }
+}
+
+/// Which limits to enforce on captures written under the capture file path template.
+///
+/// All limits are optional and applied together; a capture is removed as soon as it violates
+/// any of them. The newest captures are always preferred when deciding what to keep.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CaptureRetentionPolicy {
+    /// Keep at most this many captures.
+    pub keep_last: Option<usize>,
+    /// Keep the total size of retained captures at or below this many bytes.
+    pub max_total_bytes: Option<u64>,
+    /// Remove captures older than this.
+    pub max_age: Option<Duration>,
+}
+
+/// Why a capture was selected for removal.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RetentionReason {
+    KeepLast,
+    MaxAge,
+    MaxTotalBytes,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CaptureFileInfo {
+    pub path: PathBuf,
+    pub timestamp: SystemTime,
+    pub byte_size: u64,
+    /// Whether RenderDoc reported this capture through `GetCapture` in the current session.
+    pub known_to_renderdoc: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemovedCapture {
+    pub capture: CaptureFileInfo,
+    pub reason: RetentionReason,
+}
+
+#[derive(Debug)]
+pub struct FailedCaptureRemoval {
+    pub capture: CaptureFileInfo,
+    pub reason: RetentionReason,
+    pub error: std::io::Error,
+}
+
+/// Result of applying a [`CaptureRetentionPolicy`], newest captures first.
+#[derive(Debug, Default)]
+pub struct CaptureRetentionReport {
+    pub kept: Vec<CaptureFileInfo>,
+    pub removed: Vec<RemovedCapture>,
+    pub failed: Vec<FailedCaptureRemoval>,
+}
+
+impl CaptureRetentionReport {
+    pub fn bytes_freed(&self) -> u64 {
+        self.removed.iter().map(|r| r.capture.byte_size).sum()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CaptureRetentionError {
+    #[error(transparent)]
+    InApp(#[from] InAppError),
+
+    #[error("capture file path template is empty")]
+    EmptyTemplate,
+
+    #[error("failed to read capture directory {path}: {source}")]
+    ReadDir {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl CaptureRetentionPolicy {
+    pub fn is_unbounded(&self) -> bool {
+        self.keep_last.is_none() && self.max_total_bytes.is_none() && self.max_age.is_none()
+    }
+
+    /// Lists captures and decides which to keep, without touching the filesystem.
+    ///
+    /// Returns `(kept, removed)`, both ordered newest first.
+    pub fn plan(
+        &self,
+        inventory: &impl CaptureInventory,
+        now: SystemTime,
+    ) -> Result<(Vec<CaptureFileInfo>, Vec<RemovedCapture>), CaptureRetentionError> {
+        let captures = collect_captures(inventory)?;
+        Ok(self.partition(captures, now))
+    }
+
+    /// Deletes captures that violate the policy and reports what was removed.
+    pub fn enforce(
+        &self,
+        inventory: &impl CaptureInventory,
+    ) -> Result<CaptureRetentionReport, CaptureRetentionError> {
+        self.enforce_at(inventory, SystemTime::now())
+    }
+
+    pub fn enforce_at(
+        &self,
+        inventory: &impl CaptureInventory,
+        now: SystemTime,
+    ) -> Result<CaptureRetentionReport, CaptureRetentionError> {
+        let (kept, planned) = self.plan(inventory, now)?;
+
+        let mut report = CaptureRetentionReport {
+            kept,
+            ..CaptureRetentionReport::default()
+        };
+        for removal in planned {
+            match remove_file_if_exists(&removal.capture.path) {
+                Ok(()) => report.removed.push(removal),
+                Err(error) => report.failed.push(FailedCaptureRemoval {
+                    capture: removal.capture,
+                    reason: removal.reason,
+                    error,
+                }),
+            }
+        }
+        Ok(report)
+    }
+
+    fn partition(
+        &self,
+        mut captures: Vec<CaptureFileInfo>,
+        now: SystemTime,
+    ) -> (Vec<CaptureFileInfo>, Vec<RemovedCapture>) {
+        captures.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.path.cmp(&a.path)));
+
+        let mut kept = Vec::new();
+        let mut removed = Vec::new();
+        let mut total_bytes: u64 = 0;
+
+        for capture in captures {
+            let age = now.duration_since(capture.timestamp).unwrap_or_default();
+            let reason = if self.keep_last.is_some_and(|n| kept.len() >= n) {
+                Some(RetentionReason::KeepLast)
+            } else if self.max_age.is_some_and(|max| age > max) {
+                Some(RetentionReason::MaxAge)
+            } else if self
+                .max_total_bytes
+                .is_some_and(|max| total_bytes.saturating_add(capture.byte_size) > max)
+            {
+                Some(RetentionReason::MaxTotalBytes)
+            } else {
+                None
+            };
+
+            match reason {
+                Some(reason) => removed.push(RemovedCapture { capture, reason }),
+                None => {
+                    total_bytes = total_bytes.saturating_add(capture.byte_size);
+                    kept.push(capture);
+                }
+            }
+        }
+
+        (kept, removed)
+    }
+}
+
+impl RenderDocInApp {
+    /// Applies `policy` to the captures of this process and the capture template directory.
+    pub fn enforce_capture_retention(
+        &self,
+        policy: &CaptureRetentionPolicy,
+    ) -> Result<CaptureRetentionReport, CaptureRetentionError> {
+        policy.enforce(self)
+    }
+}
+
+fn collect_captures(
+    inventory: &impl CaptureInventory,
+) -> Result<Vec<CaptureFileInfo>, CaptureRetentionError> {
+    let mut by_path = BTreeMap::new();
+
+    for idx in 0..inventory.get_num_captures()? {
+        let Some((path, timestamp)) = inventory.get_capture_info_opt(idx)? else {
+            break;
+        };
+        // Captures that were already deleted (e.g. by an earlier retention pass) are skipped.
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        by_path.insert(
+            path.clone(),
+            CaptureFileInfo {
+                path,
+                timestamp,
+                byte_size: metadata.len(),
+                known_to_renderdoc: true,
+            },
+        );
+    }
+
+    let template = inventory.get_capture_file_path_template_path()?;
+    for capture in scan_template_dir(&template)? {
+        by_path.entry(capture.path.clone()).or_insert(capture);
+    }
+
+    Ok(by_path.into_values().collect())
+}
+
+/// Finds `.rdc` files next to the capture template whose names start with the template's
+/// file name followed by `_`, which is how RenderDoc names captures
+/// (`<template>_<YYYY.MM.DD_HH.MM.SS>_frame<N>.rdc`). The separator keeps a `game` template from
+/// matching `gameplay_*.rdc`.
+fn scan_template_dir(template: &Path) -> Result<Vec<CaptureFileInfo>, CaptureRetentionError> {
+    let prefix = template
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.is_empty())
+        .map(|name| format!("{name}_"))
+        .ok_or(CaptureRetentionError::EmptyTemplate)?;
+    let dir = match template.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(CaptureRetentionError::ReadDir {
+                path: dir.to_path_buf(),
+                source,
+            });
+        }
+    };
+
+    let mut out = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|source| CaptureRetentionError::ReadDir {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        let path = entry.path();
+        let is_capture = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".rdc"));
+        if !is_capture {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        out.push(CaptureFileInfo {
+            path,
+            timestamp: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            byte_size: metadata.len(),
+            known_to_renderdoc: false,
+        });
+    }
+    Ok(out)
+}
+
+fn remove_file_if_exists(path: &Path) -> Result<(), std::io::Error> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    struct FakeInventory {
+        template: PathBuf,
+        captures: Vec<(PathBuf, SystemTime)>,
+    }
+
+    impl CaptureInventory for FakeInventory {
+        fn get_num_captures(&self) -> Result<u32, InAppError> {
+            Ok(self.captures.len() as u32)
+        }
+
+        fn get_capture_info_opt(
+            &self,
+            idx: u32,
+        ) -> Result<Option<(PathBuf, SystemTime)>, InAppError> {
+            Ok(self.captures.get(idx as usize).cloned())
+        }
+
+        fn get_capture_file_path_template_path(&self) -> Result<PathBuf, InAppError> {
+            Ok(self.template.clone())
+        }
+    }
+
+    fn make_temp_dir() -> PathBuf {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "renderdog-retention-test-{}-{unique}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+        dir
+    }
+
+    fn write_capture(dir: &Path, name: &str, bytes: usize, timestamp: SystemTime) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; bytes]).expect("write capture");
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(timestamp))
+            .expect("set mtime");
+        path
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn keep_last_removes_oldest_captures_and_reports_them() {
+        let dir = make_temp_dir();
+        let template = dir.join("game");
+        let a = write_capture(&dir, "game_2026.01.01_12.00.10_frame10.rdc", 10, at(1_000));
+        let b = write_capture(&dir, "game_2026.01.01_12.00.20_frame20.rdc", 10, at(2_000));
+        let c = write_capture(&dir, "game_2026.01.01_12.00.30_frame30.rdc", 10, at(3_000));
+        let unrelated = write_capture(&dir, "other_2026.01.01_12.00.00_frame1.rdc", 10, at(500));
+        let inventory = FakeInventory {
+            template,
+            captures: vec![(b.clone(), at(2_000)), (c.clone(), at(3_000))],
+        };
+
+        let report = CaptureRetentionPolicy {
+            keep_last: Some(2),
+            ..CaptureRetentionPolicy::default()
+        }
+        .enforce_at(&inventory, at(4_000))
+        .expect("enforce retention");
+
+        let kept: Vec<_> = report.kept.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(kept, vec![c.clone(), b.clone()]);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].capture.path, a);
+        assert_eq!(report.removed[0].reason, RetentionReason::KeepLast);
+        assert!(!report.removed[0].capture.known_to_renderdoc);
+        assert_eq!(report.bytes_freed(), 10);
+        assert!(!a.exists());
+        assert!(b.exists() && c.exists() && unrelated.exists());
+
+        std::fs::remove_dir_all(&dir).expect("cleanup should succeed");
+    }
+
+    #[test]
+    fn max_age_and_total_bytes_are_applied_newest_first() {
+        let dir = make_temp_dir();
+        let template = dir.join("game");
+        let old = write_capture(&dir, "game_2026.01.01_12.00.01_frame1.rdc", 5, at(100));
+        let mid = write_capture(&dir, "game_2026.01.01_12.00.02_frame2.rdc", 30, at(900));
+        let new = write_capture(&dir, "game_2026.01.01_12.00.03_frame3.rdc", 20, at(950));
+        let inventory = FakeInventory {
+            template,
+            captures: Vec::new(),
+        };
+        let policy = CaptureRetentionPolicy {
+            keep_last: None,
+            max_total_bytes: Some(40),
+            max_age: Some(Duration::from_secs(500)),
+        };
+
+        let (kept, removed) = policy.plan(&inventory, at(1_000)).expect("plan retention");
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, new);
+        let removed: Vec<_> = removed
+            .iter()
+            .map(|r| (r.capture.path.clone(), r.reason))
+            .collect();
+        assert_eq!(
+            removed,
+            vec![
+                (mid.clone(), RetentionReason::MaxTotalBytes),
+                (old.clone(), RetentionReason::MaxAge),
+            ]
+        );
+        assert!(old.exists() && mid.exists(), "plan must not delete files");
+
+        std::fs::remove_dir_all(&dir).expect("cleanup should succeed");
+    }
+
+    #[test]
+    fn renderdoc_timestamps_take_precedence_over_file_mtime() {
+        let dir = make_temp_dir();
+        let template = dir.join("game");
+        let capture = write_capture(&dir, "game_2026.01.01_12.00.01_frame1.rdc", 1, at(100));
+        let inventory = FakeInventory {
+            template,
+            captures: vec![(capture.clone(), at(990))],
+        };
+
+        let (kept, removed) = CaptureRetentionPolicy {
+            max_age: Some(Duration::from_secs(60)),
+            ..CaptureRetentionPolicy::default()
+        }
+        .plan(&inventory, at(1_000))
+        .expect("plan retention");
+
+        assert!(removed.is_empty());
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].known_to_renderdoc);
+        assert_eq!(kept[0].timestamp, at(990));
+
+        std::fs::remove_dir_all(&dir).expect("cleanup should succeed");
+    }
+
+    #[test]
+    fn template_prefix_requires_separator() {
+        let dir = make_temp_dir();
+        let template = dir.join("game");
+        let own = write_capture(&dir, "game_2026.01.01_12.00.00_frame1.rdc", 1, at(100));
+        let gameplay = write_capture(&dir, "gameplay_2026.01.01_12.00.00_frame1.rdc", 1, at(100));
+        let bare = write_capture(&dir, "game.rdc", 1, at(100));
+        let inventory = FakeInventory {
+            template,
+            captures: Vec::new(),
+        };
+
+        let report = CaptureRetentionPolicy {
+            keep_last: Some(0),
+            ..CaptureRetentionPolicy::default()
+        }
+        .enforce_at(&inventory, at(1_000))
+        .expect("enforce retention");
+
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].capture.path, own);
+        assert!(!own.exists());
+        assert!(gameplay.exists() && bare.exists());
+
+        std::fs::remove_dir_all(&dir).expect("cleanup should succeed");
+    }
+
+    #[test]
+    fn missing_capture_dir_yields_empty_report() {
+        let dir = make_temp_dir();
+        let inventory = FakeInventory {
+            template: dir.join("missing").join("game"),
+            captures: Vec::new(),
+        };
+
+        let report = CaptureRetentionPolicy {
+            keep_last: Some(0),
+            ..CaptureRetentionPolicy::default()
+        }
+        .enforce_at(&inventory, at(1_000))
+        .expect("enforce retention");
+
+        assert!(report.kept.is_empty());
+        assert!(report.removed.is_empty());
+        assert!(report.failed.is_empty());
+
+        std::fs::remove_dir_all(&dir).expect("cleanup should succeed");
+    }
+}
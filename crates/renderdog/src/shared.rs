@@ -0,0 +1,163 @@
+use std::{
+    ops::Deref,
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use renderdog_sys as sys;
+
+use crate::{InAppError, RenderDocInApp};
+
+static GLOBAL: OnceLock<SharedRenderDoc> = OnceLock::new();
+static GLOBAL_INIT: Mutex<()> = Mutex::new(());
+
+struct SendInApp(RenderDocInApp);
+
+// SAFETY: `RenderDocInApp` is only `!Send` because it stores a raw pointer to RenderDoc's
+// process-global API table. The table and the library guard are valid for the whole process
+// regardless of which thread touches them, and `SharedRenderDoc` only hands out access while
+// holding its mutex, so calls are never made from two threads at once.
+unsafe impl Send for SendInApp {}
+
+/// A process-wide RenderDoc handle that can be used from any thread.
+///
+/// [`RenderDocInApp`] is `!Sync` so that a single handle cannot be called concurrently. This
+/// wrapper owns one handle behind a mutex, which makes it `Send + Sync` and serialises every call
+/// into RenderDoc.
+///
+/// # Concurrent captures
+///
+/// Each method locks for the duration of a single RenderDoc call only. Two threads calling
+/// [`Self::start_frame_capture`] and [`Self::end_frame_capture`] independently will interleave
+/// exactly as they would on one thread: RenderDoc keeps one active capture per device/window pair,
+/// so a second start for the same pair while a capture is running is ignored by RenderDoc, and the
+/// first end finishes the capture for whichever thread started it.
+///
+/// To make a capture exclusive, hold the guard returned by [`Self::lock`] from start to end. Other
+/// threads then block on any RenderDoc call until the capture has been written; GPU work they
+/// submit to the captured device in the meantime is still recorded, because RenderDoc captures
+/// per device rather than per thread.
+pub struct SharedRenderDoc {
+    inner: Mutex<SendInApp>,
+}
+
+/// Exclusive access to the handle inside a [`SharedRenderDoc`].
+pub struct SharedRenderDocGuard<'a> {
+    guard: MutexGuard<'a, SendInApp>,
+}
+
+impl Deref for SharedRenderDocGuard<'_> {
+    type Target = RenderDocInApp;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard.0
+    }
+}
+
+impl SharedRenderDoc {
+    /// Wraps an existing handle, e.g. one created with a custom library path.
+    pub fn new(in_app: RenderDocInApp) -> Self {
+        Self {
+            inner: Mutex::new(SendInApp(in_app)),
+        }
+    }
+
+    /// Returns the process-wide handle, connecting on first use.
+    ///
+    /// On Linux this prefers an already-loaded RenderDoc (`try_connect_noload_or_load_default`);
+    /// elsewhere it uses `try_connect_or_load_default`. A failed connection is not cached, so a
+    /// later call retries (for example once RenderDoc has been injected).
+    pub fn global() -> Result<&'static Self, InAppError> {
+        if let Some(shared) = GLOBAL.get() {
+            return Ok(shared);
+        }
+
+        let _init = GLOBAL_INIT.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(shared) = GLOBAL.get() {
+            return Ok(shared);
+        }
+
+        let shared = Self::new(connect_default()?);
+        Ok(GLOBAL.get_or_init(|| shared))
+    }
+
+    /// Returns the process-wide handle only if [`Self::global`] already connected.
+    pub fn get() -> Option<&'static Self> {
+        GLOBAL.get()
+    }
+
+    /// Locks the handle until the returned guard is dropped.
+    ///
+    /// A panic on another thread while holding the lock does not invalidate RenderDoc's state, so
+    /// poisoning is ignored.
+    pub fn lock(&self) -> SharedRenderDocGuard<'_> {
+        SharedRenderDocGuard {
+            guard: self.inner.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the handle.
+    pub fn with<R>(&self, f: impl FnOnce(&RenderDocInApp) -> R) -> R {
+        f(&self.lock())
+    }
+
+    pub fn trigger_capture(&self) -> Result<(), InAppError> {
+        self.with(RenderDocInApp::trigger_capture)
+    }
+
+    pub fn trigger_multi_frame_capture(&self, frames: u32) -> Result<(), InAppError> {
+        self.with(|rd| rd.trigger_multi_frame_capture(frames))
+    }
+
+    pub fn start_frame_capture(
+        &self,
+        device: Option<sys::RENDERDOC_DevicePointer>,
+        window: Option<sys::RENDERDOC_WindowHandle>,
+    ) -> Result<(), InAppError> {
+        self.with(|rd| rd.start_frame_capture(device, window))
+    }
+
+    pub fn end_frame_capture(
+        &self,
+        device: Option<sys::RENDERDOC_DevicePointer>,
+        window: Option<sys::RENDERDOC_WindowHandle>,
+    ) -> Result<bool, InAppError> {
+        self.with(|rd| rd.end_frame_capture(device, window))
+    }
+
+    pub fn is_frame_capturing(&self) -> Result<bool, InAppError> {
+        self.with(RenderDocInApp::is_frame_capturing)
+    }
+
+    pub fn get_num_captures(&self) -> Result<u32, InAppError> {
+        self.with(RenderDocInApp::get_num_captures)
+    }
+}
+
+impl From<RenderDocInApp> for SharedRenderDoc {
+    fn from(value: RenderDocInApp) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(all(unix, target_os = "linux"))]
+fn connect_default() -> Result<RenderDocInApp, InAppError> {
+    RenderDocInApp::try_connect_noload_or_load_default()
+}
+
+#[cfg(not(all(unix, target_os = "linux")))]
+fn connect_default() -> Result<RenderDocInApp, InAppError> {
+    RenderDocInApp::try_connect_or_load_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedRenderDoc;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn shared_handle_is_send_and_sync() {
+        assert_send_sync::<SharedRenderDoc>();
+        assert_send_sync::<&'static SharedRenderDoc>();
+    }
+}